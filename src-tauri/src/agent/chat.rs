@@ -19,6 +19,7 @@ use super::tools::{FetchWebpage, GenerateServerSchema};
 use super::types::{
     ChatMessage, ChatRole, GeneratedSchema, StreamEvent, ToolCallInfo, ToolCallStatus,
 };
+use crate::mcp::redact::redact_text;
 use crate::mcp::server_schema::ServerSchema;
 
 /// Global chat history (simplified single session)
@@ -51,6 +52,10 @@ START WORKING NOW. Don't ask - just do all steps automatically.
 
 /// Send a message to the agent and get a response
 pub async fn send_message(api_key: &str, user_message: &str) -> Result<ChatMessage, String> {
+    // Pasted configs often carry tokens; the model never needs them
+    let redacted_message = redact_text(user_message);
+    let user_message = redacted_message.as_str();
+
     // Set the API key in environment for OpenRouter client
     std::env::set_var("OPENROUTER_API_KEY", api_key);
 
@@ -215,6 +220,10 @@ pub async fn send_message_stream(
     api_key: &str,
     user_message: &str,
 ) -> Result<(), String> {
+    // Pasted configs often carry tokens; the model never needs them
+    let redacted_message = redact_text(user_message);
+    let user_message = redacted_message.as_str();
+
     // Set the API key in environment for OpenRouter client
     std::env::set_var("OPENROUTER_API_KEY", api_key);
 
@@ -0,0 +1,52 @@
+//! Provider API keys sealed at rest
+//!
+//! Keys are stored in `settings.json` sealed with the machine-local key (see
+//! [`crate::mcp::sealed`]). The settings file can then be synced or committed without
+//! exposing the key, and only this module can open it.
+
+use zeroize::Zeroizing;
+
+use crate::config::{load_app_config, save_app_config};
+use crate::mcp::sealed::{self, open};
+
+const SEALED_AAD: &[u8] = b"rainmcp-provider-key-v1";
+
+const NOT_CONFIGURED: &str = "OpenRouter API key not configured. Please add 'openrouter_api_key' to ~/.config/rain-mcp/settings.json";
+
+pub fn is_sealed(value: &str) -> bool {
+    sealed::is_sealed(value)
+}
+
+/// Encrypt `plaintext` for storage in `settings.json`
+pub fn seal(plaintext: &str) -> Result<String, String> {
+    sealed::seal(plaintext, SEALED_AAD)
+}
+
+/// Store the OpenRouter key sealed; an empty key removes it
+pub fn set_openrouter_api_key(api_key: &str) -> Result<(), String> {
+    let mut config = load_app_config();
+    config.openrouter_api_key = if api_key.is_empty() { None } else { Some(seal(api_key)?) };
+    save_app_config(&config)
+}
+
+/// The decrypted OpenRouter key
+pub(super) fn openrouter_api_key() -> Result<Zeroizing<String>, String> {
+    let stored = load_app_config().openrouter_api_key.ok_or(NOT_CONFIGURED)?;
+    if is_sealed(&stored) {
+        open(&stored, SEALED_AAD).map_err(|e| {
+            format!("Cannot decrypt the OpenRouter API key ({}). Please set it again", e)
+        })
+    } else {
+        // Written by hand or by an older version
+        set_openrouter_api_key(&stored)?;
+        Ok(Zeroizing::new(stored))
+    }
+}
+
+/// Seal provider keys still stored in plain text
+pub fn seal_plaintext_keys() -> Result<(), String> {
+    match load_app_config().openrouter_api_key {
+        Some(stored) if !is_sealed(&stored) => set_openrouter_api_key(&stored),
+        _ => Ok(()),
+    }
+}
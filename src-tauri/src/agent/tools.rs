@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use thiserror::Error;
 
+use crate::mcp::redact::redact_text;
 use crate::mcp::server_schema::ServerSchema;
 
 #[derive(Debug, Error)]
@@ -88,7 +89,7 @@ async fn fetch_url_with_jina(url: &str) -> Result<String, ToolError> {
         markdown
     };
 
-    Ok(truncated)
+    Ok(redact_text(&truncated))
 }
 
 /// Tool to generate MCP ServerSchema
@@ -3,7 +3,9 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
-use crate::mcp::user_server::UserServer;
+use crate::mcp::profiles::Profile;
+use crate::mcp::registry::RegistrySource;
+use crate::mcp::user_server::{ServerDeployment, UserServer};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClientConfigItem {
@@ -20,14 +22,60 @@ pub struct AppConfig {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub user_servers: Vec<UserServer>,
 
+    /// Sealed with the machine-local provider key (`enc:v1:...`), see `agent::keys`
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub openrouter_api_key: Option<String>,
+
+    /// Agents each user server was deployed to, keyed by user server id
+    ///
+    /// Deleting a user server keeps the records of copies still in agent configs until
+    /// those copies are removed.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub server_deployments: HashMap<String, Vec<ServerDeployment>>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<Profile>,
+
+    /// Id of the last activated profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+
+    /// Registries to fetch servers from; earlier sources win name clashes.
+    /// Empty means the official registry only
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub registry_sources: Vec<RegistrySource>,
 }
 
 pub fn get_app_config_path() -> Option<PathBuf> {
     dirs::config_dir().map(|p| p.join("rain-mcp").join("settings.json"))
 }
 
+/// Directory holding user-provided agent definitions (`*.json`)
+pub fn get_user_agents_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("rain-mcp").join("agents"))
+}
+
+/// Append-only journal of configuration changes
+pub fn get_journal_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("rain-mcp").join("journal.jsonl"))
+}
+
+/// Passphrase-encrypted secret vault
+pub fn get_vault_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("rain-mcp").join("vault.json"))
+}
+
+/// Shell env file holding secrets migrated out of agent configs
+pub fn get_secrets_env_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("rain-mcp").join("secrets.env"))
+}
+
+/// Machine-local key sealing provider API keys and the journal; kept out of the config
+/// dir so it is not synced along with `settings.json`. The file name predates the journal
+pub fn get_machine_key_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|p| p.join("rain-mcp").join("provider.key"))
+}
+
 pub fn load_app_config() -> AppConfig {
     let path = match get_app_config_path() {
         Some(p) => p,
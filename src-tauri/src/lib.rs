@@ -4,6 +4,8 @@ pub mod mcp;
 pub mod window;
 
 use std::env;
+use tauri::Manager;
+use tauri_plugin_deep_link::DeepLinkExt;
 use tauri_plugin_log::{Target, TargetKind};
 
 /// 获取日志级别
@@ -43,6 +45,13 @@ pub fn run() {
     let log_level = get_log_level();
 
     tauri::Builder::default()
+        // Must be registered first; forwards `rainmcp://` links opened while running
+        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.set_focus();
+            }
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .plugin(
@@ -53,15 +62,55 @@ pub fn run() {
                     Target::new(TargetKind::Webview),
                 ])
                 .level(log_level)
+                // `[date][time][level][target] message` with a UTC timestamp, as the plugin's
+                // default formatter writes it, but with secrets masked
+                .format(|out, message, record| {
+                    let message = mcp::redact::redact_text(&message.to_string());
+                    out.finish(format_args!(
+                        "{}[{}][{}] {}",
+                        chrono::Utc::now().format("[%Y-%m-%d][%H:%M:%S]"),
+                        record.level(),
+                        record.target(),
+                        message
+                    ))
+                })
                 .build(),
         )
         .plugin(tauri_plugin_decorum::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_clipboard_manager::init())
+        .setup(|app| {
+            if let Err(e) = agent::keys::seal_plaintext_keys() {
+                log::warn!("Failed to encrypt stored API keys: {}", e);
+            }
+            if let Err(e) = mcp::registry::seal_plaintext_auth_headers() {
+                log::warn!("Failed to encrypt registry auth headers: {}", e);
+            }
+            if let Err(e) = mcp::watcher::start_config_watcher(app.handle().clone()) {
+                log::error!("{}", e);
+            }
+
+            #[cfg(any(windows, target_os = "linux"))]
+            if let Err(e) = app.deep_link().register_all() {
+                log::warn!("Failed to register install link scheme: {}", e);
+            }
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                let urls: Vec<String> = event.urls().iter().map(|u| u.to_string()).collect();
+                mcp::deeplink::handle_opened_urls(&handle, &urls);
+            });
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                let urls: Vec<String> = urls.iter().map(|u| u.to_string()).collect();
+                mcp::deeplink::handle_opened_urls(app.handle(), &urls);
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             mcp::get_agent_mcp_config_command,
             mcp::get_server_raw_config_command,
+            mcp::reveal_server_raw_config_command,
             mcp::update_agent_mcp_config_command,
+            mcp::preview_agent_mcp_config_command,
             mcp::get_supported_agents_command,
             mcp::get_enabled_agents_command,
             mcp::update_enabled_agents_command,
@@ -69,12 +118,53 @@ pub fn run() {
             mcp::update_app_config_command,
             mcp::open_config_file_command,
             mcp::refresh_schema_store_command,
+            mcp::set_registry_auth_header_command,
             mcp::get_schema_store_command,
             mcp::get_user_servers_command,
             mcp::add_user_server_command,
             mcp::update_user_server_command,
             mcp::delete_user_server_command,
             mcp::add_server_to_agent_command,
+            mcp::preview_add_server_to_agent_command,
+            mcp::copy_server_to_agents_command,
+            mcp::move_server_to_agents_command,
+            mcp::scan_agent_servers_command,
+            mcp::import_user_servers_command,
+            mcp::get_cross_agent_report_command,
+            mcp::get_user_server_links_command,
+            mcp::sync_user_server_command,
+            mcp::remove_server_from_all_agents_command,
+            mcp::rename_server_in_all_agents_command,
+            mcp::get_profiles_command,
+            mcp::save_profile_command,
+            mcp::delete_profile_command,
+            mcp::activate_profile_command,
+            mcp::deploy_user_server_command,
+            mcp::get_journal_command,
+            mcp::undo_command,
+            mcp::redo_command,
+            mcp::export_server_bundle_command,
+            mcp::preview_server_bundle_command,
+            mcp::import_server_bundle_command,
+            mcp::parse_install_link_command,
+            mcp::install_from_link_command,
+            mcp::parse_config_snippet_command,
+            mcp::get_install_snippets_command,
+            mcp::get_vault_status_command,
+            mcp::create_vault_command,
+            mcp::unlock_vault_command,
+            mcp::lock_vault_command,
+            mcp::set_vault_secret_command,
+            mcp::delete_vault_secret_command,
+            mcp::get_secret_exports_command,
+            mcp::scan_plaintext_secrets_command,
+            mcp::migrate_plaintext_secrets_command,
+            mcp::import_env_file_command,
+            mcp::search_schema_store_command,
+            mcp::list_server_versions_command,
+            mcp::get_server_updates_command,
+            mcp::upgrade_user_server_command,
+            mcp::package_config_command,
             window::set_traffic_lights_inset_command,
             agent::agent_chat_command,
             agent::agent_chat_stream_command,
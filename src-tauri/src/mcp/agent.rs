@@ -2,14 +2,21 @@
 //!
 //! This module defines supported AI coding agents and their configuration paths.
 
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Metadata, Schema, SchemaObject, SubschemaValidation};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::path::PathBuf;
 
+use super::agent_definition::{builtin_agent_ids, get_agent_definition, load_agent_definitions};
 use super::AgentServers;
 
 /// Supported AI coding agent types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Hash)]
+///
+/// Built-in agents have their own variant; agents described only by a user
+/// definition file are represented as `Custom` with the definition id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub enum AgentType {
     ClaudeCode,
     Cursor,
@@ -26,6 +33,101 @@ pub enum AgentType {
     CopilotCli,
     Alma,
     OpenCode,
+    #[serde(untagged)]
+    Custom(String),
+}
+
+impl AgentType {
+    /// Definition id of this agent (e.g. "ClaudeCode")
+    pub fn id(&self) -> &str {
+        match self {
+            AgentType::ClaudeCode => "ClaudeCode",
+            AgentType::Cursor => "Cursor",
+            AgentType::Windsurf => "Windsurf",
+            AgentType::Cline => "Cline",
+            AgentType::ClaudeDesktop => "ClaudeDesktop",
+            AgentType::RooCode => "RooCode",
+            AgentType::Trae => "Trae",
+            AgentType::GeminiCli => "GeminiCli",
+            AgentType::Kiro => "Kiro",
+            AgentType::OpenAiCodex => "OpenAiCodex",
+            AgentType::Comate => "Comate",
+            AgentType::VsCodeCopilot => "VsCodeCopilot",
+            AgentType::CopilotCli => "CopilotCli",
+            AgentType::Alma => "Alma",
+            AgentType::OpenCode => "OpenCode",
+            AgentType::Custom(id) => id,
+        }
+    }
+
+    /// Map a definition id back to an agent type
+    pub fn from_id(id: &str) -> AgentType {
+        match id {
+            "ClaudeCode" => AgentType::ClaudeCode,
+            "Cursor" => AgentType::Cursor,
+            "Windsurf" => AgentType::Windsurf,
+            "Cline" => AgentType::Cline,
+            "ClaudeDesktop" => AgentType::ClaudeDesktop,
+            "RooCode" => AgentType::RooCode,
+            "Trae" => AgentType::Trae,
+            "GeminiCli" => AgentType::GeminiCli,
+            "Kiro" => AgentType::Kiro,
+            "OpenAiCodex" => AgentType::OpenAiCodex,
+            "Comate" => AgentType::Comate,
+            "VsCodeCopilot" => AgentType::VsCodeCopilot,
+            "CopilotCli" => AgentType::CopilotCli,
+            "Alma" => AgentType::Alma,
+            "OpenCode" => AgentType::OpenCode,
+            _ => AgentType::Custom(id.to_string()),
+        }
+    }
+}
+
+/// Written by hand because the derive ignores `#[serde(untagged)]` on `Custom` and would
+/// describe it as `{ "Custom": id }` instead of the plain id it serializes to
+impl JsonSchema for AgentType {
+    fn schema_name() -> String {
+        "AgentType".to_string()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        let built_in = SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(builtin_agent_ids().map(Into::into).collect()),
+            ..Default::default()
+        };
+        let custom = SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                description: Some("Id of an agent from a user definition file".to_string()),
+                ..Default::default()
+            })),
+            instance_type: Some(InstanceType::String.into()),
+            ..Default::default()
+        };
+        SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                description: Some(
+                    "Supported AI coding agent types\n\nBuilt-in agents have their own variant; \
+                     agents described only by a user definition file are represented as \
+                     `Custom` with the definition id."
+                        .to_string(),
+                ),
+                ..Default::default()
+            })),
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![built_in.into(), custom.into()]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl fmt::Display for AgentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
 }
 
 /// Information about a supported agent
@@ -39,98 +141,21 @@ pub struct SupportedAgent {
     pub mcp_config: Option<AgentServers>,
 }
 
-/// Returns all supported agent types
+/// Returns all supported agent types (built-in first, then user-defined)
 pub fn get_all_agent_types() -> Vec<AgentType> {
-    vec![
-        AgentType::ClaudeCode,
-        AgentType::Cursor,
-        AgentType::Windsurf,
-        AgentType::Cline,
-        AgentType::ClaudeDesktop,
-        AgentType::RooCode,
-        AgentType::Trae,
-        AgentType::GeminiCli,
-        AgentType::Kiro,
-        AgentType::OpenAiCodex,
-        AgentType::Comate,
-        AgentType::VsCodeCopilot,
-        AgentType::CopilotCli,
-        AgentType::Alma,
-        AgentType::OpenCode,
-    ]
+    load_agent_definitions().iter().map(|d| d.agent_type()).collect()
 }
 
 /// Parse agent name string to AgentType
 pub fn parse_agent_name(name: &str) -> Result<AgentType, String> {
-    match name.to_lowercase().as_str() {
-        "claude-code" | "claude_code" | "claudecode" => Ok(AgentType::ClaudeCode),
-        "cursor" => Ok(AgentType::Cursor),
-        "windsurf" => Ok(AgentType::Windsurf),
-        "cline" => Ok(AgentType::Cline),
-        "claude-desktop" | "claude_desktop" | "claudedesktop" => Ok(AgentType::ClaudeDesktop),
-        "roo-code" | "roo_code" | "roocode" => Ok(AgentType::RooCode),
-        "trae" => Ok(AgentType::Trae),
-        "gemini-cli" | "gemini_cli" | "geminicli" => Ok(AgentType::GeminiCli),
-        "kiro" => Ok(AgentType::Kiro),
-        "openai-codex" | "openai_codex" | "openaicodex" | "codex" => Ok(AgentType::OpenAiCodex),
-        "comate" => Ok(AgentType::Comate),
-        "vscode-copilot" | "vscode_copilot" | "vscodecopilot" | "vs-code-copilot"
-        | "vs_code_copilot" => Ok(AgentType::VsCodeCopilot),
-        "copilot-cli" | "copilot_cli" | "copilotcli" => Ok(AgentType::CopilotCli),
-        "alma" => Ok(AgentType::Alma),
-        "opencode" | "open-code" | "open_code" => Ok(AgentType::OpenCode),
-        _ => Err(format!("Unknown agent: {}", name)),
-    }
+    load_agent_definitions()
+        .into_iter()
+        .find(|d| d.matches_name(name))
+        .map(|d| d.agent_type())
+        .ok_or_else(|| format!("Unknown agent: {}", name))
 }
 
 /// Get the global configuration file path for an agent
-pub fn get_global_config_path(agent: AgentType) -> Result<PathBuf, String> {
-    let home = dirs::home_dir().ok_or("Cannot find home directory")?;
-    let config_dir = dirs::config_dir().ok_or("Cannot find config directory")?;
-
-    let path = match agent {
-        AgentType::ClaudeCode => home.join(".claude.json"),
-        AgentType::Cursor => home.join(".cursor/mcp.json"),
-        AgentType::Windsurf => {
-            // Windsurf uses .codeium/windsurf/mcp_config.json on all platforms in HOME
-            home.join(".codeium/windsurf/mcp_config.json")
-        }
-        AgentType::Cline => {
-            // macOS: ~/Library/Application Support/Code/User/...
-            // Windows: %APPDATA%\Code\User\...
-            // Linux: ~/.config/Code/User/...
-            // dirs::config_dir() handles the prefix for all these cases.
-            config_dir.join(
-                "Code/User/globalStorage/saoudrizwan.claude-dev/settings/cline_mcp_settings.json",
-            )
-        }
-        AgentType::ClaudeDesktop => {
-            // macOS: ~/Library/Application Support/Claude/...
-            // Windows: %APPDATA%\Claude\...
-            // Linux: Not officially supported, but XDG would be ~/.config/Claude/...
-            config_dir.join("Claude/claude_desktop_config.json")
-        }
-        AgentType::RooCode => {
-            // Same as VSCode/Cline structure
-            config_dir.join(
-                "Code/User/globalStorage/rooveterinaryinc.roo-cline/settings/mcp_settings.json",
-            )
-        }
-        AgentType::Trae => {
-            // macOS: ~/Library/Application Support/Trae/User/...
-            // Windows: %APPDATA%\Trae\User\...
-            // Linux: ~/.config/Trae/User/...
-            config_dir.join("Trae/User/mcp.json")
-        }
-        AgentType::GeminiCli => home.join(".gemini/settings.json"),
-        AgentType::Kiro => home.join(".kiro/settings/mcp.json"),
-        AgentType::OpenAiCodex => home.join(".codex/config.toml"),
-        AgentType::Comate => home.join(".comate/mcp.json"),
-        AgentType::VsCodeCopilot => config_dir.join("Code/User/mcp.json"),
-        AgentType::CopilotCli => home.join(".copilot/mcp-config.json"),
-        AgentType::Alma => home.join(".config/alma/mcp.json"),
-        AgentType::OpenCode => home.join(".config/opencode/opencode.json"),
-    };
-
-    Ok(path)
+pub fn get_global_config_path(agent: &AgentType) -> Result<PathBuf, String> {
+    get_agent_definition(agent)?.config_path()
 }
@@ -58,6 +58,18 @@ pub fn parse_server_entry(value: &Value) -> Result<AgentServerEntry, String> {
     }
 }
 
+/// Move `from` to `to` inside a server object (no-op when the keys are equal or absent)
+pub fn rename_server_key(value: &mut Value, from: &str, to: &str) {
+    if from == to {
+        return;
+    }
+    if let Some(obj) = value.as_object_mut() {
+        if let Some(inner) = obj.remove(from) {
+            obj.insert(to.to_string(), inner);
+        }
+    }
+}
+
 pub fn server_entry_to_value(entry: &AgentServerEntry) -> Result<Value, String> {
     match entry {
         AgentServerEntry::Local(local) => serde_json::to_value(local).map_err(|e| e.to_string()),
@@ -4,8 +4,9 @@ pub mod opencode_adapter;
 pub mod toml_adapter;
 
 use crate::mcp::agent::AgentType;
+use crate::mcp::agent_definition::{get_agent_definition, AgentConfigFormat, AgentDefinition};
 
-use json_adapter::{JsonAdapter, JsonServerExtras};
+use json_adapter::JsonAdapter;
 use opencode_adapter::OpenCodeAdapter;
 use toml_adapter::TomlAdapter;
 
@@ -13,34 +14,56 @@ pub trait AgentConfigAdapter {
     fn agent_type(&self) -> AgentType;
     fn config_path(&self) -> Result<std::path::PathBuf, String>;
     fn read_config(&self) -> Result<crate::mcp::AgentServers, String>;
-    fn write_config(&self, config: crate::mcp::AgentServers) -> Result<(), String>;
-    fn get_server_raw_config(&self, server_name: &str) -> Result<String, String>;
-}
 
-pub fn get_adapter(agent: AgentType) -> Box<dyn AgentConfigAdapter> {
-    match agent {
-        AgentType::OpenAiCodex => Box::new(TomlAdapter::new(agent)),
-        AgentType::OpenCode => Box::new(OpenCodeAdapter::new()),
-        AgentType::VsCodeCopilot => {
-            Box::new(JsonAdapter::new(agent, "servers", JsonServerExtras::VsCodeCopilot))
-        }
-        AgentType::CopilotCli => {
-            Box::new(JsonAdapter::new(agent, "mcpServers", JsonServerExtras::CopilotCli))
-        }
-        AgentType::ClaudeCode => {
-            Box::new(JsonAdapter::new(agent, "mcpServers", JsonServerExtras::ClaudeCode))
+    /// Compute the exact file content `write_config` would write, without touching disk
+    fn render_config(&self, config: crate::mcp::AgentServers) -> Result<String, String> {
+        let path = self.config_path()?;
+        let content = if path.exists() {
+            std::fs::read_to_string(&path).map_err(|e| e.to_string())?
+        } else {
+            String::new()
+        };
+        self.render_config_from(&content, config)
+    }
+
+    /// Like `render_config`, merging into `content` instead of the file on disk
+    fn render_config_from(
+        &self,
+        content: &str,
+        config: crate::mcp::AgentServers,
+    ) -> Result<String, String>;
+
+    fn write_config(&self, config: crate::mcp::AgentServers) -> Result<(), String> {
+        let output = self.render_config(config)?;
+        let path = self.config_path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
-        AgentType::Cursor
-        | AgentType::Windsurf
-        | AgentType::Cline
-        | AgentType::ClaudeDesktop
-        | AgentType::RooCode
-        | AgentType::Trae
-        | AgentType::GeminiCli
-        | AgentType::Kiro
-        | AgentType::Comate
-        | AgentType::Alma => {
-            Box::new(JsonAdapter::new(agent, "mcpServers", JsonServerExtras::None))
+        std::fs::write(path, output).map_err(|e| e.to_string())
+    }
+
+    fn get_server_raw_config(&self, server_name: &str) -> Result<String, String> {
+        let path = self.config_path()?;
+        if !path.exists() {
+            return Err(format!("Config file not found: {:?}", path));
         }
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read config file: {}", e))?;
+        self.server_raw_config_from(&content, server_name)
+    }
+
+    /// Like `get_server_raw_config`, reading from `content` instead of the file on disk
+    fn server_raw_config_from(&self, content: &str, server_name: &str) -> Result<String, String>;
+}
+
+pub fn get_adapter(agent: &AgentType) -> Result<Box<dyn AgentConfigAdapter>, String> {
+    Ok(adapter_for_definition(get_agent_definition(agent)?))
+}
+
+pub fn adapter_for_definition(definition: AgentDefinition) -> Box<dyn AgentConfigAdapter> {
+    match definition.format {
+        AgentConfigFormat::Json => Box::new(JsonAdapter::new(definition)),
+        AgentConfigFormat::Toml => Box::new(TomlAdapter::new(definition)),
+        AgentConfigFormat::OpenCode => Box::new(OpenCodeAdapter::new(definition)),
     }
 }
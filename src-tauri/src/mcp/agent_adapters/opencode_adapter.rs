@@ -10,7 +10,7 @@
 use serde_json::{Map, Value};
 use std::collections::HashMap;
 
-use crate::mcp::agent::get_global_config_path;
+use crate::mcp::agent_definition::AgentDefinition;
 use crate::mcp::{
     AgentServerEntry, AgentServers, AgentType, BaseServerEntry, LocalServerEntry, RemoteServerEntry,
 };
@@ -18,32 +18,36 @@ use crate::mcp::{
 use super::AgentConfigAdapter;
 
 pub struct OpenCodeAdapter {
-    agent_type: AgentType,
+    definition: AgentDefinition,
 }
 
 impl OpenCodeAdapter {
-    pub fn new() -> Self {
-        Self { agent_type: AgentType::OpenCode }
+    pub fn new(definition: AgentDefinition) -> Self {
+        Self { definition }
     }
 
     fn read_root_value(&self) -> Result<Value, String> {
-        let path = get_global_config_path(self.agent_type)?;
+        let path = self.definition.config_path()?;
         if !path.exists() {
             return Err(format!("Config file not found: {:?}", path));
         }
 
         let content = std::fs::read_to_string(&path)
             .map_err(|e| format!("Failed to read config file: {}", e))?;
+        Self::parse_root_value(&content)
+    }
+
+    fn parse_root_value(content: &str) -> Result<Value, String> {
         if content.trim().is_empty() {
             return Ok(Value::Object(Map::new()));
         }
 
-        let content = strip_jsonc_comments(&content);
+        let content = strip_jsonc_comments(content);
 
         serde_json::from_str(&content).map_err(|e| format!("Failed to parse JSON config: {}", e))
     }
 
-    fn parse_server_entry(value: &Value) -> Result<AgentServerEntry, String> {
+    pub fn parse_server_entry(value: &Value) -> Result<AgentServerEntry, String> {
         let obj = value.as_object().ok_or_else(|| "Server config must be an object".to_string())?;
 
         let server_type = obj
@@ -154,11 +158,11 @@ impl OpenCodeAdapter {
 
 impl AgentConfigAdapter for OpenCodeAdapter {
     fn agent_type(&self) -> AgentType {
-        self.agent_type
+        self.definition.agent_type()
     }
 
     fn config_path(&self) -> Result<std::path::PathBuf, String> {
-        get_global_config_path(self.agent_type)
+        self.definition.config_path()
     }
 
     fn read_config(&self) -> Result<AgentServers, String> {
@@ -166,11 +170,11 @@ impl AgentConfigAdapter for OpenCodeAdapter {
         let root_obj =
             root.as_object().ok_or_else(|| "Config root must be an object".to_string())?;
 
-        let mcp_value = root_obj.get("mcp");
+        let mcp_value = root_obj.get(self.definition.servers_key());
         let mcp_obj = match mcp_value {
             Some(Value::Object(map)) => map,
             Some(_) => {
-                return Err("mcp must be an object".to_string());
+                return Err(format!("{} must be an object", self.definition.servers_key()));
             }
             None => {
                 return Ok(AgentServers { servers: HashMap::new() });
@@ -193,19 +197,8 @@ impl AgentConfigAdapter for OpenCodeAdapter {
         Ok(AgentServers { servers })
     }
 
-    fn write_config(&self, config: AgentServers) -> Result<(), String> {
-        let path = get_global_config_path(self.agent_type)?;
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-        }
-
-        let content = if path.exists() {
-            std::fs::read_to_string(&path).map_err(|e| e.to_string())?
-        } else {
-            String::new()
-        };
-
-        let content = strip_jsonc_comments(&content);
+    fn render_config_from(&self, content: &str, config: AgentServers) -> Result<String, String> {
+        let content = strip_jsonc_comments(content);
 
         let mut root_value = if content.trim().is_empty() {
             Value::Object(Map::new())
@@ -217,8 +210,11 @@ impl AgentConfigAdapter for OpenCodeAdapter {
             .as_object_mut()
             .ok_or_else(|| "Config root must be an object".to_string())?;
 
-        let existing_mcp =
-            root_obj.get("mcp").and_then(|v| v.as_object()).cloned().unwrap_or_default();
+        let existing_mcp = root_obj
+            .get(self.definition.servers_key())
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default();
 
         let mut new_mcp = Map::new();
 
@@ -248,20 +244,19 @@ impl AgentConfigAdapter for OpenCodeAdapter {
             }
         }
 
-        root_obj.insert("mcp".to_string(), Value::Object(new_mcp));
+        root_obj.insert(self.definition.servers_key().to_string(), Value::Object(new_mcp));
 
-        let output = serde_json::to_string_pretty(&root_value)
-            .map_err(|e| format!("Failed to serialize JSON: {}", e))?;
-        std::fs::write(path, output).map_err(|e| e.to_string())
+        serde_json::to_string_pretty(&root_value)
+            .map_err(|e| format!("Failed to serialize JSON: {}", e))
     }
 
-    fn get_server_raw_config(&self, server_name: &str) -> Result<String, String> {
-        let root = self.read_root_value()?;
+    fn server_raw_config_from(&self, content: &str, server_name: &str) -> Result<String, String> {
+        let root = Self::parse_root_value(content)?;
         let root_obj =
             root.as_object().ok_or_else(|| "Config root must be an object".to_string())?;
 
         let server_value = root_obj
-            .get("mcp")
+            .get(self.definition.servers_key())
             .and_then(|v| v.get(server_name))
             .ok_or_else(|| format!("Server '{}' not found", server_name))?
             .clone();
@@ -274,7 +269,7 @@ impl AgentConfigAdapter for OpenCodeAdapter {
     }
 }
 
-fn strip_jsonc_comments(content: &str) -> String {
+pub fn strip_jsonc_comments(content: &str) -> String {
     let mut result = String::with_capacity(content.len());
     let mut chars = content.chars().peekable();
     let mut in_string = false;
@@ -1,83 +1,85 @@
 use serde_json::Value;
 
-use crate::mcp::agent::get_global_config_path;
+use crate::mcp::agent_definition::AgentDefinition;
 use crate::mcp::{AgentServers, AgentType};
 
 use super::common::{merge_server_value, parse_server_entry, server_entry_to_value};
 use super::AgentConfigAdapter;
 
 pub struct TomlAdapter {
-    agent_type: AgentType,
+    definition: AgentDefinition,
 }
 
 impl TomlAdapter {
-    pub fn new(agent_type: AgentType) -> Self {
-        Self { agent_type }
+    pub fn new(definition: AgentDefinition) -> Self {
+        Self { definition }
     }
 
     fn read_root_table(&self) -> Result<toml::Table, String> {
-        let path = get_global_config_path(self.agent_type)?;
+        let path = self.definition.config_path()?;
         if !path.exists() {
             return Err(format!("Config file not found: {:?}", path));
         }
 
         let content = std::fs::read_to_string(&path)
             .map_err(|e| format!("Failed to read config file: {}", e))?;
-        if content.trim().is_empty() {
-            return Ok(toml::Table::new());
-        }
+        parse_root_table(&content)
+    }
+}
 
-        toml::from_str(&content).map_err(|e| format!("Failed to parse TOML config: {}", e))
+fn parse_root_table(content: &str) -> Result<toml::Table, String> {
+    if content.trim().is_empty() {
+        return Ok(toml::Table::new());
     }
+
+    toml::from_str(content).map_err(|e| format!("Failed to parse TOML config: {}", e))
+}
+
+/// Parse one `[mcp_servers.<name>]` table
+pub fn parse_toml_server_entry(
+    name: &str,
+    value: &toml::Value,
+) -> Result<crate::mcp::AgentServerEntry, String> {
+    let json_value = serde_json::to_value(value)
+        .map_err(|e| format!("Failed to convert TOML to JSON: {}", e))?;
+    parse_server_entry(&json_value).map_err(|e| format!("Failed to parse server '{}': {}", name, e))
 }
 
 impl AgentConfigAdapter for TomlAdapter {
     fn agent_type(&self) -> AgentType {
-        self.agent_type
+        self.definition.agent_type()
     }
 
     fn config_path(&self) -> Result<std::path::PathBuf, String> {
-        get_global_config_path(self.agent_type)
+        self.definition.config_path()
     }
 
     fn read_config(&self) -> Result<AgentServers, String> {
         let root = self.read_root_table()?;
-        let servers_table = root.get("mcp_servers").and_then(|v| v.as_table());
+        let servers_table = root.get(self.definition.servers_key()).and_then(|v| v.as_table());
 
         let mut servers = std::collections::HashMap::new();
         if let Some(table) = servers_table {
             for (name, value) in table {
-                let json_value = serde_json::to_value(value)
-                    .map_err(|e| format!("Failed to convert TOML to JSON: {}", e))?;
-                let server_config = parse_server_entry(&json_value)
-                    .map_err(|e| format!("Failed to parse server '{}': {}", name, e))?;
-                servers.insert(name.clone(), server_config);
+                servers.insert(name.clone(), parse_toml_server_entry(name, value)?);
             }
         }
 
         Ok(AgentServers { servers })
     }
 
-    fn write_config(&self, config: AgentServers) -> Result<(), String> {
-        let path = get_global_config_path(self.agent_type)?;
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-        }
-
-        let content = if path.exists() {
-            std::fs::read_to_string(&path).map_err(|e| e.to_string())?
-        } else {
-            String::new()
-        };
-
+    fn render_config_from(&self, content: &str, config: AgentServers) -> Result<String, String> {
         let mut root: toml::Table = if content.trim().is_empty() {
             toml::Table::new()
         } else {
-            toml::from_str(&content).map_err(|e| format!("Failed to parse TOML: {}", e))?
+            toml::from_str(content).map_err(|e| format!("Failed to parse TOML: {}", e))?
         };
 
-        let existing_servers =
-            root.get("mcp_servers").and_then(|v| v.as_table()).cloned().unwrap_or_default();
+        let existing_servers = root
+            .get(self.definition.servers_key())
+            .and_then(|v| v.as_table())
+            .cloned()
+            .unwrap_or_default();
 
         let mut new_servers = toml::Table::new();
         for (name, server_config) in config.servers {
@@ -91,17 +93,15 @@ impl AgentConfigAdapter for TomlAdapter {
             new_servers.insert(name, merged_toml);
         }
 
-        root.insert("mcp_servers".to_string(), toml::Value::Table(new_servers));
+        root.insert(self.definition.servers_key().to_string(), toml::Value::Table(new_servers));
 
-        let output = toml::to_string_pretty(&root)
-            .map_err(|e| format!("Failed to serialize TOML: {}", e))?;
-        std::fs::write(path, output).map_err(|e| e.to_string())
+        toml::to_string_pretty(&root).map_err(|e| format!("Failed to serialize TOML: {}", e))
     }
 
-    fn get_server_raw_config(&self, server_name: &str) -> Result<String, String> {
-        let root = self.read_root_table()?;
+    fn server_raw_config_from(&self, content: &str, server_name: &str) -> Result<String, String> {
+        let root = parse_root_table(content)?;
         let server_value = root
-            .get("mcp_servers")
+            .get(self.definition.servers_key())
             .and_then(|v| v.get(server_name))
             .ok_or_else(|| format!("Server '{}' not found", server_name))?
             .clone();
@@ -1,7 +1,31 @@
 use super::agent_adapters;
+use super::journal::{self, JournalChange};
 use super::user_server::UserServer;
+use super::vault::config_for_agent;
 use super::{AgentServers, AgentType};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
 use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Result of a dry-run write: what the config file would look like after saving
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigPreview {
+    pub config_path: PathBuf,
+
+    /// Current file content (empty if the file does not exist)
+    pub original: String,
+
+    /// Exact content that would be written
+    pub updated: String,
+
+    /// Unified diff from `original` to `updated`
+    pub diff: String,
+
+    pub has_changes: bool,
+}
 
 /// Add a server to agent's configuration
 pub fn add_server_to_agent(
@@ -9,8 +33,30 @@ pub fn add_server_to_agent(
     server: &UserServer,
     server_name: Option<String>,
 ) -> Result<(), String> {
+    let agent_servers = config_with_server(agent.clone(), server, server_name)?;
+    save_agent_config(agent, agent_servers)
+}
+
+/// Preview adding a server to agent's configuration without writing
+pub fn preview_add_server_to_agent(
+    agent: AgentType,
+    server: &UserServer,
+    server_name: Option<String>,
+) -> Result<ConfigPreview, String> {
+    let agent_servers = config_with_server(agent.clone(), server, server_name)?;
+    preview_agent_config(agent, agent_servers)
+}
+
+/// `agent`'s config with `server` added under `server_name` (its own name by default)
+pub fn config_with_server(
+    agent: AgentType,
+    server: &UserServer,
+    server_name: Option<String>,
+) -> Result<AgentServers, String> {
     let name = server_name.unwrap_or_else(|| server.name.clone());
 
+    let config = config_for_agent(&server.config, &agent)?;
+
     // Read existing config or create new one
     let mut agent_servers = match read_agent_config(agent) {
         Ok(c) => c,
@@ -22,20 +68,74 @@ pub fn add_server_to_agent(
         return Err(format!("Server '{}' already exists in agent config", name));
     }
 
-    agent_servers.servers.insert(name, server.config.clone());
-    save_agent_config(agent, agent_servers)
+    agent_servers.servers.insert(name, config);
+    Ok(agent_servers)
 }
 
 /// 统一读取接口
 pub fn read_agent_config(agent: AgentType) -> Result<AgentServers, String> {
-    agent_adapters::get_adapter(agent).read_config()
+    agent_adapters::get_adapter(&agent)?.read_config()
+}
+
+/// Like `read_agent_config`, but a missing config file counts as an empty config
+pub fn read_agent_config_or_empty(agent: AgentType) -> Result<AgentServers, String> {
+    let adapter = agent_adapters::get_adapter(&agent)?;
+    if !adapter.config_path()?.exists() {
+        return Ok(AgentServers { servers: HashMap::new() });
+    }
+    adapter.read_config()
 }
 
 /// 获取指定 server 的原始配置字符串（包含 server 名称作为 key）
 pub fn get_server_raw_config(agent: AgentType, server_name: &str) -> Result<String, String> {
-    agent_adapters::get_adapter(agent).get_server_raw_config(server_name)
+    agent_adapters::get_adapter(&agent)?.get_server_raw_config(server_name)
 }
 
 pub fn save_agent_config(agent: AgentType, config: AgentServers) -> Result<(), String> {
-    agent_adapters::get_adapter(agent).write_config(config)
+    if let Some(change) = write_agent_config(agent.clone(), config)? {
+        journal::record(format!("Update {} config", agent), vec![change]);
+    }
+    Ok(())
+}
+
+/// Write `agent`'s config without journaling it, returning the change for the caller to
+/// journal together with related changes
+pub fn write_agent_config(
+    agent: AgentType,
+    config: AgentServers,
+) -> Result<Option<JournalChange>, String> {
+    let adapter = agent_adapters::get_adapter(&agent)?;
+    let before = read_agent_config_or_empty(agent.clone()).ok();
+
+    adapter.write_config(config)?;
+
+    Ok(match (before, adapter.read_config()) {
+        (Some(before), Ok(after)) => Some(JournalChange::agent_config(agent, &before, &after)),
+        _ => None,
+    })
+}
+
+/// 预览写入结果：计算将写入的内容并与当前文件做 unified diff，不修改磁盘
+pub fn preview_agent_config(
+    agent: AgentType,
+    config: AgentServers,
+) -> Result<ConfigPreview, String> {
+    let adapter = agent_adapters::get_adapter(&agent)?;
+    let config_path = adapter.config_path()?;
+
+    let original = if config_path.exists() {
+        std::fs::read_to_string(&config_path).map_err(|e| e.to_string())?
+    } else {
+        String::new()
+    };
+    let updated = adapter.render_config(config)?;
+
+    let label = config_path.to_string_lossy();
+    let diff = TextDiff::from_lines(&original, &updated)
+        .unified_diff()
+        .context_radius(3)
+        .header(&label, &label)
+        .to_string();
+
+    Ok(ConfigPreview { has_changes: original != updated, config_path, original, updated, diff })
 }
@@ -0,0 +1,342 @@
+//! Declarative agent definitions
+//!
+//! Every agent rainMcp knows about is described by an [`AgentDefinition`]: where its
+//! config file lives, which dialect the file uses and how server entries are shaped.
+//! Built-in definitions are embedded from `builtin_agents.json`; users can add or
+//! override agents by dropping `*.json` files into `~/.config/rain-mcp/agents/`.
+//! User definitions are parsed once and re-read only when the directory changes.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use log::warn;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::config::get_user_agents_dir;
+
+use super::agent::AgentType;
+use super::AgentServerEntry;
+
+const BUILTIN_AGENTS_JSON: &str = include_str!("builtin_agents.json");
+
+static BUILTIN_DEFINITIONS: Lazy<Vec<AgentDefinition>> = Lazy::new(|| {
+    serde_json::from_str(BUILTIN_AGENTS_JSON).expect("builtin_agents.json must be valid")
+});
+
+/// Identifies the state of the user agents directory: its path and each file's mtime and size
+type DefinitionsVersion = (PathBuf, Vec<(PathBuf, Option<SystemTime>, u64)>);
+type CachedDefinitions = (DefinitionsVersion, Vec<AgentDefinition>);
+
+static USER_DEFINITIONS: Lazy<Mutex<Option<CachedDefinitions>>> = Lazy::new(|| Mutex::new(None));
+
+/// Ids of the built-in agents, in definition order
+pub fn builtin_agent_ids() -> impl Iterator<Item = &'static str> {
+    BUILTIN_DEFINITIONS.iter().map(|d| d.id.as_str())
+}
+
+/// Config file dialect used by an agent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentConfigFormat {
+    /// JSON object with `command`/`args`/`env` or `url`/`headers` entries
+    #[default]
+    Json,
+    /// TOML tables in the Codex style (`[mcp_servers.name]`)
+    Toml,
+    /// OpenCode JSONC (`command` array, `environment`, `enabled`)
+    OpenCode,
+}
+
+/// Config file location, either shared by all platforms or chosen per OS
+///
+/// Paths may start with `~/` (home directory) or `{config}/` (platform config
+/// directory, e.g. `~/Library/Application Support` on macOS or `%APPDATA%` on Windows).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ConfigPathSpec {
+    Uniform(String),
+    PerOs {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        macos: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        windows: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        linux: Option<String>,
+        /// Used when no OS-specific path is given
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default: Option<String>,
+    },
+}
+
+/// Values written to the transport type field (e.g. `"type": "stdio"`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeFieldSpec {
+    #[serde(default = "default_type_key")]
+    pub key: String,
+    pub local: String,
+    pub remote: String,
+}
+
+fn default_type_key() -> String {
+    "type".to_string()
+}
+
+/// Declarative description of an agent's MCP configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentDefinition {
+    /// Stable identifier, also used as the key in `settings.json` clients
+    pub id: String,
+
+    /// Human-readable name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+
+    /// Extra names accepted by `parse_agent_name` (matched case-insensitively)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+
+    /// Global config file location
+    pub config_path: ConfigPathSpec,
+
+    /// Config file dialect
+    #[serde(default)]
+    pub format: AgentConfigFormat,
+
+    /// Key holding the server map (defaults depend on `format`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub servers_key: Option<String>,
+
+    /// Transport type field written for each server, if the agent expects one (JSON format only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_field: Option<TypeFieldSpec>,
+
+    /// Key holding a remote server's URL (defaults to `url`; JSON format only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_key: Option<String>,
+
+    /// Key holding a local server's environment (defaults to `env`; JSON format only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_key: Option<String>,
+
+    /// Fields added to every written server unless already present (JSON format only)
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub default_fields: Map<String, Value>,
+
+    /// How the agent references an environment variable in env values and headers, with
+    /// `VAR` standing for the variable name (e.g. `${env:VAR}`); unset if unsupported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_reference: Option<String>,
+
+    /// Whether the agent is usually started from a terminal, and so sees variables exported
+    /// by the shell profile; GUI apps launched from the Dock or Start menu do not
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub launched_from_shell: bool,
+}
+
+impl AgentDefinition {
+    pub fn agent_type(&self) -> AgentType {
+        AgentType::from_id(&self.id)
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.id)
+    }
+
+    pub fn servers_key(&self) -> &str {
+        match (&self.servers_key, self.format) {
+            (Some(key), _) => key,
+            (None, AgentConfigFormat::Json) => "mcpServers",
+            (None, AgentConfigFormat::Toml) => "mcp_servers",
+            (None, AgentConfigFormat::OpenCode) => "mcp",
+        }
+    }
+
+    pub fn url_key(&self) -> &str {
+        self.url_key.as_deref().unwrap_or("url")
+    }
+
+    pub fn env_key(&self) -> &str {
+        self.env_key.as_deref().unwrap_or("env")
+    }
+
+    /// The env reference syntax, if the agent can also read the managed `secrets.env` file
+    pub fn shell_env_reference(&self) -> Option<&str> {
+        self.env_reference.as_deref().filter(|_| self.launched_from_shell)
+    }
+
+    /// Whether `name` refers to this agent (id or alias, case-insensitive)
+    pub fn matches_name(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.id.to_lowercase() == name || self.aliases.iter().any(|a| a.to_lowercase() == name)
+    }
+
+    /// Reject entry-shape fields the TOML and OpenCode adapters would silently ignore
+    pub fn validate(&self) -> Result<(), String> {
+        if self.format == AgentConfigFormat::Json {
+            return Ok(());
+        }
+        let unsupported: Vec<&str> = [
+            ("typeField", self.type_field.is_some()),
+            ("urlKey", self.url_key.is_some()),
+            ("envKey", self.env_key.is_some()),
+            ("defaultFields", !self.default_fields.is_empty()),
+        ]
+        .into_iter()
+        .filter_map(|(field, set)| set.then_some(field))
+        .collect();
+
+        if unsupported.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} only apply to the json format, not {:?}",
+                unsupported.join(", "),
+                self.format
+            ))
+        }
+    }
+
+    /// Resolve the global config file path for the current platform
+    pub fn config_path(&self) -> Result<PathBuf, String> {
+        let raw = match &self.config_path {
+            ConfigPathSpec::Uniform(path) => Some(path.as_str()),
+            ConfigPathSpec::PerOs { macos, windows, linux, default } => {
+                let os_specific = if cfg!(target_os = "macos") {
+                    macos
+                } else if cfg!(target_os = "windows") {
+                    windows
+                } else {
+                    linux
+                };
+                os_specific.as_deref().or(default.as_deref())
+            }
+        }
+        .ok_or_else(|| format!("Agent '{}' has no config path for this platform", self.id))?;
+
+        expand_config_path(raw)
+    }
+
+    /// Fields written alongside a server entry (type field and `defaultFields`)
+    pub fn build_extra_fields(&self, entry: &AgentServerEntry) -> Map<String, Value> {
+        let mut extras = Map::new();
+        if let Some(type_field) = &self.type_field {
+            let value = match entry {
+                AgentServerEntry::Local(_) => &type_field.local,
+                AgentServerEntry::Remote(_) => &type_field.remote,
+            };
+            extras.insert(type_field.key.clone(), Value::String(value.clone()));
+        }
+        for (key, value) in &self.default_fields {
+            extras.insert(key.clone(), value.clone());
+        }
+        extras
+    }
+}
+
+fn expand_config_path(raw: &str) -> Result<PathBuf, String> {
+    if let Some(rest) = raw.strip_prefix("~/") {
+        let home = dirs::home_dir().ok_or("Cannot find home directory")?;
+        Ok(home.join(rest))
+    } else if let Some(rest) = raw.strip_prefix("{config}/") {
+        let config_dir = dirs::config_dir().ok_or("Cannot find config directory")?;
+        Ok(config_dir.join(rest))
+    } else {
+        Ok(PathBuf::from(raw))
+    }
+}
+
+/// Load built-in definitions followed by user definitions
+///
+/// A user definition with the same id as a built-in one replaces it.
+pub fn load_agent_definitions() -> Vec<AgentDefinition> {
+    let mut definitions = BUILTIN_DEFINITIONS.clone();
+
+    for definition in load_user_definitions() {
+        match definitions.iter_mut().find(|d| d.id == definition.id) {
+            Some(existing) => *existing = definition,
+            None => definitions.push(definition),
+        }
+    }
+
+    definitions
+}
+
+/// Find the definition for an agent
+pub fn get_agent_definition(agent: &AgentType) -> Result<AgentDefinition, String> {
+    let id = agent.id();
+    load_agent_definitions()
+        .into_iter()
+        .find(|d| d.id == id)
+        .ok_or_else(|| format!("No definition found for agent '{}'", id))
+}
+
+fn load_user_definitions() -> Vec<AgentDefinition> {
+    let Some(dir) = get_user_agents_dir() else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<PathBuf> = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+
+    let files = paths
+        .iter()
+        .map(|path| {
+            let metadata = std::fs::metadata(path).ok();
+            (
+                path.clone(),
+                metadata.as_ref().and_then(|m| m.modified().ok()),
+                metadata.as_ref().map_or(0, |m| m.len()),
+            )
+        })
+        .collect();
+    let version = (dir, files);
+
+    let mut cached = USER_DEFINITIONS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached_version, definitions)) = cached.as_ref() {
+        if *cached_version == version {
+            return definitions.clone();
+        }
+    }
+    let definitions: Vec<AgentDefinition> =
+        paths.iter().filter_map(|path| parse_user_definition(path)).collect();
+    *cached = Some((version, definitions.clone()));
+    definitions
+}
+
+fn parse_user_definition(path: &std::path::Path) -> Option<AgentDefinition> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            warn!("Failed to read agent definition {:?}: {}", path, e);
+            return None;
+        }
+    };
+
+    match serde_json::from_str::<AgentDefinition>(&content) {
+        Ok(definition) if definition.id.trim().is_empty() => {
+            warn!("Agent definition {:?} has an empty id, skipping", path);
+            None
+        }
+        Ok(definition) => match definition.validate() {
+            Ok(()) => Some(definition),
+            Err(e) => {
+                warn!("Agent definition {:?} is invalid, skipping: {}", path, e);
+                None
+            }
+        },
+        Err(e) => {
+            warn!("Failed to parse agent definition {:?}: {}", path, e);
+            None
+        }
+    }
+}
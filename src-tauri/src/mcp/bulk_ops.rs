@@ -0,0 +1,161 @@
+//! Remove or rename a server in every agent holding it
+//!
+//! The server is selected either by name (all agents with a server of that name) or by
+//! a linked `UserServer` id (every deployment recorded for it, including those left
+//! behind by a deleted user server). Deployment records are kept in step with the agent
+//! configs.
+
+use log::debug;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{load_app_config, save_app_config, AppConfig};
+
+use super::agent_config::{read_agent_config, save_agent_config};
+use super::{get_all_agent_types, AgentType};
+
+/// Which agent servers a bulk operation applies to
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ServerSelector {
+    /// Every agent server with this name
+    #[serde(rename_all = "camelCase")]
+    Name { name: String },
+    /// Every agent copy linked to this user server
+    #[serde(rename_all = "camelCase")]
+    UserServer { server_id: String },
+}
+
+/// Outcome for one agent server
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkResult {
+    pub agent_type: AgentType,
+    pub server_name: String,
+    pub success: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Resolve the selector to `(agent, server name)` pairs currently present in agent configs
+pub fn resolve_selector(
+    app_config: &AppConfig,
+    selector: &ServerSelector,
+) -> Result<Vec<(AgentType, String)>, String> {
+    let candidates: Vec<(AgentType, String)> = match selector {
+        ServerSelector::Name { name } => {
+            get_all_agent_types().into_iter().map(|agent| (agent, name.clone())).collect()
+        }
+        ServerSelector::UserServer { server_id } => {
+            if !app_config.user_servers.iter().any(|s| s.id == *server_id)
+                && !app_config.server_deployments.contains_key(server_id)
+            {
+                return Err(format!("User server with ID '{}' not found", server_id));
+            }
+            app_config
+                .server_deployments
+                .get(server_id)
+                .into_iter()
+                .flatten()
+                .map(|d| (d.agent_type.clone(), d.server_name.clone()))
+                .collect()
+        }
+    };
+
+    Ok(candidates
+        .into_iter()
+        .filter(|(agent, name)| match read_agent_config(agent.clone()) {
+            Ok(config) => config.servers.contains_key(name),
+            Err(e) => {
+                debug!("Skipping {}: {}", agent, e);
+                false
+            }
+        })
+        .collect())
+}
+
+/// Remove the selected server from every agent holding it
+pub fn remove_server_everywhere(selector: &ServerSelector) -> Result<Vec<BulkResult>, String> {
+    let mut app_config = load_app_config();
+    let targets = resolve_selector(&app_config, selector)?;
+
+    let results = apply_to_targets(targets, |agent, name| {
+        let mut config = read_agent_config(agent.clone())?;
+        config.servers.remove(name);
+        save_agent_config(agent.clone(), config)
+    });
+
+    for result in results.iter().filter(|r| r.success) {
+        for deployments in app_config.server_deployments.values_mut() {
+            deployments.retain(|d| {
+                !(d.agent_type == result.agent_type && d.server_name == result.server_name)
+            });
+        }
+    }
+    app_config.server_deployments.retain(|_, deployments| !deployments.is_empty());
+    save_app_config(&app_config)?;
+
+    Ok(results)
+}
+
+/// Rename the selected server in every agent holding it
+///
+/// Agents that already have a server named `new_name` are left unchanged and reported
+/// as failed; renaming a server to its current name succeeds without writing.
+pub fn rename_server_everywhere(
+    selector: &ServerSelector,
+    new_name: &str,
+) -> Result<Vec<BulkResult>, String> {
+    if new_name.trim().is_empty() {
+        return Err("New server name must not be empty".to_string());
+    }
+
+    let mut app_config = load_app_config();
+    let targets = resolve_selector(&app_config, selector)?;
+
+    let results = apply_to_targets(targets, |agent, name| {
+        if name == new_name {
+            return Ok(());
+        }
+        let mut config = read_agent_config(agent.clone())?;
+        if config.servers.contains_key(new_name) {
+            return Err(format!("Server '{}' already exists in {}", new_name, agent));
+        }
+        let entry =
+            config.servers.remove(name).ok_or_else(|| format!("Server '{}' not found", name))?;
+        config.servers.insert(new_name.to_string(), entry);
+        save_agent_config(agent.clone(), config)
+    });
+
+    for result in results.iter().filter(|r| r.success) {
+        for deployment in app_config.server_deployments.values_mut().flatten() {
+            if deployment.agent_type == result.agent_type
+                && deployment.server_name == result.server_name
+            {
+                deployment.server_name = new_name.to_string();
+            }
+        }
+    }
+    save_app_config(&app_config)?;
+
+    Ok(results)
+}
+
+fn apply_to_targets(
+    targets: Vec<(AgentType, String)>,
+    op: impl Fn(&AgentType, &str) -> Result<(), String>,
+) -> Vec<BulkResult> {
+    targets
+        .into_iter()
+        .map(|(agent, name)| {
+            let outcome = op(&agent, &name);
+            BulkResult {
+                agent_type: agent,
+                server_name: name,
+                success: outcome.is_ok(),
+                error: outcome.err(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,369 @@
+//! Shareable server bundles
+//!
+//! A bundle is a versioned JSON file holding selected user servers with their origin.
+//! Secret env values and headers are replaced by named `{{PLACEHOLDER}}` tokens on
+//! export; import validates the bundle, fills placeholders with user-provided values and
+//! merges the servers into the library.
+
+use std::collections::{HashMap, HashSet};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{load_app_config, save_app_config};
+
+use super::journal::{self, JournalChange};
+use super::redact::{is_secret_value, origin_secret_names, AUTH_SCHEMES};
+use super::registry::load_schema_store;
+use super::transfer::ConflictStrategy;
+use super::user_server::UserServer;
+use super::AgentServerEntry;
+
+pub const BUNDLE_FORMAT: &str = "rainmcp-bundle";
+pub const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerBundle {
+    /// Always `rainmcp-bundle`
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub servers: Vec<UserServer>,
+
+    #[serde(default)]
+    pub placeholders: Vec<BundlePlaceholder>,
+}
+
+/// A secret value removed on export, to be provided on import
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlePlaceholder {
+    /// Token name, used as `{{NAME}}` in the server config
+    pub name: String,
+    pub server_id: String,
+
+    /// Field path, e.g. `env.GITHUB_TOKEN` or `headers.Authorization`
+    pub field: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleConflictKind {
+    Id,
+    Name,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleConflict {
+    pub server_id: String,
+    pub server_name: String,
+    pub kind: BundleConflictKind,
+
+    /// Id of the library server it clashes with
+    pub existing_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleImportPreview {
+    pub servers: Vec<UserServer>,
+    pub placeholders: Vec<BundlePlaceholder>,
+    pub conflicts: Vec<BundleConflict>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleImportResult {
+    pub imported: Vec<UserServer>,
+
+    /// Ids (from the bundle) of servers skipped because of conflicts
+    pub skipped: Vec<String>,
+}
+
+/// Call `f(field path, key, value)` for every env value and header of `config`
+fn for_each_secret_field(
+    config: &mut AgentServerEntry,
+    mut f: impl FnMut(&str, &str, &mut String),
+) {
+    let (prefix, values) = match config {
+        AgentServerEntry::Local(local) => ("env", local.env.as_mut()),
+        AgentServerEntry::Remote(remote) => ("headers", remote.headers.as_mut()),
+    };
+    for (key, value) in values.into_iter().flatten() {
+        f(&format!("{}.{}", prefix, key), key, value);
+    }
+}
+
+/// `GITHUB_TOKEN` from `GITHUB_TOKEN`, `AUTHORIZATION` from `Authorization`
+fn placeholder_base(key: &str) -> String {
+    key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+fn token(name: &str) -> String {
+    format!("{{{{{}}}}}", name)
+}
+
+/// A secret value replaced by a placeholder
+pub struct ReplacedSecret {
+    /// Field path, e.g. `env.GITHUB_TOKEN` or `headers.Authorization`
+    pub field: String,
+    pub key: String,
+    pub placeholder: String,
+}
+
+/// Replace secret env values and headers of `config` with `{{NAME}}` placeholders
+///
+/// A value is secret when its key is in `secret_names` (registry inputs marked
+/// `isSecret`) or the redaction heuristics flag it; references are left alone. Auth
+/// schemes are kept in front of the placeholder. Placeholder names are made unique
+/// against `taken`, which receives the new ones.
+pub fn replace_secrets(
+    config: &mut AgentServerEntry,
+    secret_names: &HashSet<String>,
+    taken: &mut HashSet<String>,
+) -> Vec<ReplacedSecret> {
+    let mut replaced = Vec::new();
+    for_each_secret_field(config, |field, key, value| {
+        if !is_secret_value(key, value, secret_names) {
+            return;
+        }
+
+        let base = placeholder_base(key);
+        let mut name = base.clone();
+        let mut n = 2;
+        while taken.contains(&name) {
+            name = format!("{}_{}", base, n);
+            n += 1;
+        }
+
+        let scheme = AUTH_SCHEMES.iter().find(|s| value.starts_with(*s)).copied().unwrap_or("");
+        *value = format!("{}{}", scheme, token(&name));
+        taken.insert(name.clone());
+        replaced.push(ReplacedSecret {
+            field: field.to_string(),
+            key: key.to_string(),
+            placeholder: name,
+        });
+    });
+    replaced
+}
+
+/// Export the given user servers as a bundle JSON string
+pub fn export_bundle(server_ids: &[String]) -> Result<String, String> {
+    let app_config = load_app_config();
+    let store = load_schema_store().unwrap_or_default();
+
+    let mut servers = Vec::new();
+    let mut placeholders: Vec<BundlePlaceholder> = Vec::new();
+    let mut taken = HashSet::new();
+    for id in server_ids {
+        let mut server = app_config
+            .user_servers
+            .iter()
+            .find(|s| s.id == *id)
+            .cloned()
+            .ok_or_else(|| format!("User server with ID '{}' not found", id))?;
+
+        // Falls back to the latest version so descriptions are still found
+        let schema = server.origin.as_ref().and_then(|o| {
+            store.schema_for_origin(o).or_else(|| store.latest(o.schema_name.as_deref()?))
+        });
+        let registry_secrets =
+            server.origin.as_ref().map(|o| origin_secret_names(&store, o)).unwrap_or_default();
+        let description = |key: &str| {
+            schema.and_then(|s| {
+                s.packages
+                    .iter()
+                    .flat_map(|p| p.environment_variables.iter())
+                    .find(|v| v.name == key)
+                    .and_then(|v| v.description.clone())
+            })
+        };
+
+        for secret in replace_secrets(&mut server.config, &registry_secrets, &mut taken) {
+            placeholders.push(BundlePlaceholder {
+                description: description(&secret.key),
+                name: secret.placeholder,
+                server_id: server.id.clone(),
+                field: secret.field,
+            });
+        }
+
+        servers.push(server);
+    }
+
+    let bundle = ServerBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        servers,
+        placeholders,
+    };
+    serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())
+}
+
+/// Parse and validate a bundle
+pub fn parse_bundle(content: &str) -> Result<ServerBundle, String> {
+    let bundle: ServerBundle =
+        serde_json::from_str(content).map_err(|e| format!("Invalid bundle: {}", e))?;
+
+    if bundle.format != BUNDLE_FORMAT {
+        return Err(format!("Not a rainMcp bundle (format '{}')", bundle.format));
+    }
+    if bundle.version == 0 || bundle.version > BUNDLE_VERSION {
+        return Err(format!("Unsupported bundle version {}", bundle.version));
+    }
+    if bundle.servers.is_empty() {
+        return Err("Bundle contains no servers".to_string());
+    }
+
+    let mut ids = HashSet::new();
+    for server in &bundle.servers {
+        if server.id.trim().is_empty() || server.name.trim().is_empty() {
+            return Err("Bundle contains a server without id or name".to_string());
+        }
+        if !ids.insert(server.id.as_str()) {
+            return Err(format!("Bundle contains server ID '{}' twice", server.id));
+        }
+    }
+
+    let declared: HashSet<&str> = bundle.placeholders.iter().map(|p| p.name.as_str()).collect();
+    for server in &bundle.servers {
+        let mut config = server.config.clone();
+        let mut undeclared = None;
+        for_each_secret_field(&mut config, |_, _, value| {
+            for name in referenced_placeholders(value) {
+                if !declared.contains(name.as_str()) {
+                    undeclared = Some(name);
+                }
+            }
+        });
+        if let Some(name) = undeclared {
+            return Err(format!("Placeholder '{}' is used but not declared", name));
+        }
+    }
+
+    Ok(bundle)
+}
+
+/// Names of `{{NAME}}` tokens in a value
+fn referenced_placeholders(value: &str) -> Vec<String> {
+    value
+        .split("{{")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("}}").map(|(name, _)| name.to_string()))
+        .collect()
+}
+
+fn find_conflicts(bundle: &ServerBundle, library: &[UserServer]) -> Vec<BundleConflict> {
+    bundle
+        .servers
+        .iter()
+        .filter_map(|server| {
+            let (kind, existing) = if let Some(s) = library.iter().find(|s| s.id == server.id) {
+                (BundleConflictKind::Id, s)
+            } else {
+                (BundleConflictKind::Name, library.iter().find(|s| s.name == server.name)?)
+            };
+            Some(BundleConflict {
+                server_id: server.id.clone(),
+                server_name: server.name.clone(),
+                kind,
+                existing_id: existing.id.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Validate a bundle and report its placeholders and conflicts without importing
+pub fn preview_bundle_import(content: &str) -> Result<BundleImportPreview, String> {
+    let bundle = parse_bundle(content)?;
+    let conflicts = find_conflicts(&bundle, &load_app_config().user_servers);
+    Ok(BundleImportPreview {
+        servers: bundle.servers,
+        placeholders: bundle.placeholders,
+        conflicts,
+    })
+}
+
+/// Import a bundle, filling placeholders from `values` and resolving conflicts with `strategy`
+pub fn import_bundle(
+    content: &str,
+    values: &HashMap<String, String>,
+    strategy: ConflictStrategy,
+) -> Result<BundleImportResult, String> {
+    let bundle = parse_bundle(content)?;
+
+    let missing: Vec<&str> = bundle
+        .placeholders
+        .iter()
+        .map(|p| p.name.as_str())
+        .filter(|name| values.get(*name).is_none_or(|v| v.is_empty()))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Missing values for placeholders: {}", missing.join(", ")));
+    }
+
+    let mut app_config = load_app_config();
+    let conflicts = find_conflicts(&bundle, &app_config.user_servers);
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let mut result = BundleImportResult { imported: Vec::new(), skipped: Vec::new() };
+    let mut changes = Vec::new();
+    for mut server in bundle.servers {
+        for_each_secret_field(&mut server.config, |_, _, value| {
+            for (name, replacement) in values {
+                *value = value.replace(&token(name), replacement);
+            }
+        });
+        server.created_at = Some(now.clone());
+
+        match (conflicts.iter().find(|c| c.server_id == server.id), strategy) {
+            (None, _) => {}
+            (Some(_), ConflictStrategy::Skip) => {
+                result.skipped.push(server.id);
+                continue;
+            }
+            (Some(conflict), ConflictStrategy::Overwrite) => {
+                // Keep the existing id so deployments and profiles stay linked
+                server.id = conflict.existing_id.clone();
+                let index = app_config.user_servers.iter().position(|s| s.id == server.id);
+                if let Some(index) = index {
+                    let before =
+                        std::mem::replace(&mut app_config.user_servers[index], server.clone());
+                    changes.push(JournalChange::user_server(
+                        &server.id,
+                        Some(before),
+                        Some(server.clone()),
+                    ));
+                    result.imported.push(server);
+                    continue;
+                }
+            }
+            (Some(_), ConflictStrategy::Rename) => {
+                server.id = uuid::Uuid::new_v4().to_string();
+                let base = server.name.clone();
+                server.name = (2..)
+                    .map(|n| format!("{}-{}", base, n))
+                    .find(|name| !app_config.user_servers.iter().any(|s| s.name == *name))
+                    .unwrap_or(base);
+            }
+        }
+
+        changes.push(JournalChange::user_server(&server.id, None, Some(server.clone())));
+        app_config.user_servers.push(server.clone());
+        result.imported.push(server);
+    }
+
+    save_app_config(&app_config)?;
+    journal::record(format!("Import bundle ({} servers)", result.imported.len()), changes);
+
+    Ok(result)
+}
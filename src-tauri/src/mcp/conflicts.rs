@@ -0,0 +1,150 @@
+//! Cross-agent drift report for same-named servers
+//!
+//! Reads every enabled agent through its adapter and compares, per server name, the
+//! definitions field by field. Env and header values are redacted in the report but
+//! still compared on their real values.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use log::debug;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::load_app_config;
+
+use super::agent_config::read_agent_config;
+use super::{get_enabled_agents, AgentServerEntry, AgentType};
+
+const REDACTED: &str = "<redacted>";
+
+/// Value of one field in one agent (`None` when the agent does not set the field)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentFieldValue {
+    pub agent_type: AgentType,
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldDifference {
+    /// Field path, e.g. `command`, `args`, `env.API_KEY`, `headers.Authorization`
+    pub field: String,
+    pub values: Vec<AgentFieldValue>,
+}
+
+/// Report for one server name
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerDrift {
+    pub server_name: String,
+    pub agents: Vec<AgentType>,
+
+    /// Empty when every agent has the same definition
+    pub differences: Vec<FieldDifference>,
+}
+
+/// A field value: the raw value for comparison and the value shown in the report
+struct FieldValue {
+    raw: String,
+    display: String,
+}
+
+fn plain(value: String) -> FieldValue {
+    FieldValue { display: value.clone(), raw: value }
+}
+
+fn secret(value: &str) -> FieldValue {
+    FieldValue { raw: value.to_string(), display: REDACTED.to_string() }
+}
+
+/// Flatten an entry into `field path -> value`
+fn flatten_entry(entry: &AgentServerEntry) -> BTreeMap<String, FieldValue> {
+    let mut fields = BTreeMap::new();
+
+    match entry {
+        AgentServerEntry::Local(local) => {
+            fields.insert("type".to_string(), plain("local".to_string()));
+            fields.insert("command".to_string(), plain(local.command.clone()));
+            if let Some(args) = &local.args {
+                fields.insert("args".to_string(), plain(args.join(" ")));
+            }
+            if let Some(timeout) = local.base.timeout {
+                fields.insert("timeout".to_string(), plain(timeout.to_string()));
+            }
+            for (key, value) in local.env.iter().flatten() {
+                fields.insert(format!("env.{}", key), secret(value));
+            }
+        }
+        AgentServerEntry::Remote(remote) => {
+            fields.insert("type".to_string(), plain("remote".to_string()));
+            fields.insert("url".to_string(), plain(remote.url.clone()));
+            if let Some(timeout) = remote.base.timeout {
+                fields.insert("timeout".to_string(), plain(timeout.to_string()));
+            }
+            for (key, value) in remote.headers.iter().flatten() {
+                fields.insert(format!("headers.{}", key), secret(value));
+            }
+        }
+    }
+
+    fields
+}
+
+/// Compare the definitions of one server name across agents
+pub fn diff_server_definitions(entries: &[(AgentType, AgentServerEntry)]) -> Vec<FieldDifference> {
+    let flattened: Vec<(AgentType, BTreeMap<String, FieldValue>)> =
+        entries.iter().map(|(agent, entry)| (agent.clone(), flatten_entry(entry))).collect();
+
+    let field_names: BTreeSet<&String> =
+        flattened.iter().flat_map(|(_, fields)| fields.keys()).collect();
+
+    field_names
+        .into_iter()
+        .filter_map(|field| {
+            let raw: Vec<Option<&str>> = flattened
+                .iter()
+                .map(|(_, fields)| fields.get(field).map(|v| v.raw.as_str()))
+                .collect();
+            if raw.iter().all(|value| *value == raw[0]) {
+                return None;
+            }
+
+            let values = flattened
+                .iter()
+                .map(|(agent, fields)| AgentFieldValue {
+                    agent_type: agent.clone(),
+                    value: fields.get(field).map(|v| v.display.clone()),
+                })
+                .collect();
+            Some(FieldDifference { field: field.clone(), values })
+        })
+        .collect()
+}
+
+/// Build the drift report over all enabled agents, sorted by server name
+pub fn get_cross_agent_report() -> Vec<ServerDrift> {
+    let mut agents = get_enabled_agents(&load_app_config());
+    agents.sort_by_key(|agent| agent.to_string());
+
+    let mut by_name: BTreeMap<String, Vec<(AgentType, AgentServerEntry)>> = BTreeMap::new();
+    for agent in agents {
+        match read_agent_config(agent.clone()) {
+            Ok(config) => {
+                for (name, entry) in config.servers {
+                    by_name.entry(name).or_default().push((agent.clone(), entry));
+                }
+            }
+            Err(e) => debug!("Skipping {} in drift report: {}", agent, e),
+        }
+    }
+
+    by_name
+        .into_iter()
+        .map(|(server_name, entries)| ServerDrift {
+            differences: diff_server_definitions(&entries),
+            agents: entries.into_iter().map(|(agent, _)| agent).collect(),
+            server_name,
+        })
+        .collect()
+}
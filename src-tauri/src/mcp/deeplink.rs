@@ -0,0 +1,216 @@
+//! One-click install links
+//!
+//! Parses the install links published for Cursor
+//! (`cursor://anysphere.cursor-deeplink/mcp/install?name=...&config=<base64>`) and
+//! VS Code (`vscode:mcp/install?<json>`) into user servers. rainMcp registers its own
+//! `rainmcp://install` scheme, which accepts either payload and adds the server to the
+//! library instead of to a single agent.
+//!
+//! Any web page can open such a link, so links delivered by the OS are only previewed;
+//! the server is added once the user confirms it in the UI through
+//! `install_from_link_command`.
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE};
+use base64::Engine;
+use log::{error, info};
+use percent_encoding::percent_decode_str;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+use url::Url;
+
+use crate::config::{load_app_config, save_app_config};
+
+use super::agent_adapters::common::parse_server_entry;
+use super::journal::{self, JournalChange};
+use super::user_server::{OriginType, ServerOrigin, UserServer};
+
+pub const RAINMCP_SCHEME: &str = "rainmcp";
+
+pub const INSTALL_LINK_RECEIVED_EVENT: &str = "install-link-received";
+pub const INSTALL_LINK_FAILED_EVENT: &str = "install-link-failed";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallLinkSource {
+    Cursor,
+    VsCode,
+    RainMcp,
+}
+
+/// A server parsed from an install link
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallLink {
+    pub source: InstallLinkSource,
+
+    /// Proposed user server with a fresh id
+    pub server: UserServer,
+}
+
+/// Payload of the `install-link-received` event: a server waiting for confirmation
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallLinkReceivedEvent {
+    /// The link as received, to pass to `install_from_link_command` once confirmed
+    pub link: String,
+
+    #[serde(flatten)]
+    pub install: InstallLink,
+}
+
+/// Payload of the `install-link-failed` event
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallLinkFailedEvent {
+    pub link: String,
+    pub error: String,
+}
+
+/// Parse a Cursor, VS Code or rainMcp install link
+pub fn parse_install_link(link: &str) -> Result<InstallLink, String> {
+    let url = Url::parse(link.trim()).map_err(|e| format!("Invalid install link: {}", e))?;
+
+    let source = match url.scheme() {
+        "cursor" => InstallLinkSource::Cursor,
+        "vscode" | "vscode-insiders" => InstallLinkSource::VsCode,
+        RAINMCP_SCHEME => InstallLinkSource::RainMcp,
+        other => return Err(format!("Unsupported install link scheme '{}'", other)),
+    };
+
+    // `cursor://anysphere.cursor-deeplink/mcp/install`, `vscode:mcp/install`, `rainmcp://install`
+    let target = format!("{}{}", url.host_str().unwrap_or_default(), url.path());
+    let is_install = match source {
+        InstallLinkSource::Cursor => target == "anysphere.cursor-deeplink/mcp/install",
+        InstallLinkSource::VsCode => target == "mcp/install",
+        InstallLinkSource::RainMcp => target.trim_end_matches('/') == "install",
+    };
+    if !is_install {
+        return Err(format!("Not an MCP install link: {}", link));
+    }
+
+    let query = url.query().filter(|q| !q.is_empty()).ok_or("Install link has no payload")?;
+    let has_config = url.query_pairs().any(|(key, _)| key == "config");
+    let (name, config) = match source {
+        InstallLinkSource::Cursor => parse_cursor_payload(&url)?,
+        InstallLinkSource::VsCode => parse_json_payload(query)?,
+        InstallLinkSource::RainMcp if has_config => parse_cursor_payload(&url)?,
+        InstallLinkSource::RainMcp => parse_json_payload(query)?,
+    };
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Install link has no server name".to_string());
+    }
+
+    Ok(InstallLink {
+        source,
+        server: UserServer {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            config: parse_server_entry(&config)?,
+            origin: Some(ServerOrigin {
+                origin_type: OriginType::Custom,
+                schema_name: None,
+                package_id: None,
+                version: None,
+            }),
+            created_at: None,
+        },
+    })
+}
+
+/// `?name=<name>&config=<base64 json>`
+fn parse_cursor_payload(url: &Url) -> Result<(String, Value), String> {
+    let param = |key: &str| url.query_pairs().find(|(k, _)| k == key).map(|(_, v)| v.into_owned());
+
+    let name = param("name").ok_or("Install link has no 'name' parameter")?;
+    let encoded = param("config").ok_or("Install link has no 'config' parameter")?;
+
+    // Links in the wild use both alphabets, with and without padding
+    let encoded = encoded.trim().trim_end_matches('=');
+    let padded = format!("{}{}", encoded, "=".repeat((4 - encoded.len() % 4) % 4));
+    let bytes = STANDARD
+        .decode(&padded)
+        .or_else(|_| URL_SAFE.decode(&padded))
+        .map_err(|e| format!("Invalid base64 in 'config' parameter: {}", e))?;
+
+    let config = serde_json::from_slice(&bytes)
+        .map_err(|e| format!("Invalid JSON in 'config' parameter: {}", e))?;
+    Ok((name, config))
+}
+
+/// `?<percent-encoded json>` with the server name inside the object
+fn parse_json_payload(query: &str) -> Result<(String, Value), String> {
+    let json = percent_decode_str(query)
+        .decode_utf8()
+        .map_err(|e| format!("Invalid install link payload: {}", e))?;
+    let mut config: Value = serde_json::from_str(&json)
+        .map_err(|e| format!("Invalid JSON in install link payload: {}", e))?;
+
+    let name = config
+        .as_object_mut()
+        .and_then(|obj| obj.remove("name"))
+        .and_then(|v| v.as_str().map(str::to_string))
+        .ok_or("Install link payload has no 'name' field")?;
+    Ok((name, config))
+}
+
+/// Add the server from an install link to the library
+///
+/// A library server with the same name and config is returned as is; a name clash with a
+/// different config gets a `-2`, `-3`, ... suffix.
+pub fn install_from_link(link: &str) -> Result<UserServer, String> {
+    let mut server = parse_install_link(link)?.server;
+    let mut app_config = load_app_config();
+
+    if let Some(existing) =
+        app_config.user_servers.iter().find(|s| s.name == server.name && s.config == server.config)
+    {
+        return Ok(existing.clone());
+    }
+
+    let taken = |name: &str| app_config.user_servers.iter().any(|s| s.name == name);
+    if taken(&server.name) {
+        let base = server.name.clone();
+        server.name = (2..).map(|n| format!("{}-{}", base, n)).find(|n| !taken(n)).unwrap_or(base);
+    }
+    server.created_at = Some(chrono::Utc::now().to_rfc3339());
+
+    app_config.user_servers.push(server.clone());
+    save_app_config(&app_config)?;
+    journal::record(
+        format!("Add user server '{}' from install link", server.name),
+        vec![JournalChange::user_server(&server.id, None, Some(server.clone()))],
+    );
+
+    Ok(server)
+}
+
+/// Preview of a link delivered by the OS; nothing is added to the library
+pub fn receive_install_link(link: &str) -> Result<InstallLinkReceivedEvent, String> {
+    Ok(InstallLinkReceivedEvent { link: link.to_string(), install: parse_install_link(link)? })
+}
+
+/// Handle `rainmcp://` URLs delivered by the OS, asking the UI to confirm each server
+pub fn handle_opened_urls(app: &AppHandle, urls: &[String]) {
+    for link in urls {
+        match receive_install_link(link) {
+            Ok(event) => {
+                info!(
+                    "Received install link for '{}', awaiting confirmation",
+                    event.install.server.name
+                );
+                let _ = app.emit(INSTALL_LINK_RECEIVED_EVENT, event);
+            }
+            Err(e) => {
+                error!("Failed to handle install link {}: {}", link, e);
+                let _ = app.emit(
+                    INSTALL_LINK_FAILED_EVENT,
+                    InstallLinkFailedEvent { link: link.clone(), error: e },
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Deploy a user server to several agents as one transaction
+//!
+//! All target configs are read and rendered first; if any agent cannot be prepared
+//! nothing is written. Otherwise all files are written, and the originals are restored
+//! if any write fails.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{load_app_config, save_app_config};
+
+use super::agent_config::read_agent_config_or_empty;
+use super::linked_servers::record_deployment;
+use super::transaction::{AgentConfigTransaction, AgentWriteOutcome, WriteStatus};
+use super::vault::config_for_agent;
+use super::AgentType;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployReport {
+    /// True when every agent config was written
+    pub committed: bool,
+    pub agents: Vec<AgentWriteOutcome>,
+}
+
+pub fn deploy_user_server(
+    server_id: &str,
+    agents: Vec<AgentType>,
+    server_name: Option<String>,
+) -> Result<DeployReport, String> {
+    let mut app_config = load_app_config();
+    let server = app_config
+        .user_servers
+        .iter()
+        .find(|s| s.id == server_id)
+        .cloned()
+        .ok_or_else(|| format!("User server with ID '{}' not found", server_id))?;
+    let name = server_name.unwrap_or_else(|| server.name.clone());
+
+    let mut transaction = AgentConfigTransaction::new();
+    let mut prepared: Vec<AgentWriteOutcome> = Vec::new();
+    for agent in &agents {
+        let staged = read_agent_config_or_empty(agent.clone()).and_then(|mut config| {
+            if config.servers.contains_key(&name) {
+                return Err(format!("Server '{}' already exists in agent config", name));
+            }
+            config.servers.insert(name.clone(), config_for_agent(&server.config, agent)?);
+            transaction.stage(agent.clone(), config)
+        });
+        prepared.push(AgentWriteOutcome {
+            agent_type: agent.clone(),
+            status: if staged.is_ok() { WriteStatus::NotAttempted } else { WriteStatus::Failed },
+            error: staged.err(),
+        });
+    }
+
+    if prepared.iter().any(|o| o.status == WriteStatus::Failed) {
+        return Ok(DeployReport { committed: false, agents: prepared });
+    }
+
+    let outcomes = transaction.commit_with_report();
+    let committed = outcomes.iter().all(|o| o.status == WriteStatus::Written);
+    if committed {
+        for agent in agents {
+            record_deployment(&mut app_config, &server, agent, &name);
+        }
+        save_app_config(&app_config)?;
+    }
+
+    Ok(DeployReport { committed, agents: outcomes })
+}
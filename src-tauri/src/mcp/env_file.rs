@@ -0,0 +1,256 @@
+//! `.env` files as a source of server env values
+//!
+//! The parser follows the common dotenv dialect: `KEY=value` lines with optional
+//! `export` prefixes, `#` comments, single quotes (literal), double quotes (with
+//! `\n`-style escapes, may span lines) and backslash escapes in bare values. Adjacent
+//! quoted and bare segments are joined as in a shell, so files written for `source`
+//! parse too.
+
+use std::collections::{BTreeSet, HashMap};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::registry::load_schema_store;
+use super::server_schema::EnvironmentVariable;
+use super::user_server::UserServer;
+use super::AgentServerEntry;
+
+/// A server with env values filled from a `.env` file
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvFileImport {
+    /// The server to save, with matching values filled in
+    pub server: UserServer,
+
+    /// Variables whose value came from the file
+    pub filled: Vec<String>,
+
+    /// Required variables that still have no value
+    pub missing_required: Vec<String>,
+
+    /// Variables in the file that the server does not use
+    pub unused: Vec<String>,
+}
+
+/// Parse `.env` content into `(key, value)` pairs in file order
+pub fn parse_env_file(content: &str) -> Result<Vec<(String, String)>, String> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut vars = Vec::new();
+    let mut i = 0;
+    let mut line = 1;
+
+    let skip_blanks = |i: &mut usize| {
+        while chars.get(*i).is_some_and(|c| *c == ' ' || *c == '\t' || *c == '\r') {
+            *i += 1;
+        }
+    };
+    let read_key = |i: &mut usize| {
+        let start = *i;
+        while chars.get(*i).is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '.') {
+            *i += 1;
+        }
+        chars[start..*i].iter().collect::<String>()
+    };
+
+    while i < chars.len() {
+        skip_blanks(&mut i);
+        match chars.get(i) {
+            None => break,
+            Some('\n') => {
+                line += 1;
+                i += 1;
+                continue;
+            }
+            Some('#') => {
+                while chars.get(i).is_some_and(|c| *c != '\n') {
+                    i += 1;
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        let mut key = read_key(&mut i);
+        if key == "export" && chars.get(i).is_some_and(|c| *c == ' ' || *c == '\t') {
+            skip_blanks(&mut i);
+            key = read_key(&mut i);
+        }
+        skip_blanks(&mut i);
+        if key.is_empty() || chars.get(i) != Some(&'=') {
+            return Err(format!("Invalid line {}: expected KEY=value", line));
+        }
+        i += 1;
+        skip_blanks(&mut i);
+
+        let start_line = line;
+        let mut value = String::new();
+        while let Some(&c) = chars.get(i) {
+            match c {
+                '\n' => break,
+                '\'' => {
+                    let end = (i + 1..chars.len())
+                        .find(|&j| chars[j] == '\'')
+                        .ok_or_else(|| format!("Unterminated quote on line {}", start_line))?;
+                    let segment: String = chars[i + 1..end].iter().collect();
+                    line += segment.matches('\n').count();
+                    value.push_str(&segment);
+                    i = end + 1;
+                }
+                '"' => {
+                    i += 1;
+                    loop {
+                        match chars.get(i) {
+                            None => {
+                                return Err(format!("Unterminated quote on line {}", start_line))
+                            }
+                            Some('"') => break,
+                            Some('\\') => {
+                                match chars.get(i + 1) {
+                                    Some('n') => value.push('\n'),
+                                    Some('t') => value.push('\t'),
+                                    Some('r') => value.push('\r'),
+                                    Some(&e @ ('"' | '\\' | '$' | '`')) => value.push(e),
+                                    Some(&other) => {
+                                        value.push('\\');
+                                        value.push(other);
+                                    }
+                                    None => value.push('\\'),
+                                }
+                                i += 2;
+                                continue;
+                            }
+                            Some(&other) => {
+                                if other == '\n' {
+                                    line += 1;
+                                }
+                                value.push(other);
+                            }
+                        }
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                '\\' => {
+                    if let Some(&next) = chars.get(i + 1).filter(|c| **c != '\n') {
+                        value.push(next);
+                    }
+                    i += 2;
+                }
+                ' ' | '\t' | '\r' => {
+                    // Trailing blanks and ` # comments` end the value; inner blanks stay
+                    let mut j = i;
+                    while chars.get(j).is_some_and(|c| *c == ' ' || *c == '\t' || *c == '\r') {
+                        j += 1;
+                    }
+                    if matches!(chars.get(j), None | Some('\n') | Some('#')) {
+                        i = j;
+                        break;
+                    }
+                    value.extend(&chars[i..j]);
+                    i = j;
+                }
+                other => {
+                    value.push(other);
+                    i += 1;
+                }
+            }
+        }
+
+        // Skip a trailing comment
+        while chars.get(i).is_some_and(|c| *c != '\n') {
+            i += 1;
+        }
+        vars.push((key, value));
+    }
+
+    Ok(vars)
+}
+
+/// Env vars the registry declares for the server's package (at its pinned version), if it
+/// came from the registry
+fn registry_env_vars(server: &UserServer) -> Vec<EnvironmentVariable> {
+    let Some(origin) = &server.origin else {
+        return Vec::new();
+    };
+    let Ok(store) = load_schema_store() else {
+        return Vec::new();
+    };
+    let Some(schema) = store.schema_for_origin(origin) else {
+        return Vec::new();
+    };
+
+    let package = match &origin.package_id {
+        Some(id) => {
+            schema.packages.iter().find(|p| format!("{}:{}", p.registry_type, p.identifier) == *id)
+        }
+        None if schema.packages.len() == 1 => schema.packages.first(),
+        None => None,
+    };
+    package.map(|p| p.environment_variables.clone()).unwrap_or_default()
+}
+
+/// A value that still needs to be filled in by the user
+fn is_unset(value: &str) -> bool {
+    let value = value.trim();
+    value.is_empty() || (value.starts_with('<') && value.ends_with('>'))
+}
+
+/// Fill env values of a (not yet saved) local server from `.env` content
+///
+/// Keys are matched against the registry package's environment variables and the
+/// server's existing env entries. Registry variables marked required and existing
+/// entries left empty are reported when the file does not provide them.
+pub fn apply_env_file(server: &UserServer, content: &str) -> Result<EnvFileImport, String> {
+    let file_vars: HashMap<String, String> = parse_env_file(content)?.into_iter().collect();
+
+    let registry_vars = registry_env_vars(server);
+
+    let mut server = server.clone();
+    let AgentServerEntry::Local(local) = &mut server.config else {
+        return Err("Only local servers take environment variables".to_string());
+    };
+    let env = local.env.get_or_insert_with(HashMap::new);
+
+    let known: BTreeSet<String> =
+        registry_vars.iter().map(|v| v.name.clone()).chain(env.keys().cloned()).collect();
+
+    let mut filled = Vec::new();
+    for name in &known {
+        if let Some(value) = file_vars.get(name) {
+            env.insert(name.clone(), value.clone());
+            filled.push(name.clone());
+        }
+    }
+
+    // Entries already in the config count as required unless the registry says otherwise
+    let optional: Vec<&str> =
+        registry_vars.iter().filter(|v| !v.is_required).map(|v| v.name.as_str()).collect();
+    let mut missing_required: BTreeSet<String> = env
+        .iter()
+        .filter(|(name, value)| is_unset(value) && !optional.contains(&name.as_str()))
+        .map(|(name, _)| name.clone())
+        .collect();
+    for var in registry_vars.iter().filter(|v| v.is_required) {
+        let has_value = env.get(&var.name).is_some_and(|v| !is_unset(v))
+            || var.value.is_some()
+            || var.default.is_some();
+        if !has_value {
+            missing_required.insert(var.name.clone());
+        }
+    }
+
+    let mut unused: Vec<String> =
+        file_vars.into_keys().filter(|name| !known.contains(name)).collect();
+    unused.sort();
+
+    if env.is_empty() {
+        local.env = None;
+    }
+    Ok(EnvFileImport {
+        server,
+        filled,
+        missing_required: missing_required.into_iter().collect(),
+        unused,
+    })
+}
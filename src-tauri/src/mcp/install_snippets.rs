@@ -0,0 +1,206 @@
+//! Ready-to-paste install instructions for a server
+//!
+//! For every agent the server is brought into the form deploys write (vault references
+//! become the agent's env references), rendered through the agent's adapter into an
+//! empty config and extracted again, so the fragment is byte-for-byte what the
+//! (revealed) raw config shows after deploying to that agent. Snippets are meant to be
+//! shared, so secret values are the exception: vault secrets an agent would get inline
+//! and secret env values and headers become `{{NAME}}` placeholders, as in bundles.
+//! Agents with an MCP CLI also get the equivalent shell command.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::agent_adapters::adapter_for_definition;
+use super::agent_definition::{load_agent_definitions, AgentConfigFormat};
+use super::bundle::replace_secrets;
+use super::redact::origin_secret_names;
+use super::registry::load_schema_store;
+use super::user_server::UserServer;
+use super::vault::{secret_references, shareable_form};
+use super::{AgentServerEntry, AgentServers, AgentType};
+
+/// Install instructions for one agent
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentInstallSnippet {
+    pub agent_type: AgentType,
+    pub display_name: String,
+
+    /// Global config file the fragment belongs in (resolved for this machine)
+    pub config_path: Option<PathBuf>,
+
+    /// `json` or `toml`
+    pub language: String,
+
+    /// Server fragment in the agent's dialect
+    pub snippet: String,
+
+    /// Equivalent command for agents with an MCP CLI
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cli_command: Option<String>,
+}
+
+/// Install snippets for every known agent, deploying the server as `server_name`
+pub fn generate_install_snippets(
+    server: &UserServer,
+    server_name: Option<&str>,
+) -> Result<Vec<AgentInstallSnippet>, String> {
+    let name = server_name.unwrap_or(&server.name);
+    if name.trim().is_empty() {
+        return Err("Server name cannot be empty".to_string());
+    }
+
+    let secret_names = match &server.origin {
+        Some(origin) => origin_secret_names(&load_schema_store().unwrap_or_default(), origin),
+        None => HashSet::new(),
+    };
+    // Placeholders for raw values must not clash with those for vault secrets
+    let values = match &server.config {
+        AgentServerEntry::Local(local) => local.env.as_ref(),
+        AgentServerEntry::Remote(remote) => remote.headers.as_ref(),
+    };
+    let vault_names: HashSet<String> =
+        values.into_iter().flatten().flat_map(|(_, value)| secret_references(value)).collect();
+
+    load_agent_definitions()
+        .into_iter()
+        .map(|definition| {
+            let agent = definition.agent_type();
+            let adapter = adapter_for_definition(definition.clone());
+            let mut shared = shareable_form(&server.config, &agent)?;
+            replace_secrets(&mut shared, &secret_names, &mut vault_names.clone());
+            let config = AgentServers {
+                servers: [(name.to_string(), shared.clone())].into_iter().collect(),
+            };
+            let snippet = adapter
+                .render_config_from("", config)
+                .and_then(|content| adapter.server_raw_config_from(&content, name))
+                .map_err(|e| format!("{}: {}", agent, e))?;
+
+            let language = match definition.format {
+                AgentConfigFormat::Toml => "toml",
+                AgentConfigFormat::Json | AgentConfigFormat::OpenCode => "json",
+            };
+
+            Ok(AgentInstallSnippet {
+                cli_command: cli_command(&agent, name, &shared, &snippet)?,
+                display_name: definition.display_name().to_string(),
+                config_path: definition.config_path().ok(),
+                language: language.to_string(),
+                snippet,
+                agent_type: agent,
+            })
+        })
+        .collect()
+}
+
+fn cli_command(
+    agent: &AgentType,
+    name: &str,
+    config: &AgentServerEntry,
+    snippet: &str,
+) -> Result<Option<String>, String> {
+    let command = match agent {
+        AgentType::ClaudeCode => {
+            let entry = snippet_entry(snippet, name)?;
+            Some(format!("claude mcp add-json --scope user {} {}", quote(name), quote(&entry)))
+        }
+        AgentType::OpenAiCodex => codex_command(name, config),
+        AgentType::GeminiCli => Some(gemini_command(name, config)),
+        AgentType::VsCodeCopilot => {
+            let mut entry: Value =
+                serde_json::from_str(&snippet_entry(snippet, name)?).map_err(|e| e.to_string())?;
+            if let Some(obj) = entry.as_object_mut() {
+                obj.insert("name".to_string(), Value::String(name.to_string()));
+            }
+            Some(format!("code --add-mcp {}", quote(&entry.to_string())))
+        }
+        _ => None,
+    };
+    Ok(command)
+}
+
+/// The server object of a `{"<name>": {...}}` fragment as compact JSON
+fn snippet_entry(snippet: &str, name: &str) -> Result<String, String> {
+    let wrapper: Value = serde_json::from_str(snippet).map_err(|e| e.to_string())?;
+    wrapper
+        .get(name)
+        .map(Value::to_string)
+        .ok_or_else(|| format!("Server '{}' not found in snippet", name))
+}
+
+/// `codex mcp add <name> --env K=V -- <command> <args>`; Codex has no CLI flag for headers
+fn codex_command(name: &str, config: &AgentServerEntry) -> Option<String> {
+    let mut parts = vec!["codex".to_string(), "mcp".to_string(), "add".to_string(), quote(name)];
+    match config {
+        AgentServerEntry::Local(local) => {
+            for (key, value) in sorted(local.env.as_ref()) {
+                parts.push("--env".to_string());
+                parts.push(quote(&format!("{}={}", key, value)));
+            }
+            parts.push("--".to_string());
+            parts.push(quote(&local.command));
+            parts.extend(local.args.iter().flatten().map(|a| quote(a)));
+        }
+        AgentServerEntry::Remote(remote) => {
+            if remote.headers.as_ref().is_some_and(|h| !h.is_empty()) {
+                return None;
+            }
+            parts.push("--url".to_string());
+            parts.push(quote(&remote.url));
+        }
+    }
+    Some(parts.join(" "))
+}
+
+/// `gemini mcp add --scope user [-e K=V | --transport http -H 'K: V'] <name> <command|url> <args>`
+fn gemini_command(name: &str, config: &AgentServerEntry) -> String {
+    let mut parts = ["gemini", "mcp", "add", "--scope", "user"].map(String::from).to_vec();
+    match config {
+        AgentServerEntry::Local(local) => {
+            for (key, value) in sorted(local.env.as_ref()) {
+                parts.push("-e".to_string());
+                parts.push(quote(&format!("{}={}", key, value)));
+            }
+            parts.push(quote(name));
+            parts.push(quote(&local.command));
+            if local.args.as_ref().is_some_and(|a| !a.is_empty()) {
+                parts.push("--".to_string());
+                parts.extend(local.args.iter().flatten().map(|a| quote(a)));
+            }
+        }
+        AgentServerEntry::Remote(remote) => {
+            parts.push("--transport".to_string());
+            parts.push("http".to_string());
+            for (key, value) in sorted(remote.headers.as_ref()) {
+                parts.push("-H".to_string());
+                parts.push(quote(&format!("{}: {}", key, value)));
+            }
+            parts.push(quote(name));
+            parts.push(quote(&remote.url));
+        }
+    }
+    parts.join(" ")
+}
+
+fn sorted(map: Option<&std::collections::HashMap<String, String>>) -> Vec<(&String, &String)> {
+    let mut entries: Vec<_> = map.into_iter().flatten().collect();
+    entries.sort();
+    entries
+}
+
+/// Quote a word for POSIX shells when it contains anything but safe characters
+fn quote(word: &str) -> String {
+    let safe = !word.is_empty()
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || "@%+=:,./_-".contains(c));
+    if safe {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
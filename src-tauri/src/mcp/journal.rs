@@ -0,0 +1,584 @@
+//! Append-only journal of mutations made by rainMcp, with undo/redo
+//!
+//! Every user server change, agent config write and enabled-agent change is appended
+//! to `journal.jsonl` with its before/after state. Agent configs are recorded as the
+//! servers that changed, not whole files. Undo and redo never rewrite past entries;
+//! they apply the recorded state and append a new entry referring to the original one.
+//! The undo and redo stacks are rebuilt by replaying the file.
+//!
+//! Recorded states hold server configs and thus credentials, so each entry's changes are
+//! sealed with the machine-local key, the file is private to the user and only the
+//! newest entries are kept. Entries handed to the UI are redacted.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use log::warn;
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{get_journal_path, load_app_config, save_app_config, ClientConfigItem};
+
+use super::agent_adapters;
+use super::redact::redact_config;
+use super::sealed;
+use super::user_server::{ServerDeployment, UserServer};
+use super::vault::write_private_file;
+use super::{AgentServerEntry, AgentServers, AgentType};
+
+const JOURNAL_AAD: &[u8] = b"rainmcp-journal-v1";
+
+/// The file is compacted to `KEEP_ENTRIES` once it holds more than `MAX_ENTRIES`
+const MAX_ENTRIES: usize = 500;
+const KEEP_ENTRIES: usize = 400;
+
+/// Serializes appends and compaction
+static JOURNAL_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// One recorded state change
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum JournalChange {
+    /// A user server was added (`before` empty), updated or deleted (`after` empty)
+    #[serde(rename_all = "camelCase")]
+    UserServer {
+        server_id: String,
+        before: Option<Box<UserServer>>,
+        after: Option<Box<UserServer>>,
+    },
+
+    /// Servers of an agent config that changed; a name missing on one side did not exist
+    #[serde(rename_all = "camelCase")]
+    AgentConfig {
+        agent_type: AgentType,
+        before: HashMap<String, AgentServerEntry>,
+        after: HashMap<String, AgentServerEntry>,
+    },
+
+    /// Enabled flag per agent name
+    #[serde(rename_all = "camelCase")]
+    EnabledAgents { before: HashMap<String, bool>, after: HashMap<String, bool> },
+
+    /// Deployment records of a user server
+    #[serde(rename_all = "camelCase")]
+    ServerDeployments {
+        server_id: String,
+        before: Vec<ServerDeployment>,
+        after: Vec<ServerDeployment>,
+    },
+
+    /// Server ids of a profile
+    #[serde(rename_all = "camelCase")]
+    ProfileServers { profile_id: String, before: Vec<String>, after: Vec<String> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum JournalAction {
+    Do,
+    Undo,
+    Redo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    pub id: String,
+    pub timestamp: String,
+    pub action: JournalAction,
+
+    /// Human-readable description, e.g. "Delete user server 'github'"
+    pub operation: String,
+
+    /// For undo/redo entries: id of the original entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+
+    /// State changes applied by this entry
+    pub changes: Vec<JournalChange>,
+}
+
+/// On-disk form of an entry, with `changes` sealed
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredEntry {
+    id: String,
+    timestamp: String,
+    action: JournalAction,
+    operation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target: Option<String>,
+    changes: String,
+}
+
+impl StoredEntry {
+    fn seal(entry: &JournalEntry) -> Result<Self, String> {
+        let changes = serde_json::to_string(&entry.changes).map_err(|e| e.to_string())?;
+        Ok(StoredEntry {
+            id: entry.id.clone(),
+            timestamp: entry.timestamp.clone(),
+            action: entry.action,
+            operation: entry.operation.clone(),
+            target: entry.target.clone(),
+            changes: sealed::seal(&changes, JOURNAL_AAD)?,
+        })
+    }
+
+    fn open(self, key: &sealed::SealingKey) -> Result<JournalEntry, String> {
+        let changes = key.open(&self.changes, JOURNAL_AAD)?;
+        Ok(JournalEntry {
+            id: self.id,
+            timestamp: self.timestamp,
+            action: self.action,
+            operation: self.operation,
+            target: self.target,
+            changes: serde_json::from_str(&changes).map_err(|e| e.to_string())?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalView {
+    pub entries: Vec<JournalEntry>,
+    pub can_undo: usize,
+    pub can_redo: usize,
+}
+
+impl JournalChange {
+    pub fn user_server(
+        server_id: &str,
+        before: Option<UserServer>,
+        after: Option<UserServer>,
+    ) -> Self {
+        JournalChange::UserServer {
+            server_id: server_id.to_string(),
+            before: before.map(Box::new),
+            after: after.map(Box::new),
+        }
+    }
+
+    /// Servers that differ between two states of an agent config
+    pub fn agent_config(agent: AgentType, before: &AgentServers, after: &AgentServers) -> Self {
+        let names: HashSet<&String> = before.servers.keys().chain(after.servers.keys()).collect();
+        let changed: Vec<&String> = names
+            .into_iter()
+            .filter(|name| before.servers.get(*name) != after.servers.get(*name))
+            .collect();
+        let pick = |servers: &AgentServers| -> HashMap<String, AgentServerEntry> {
+            changed
+                .iter()
+                .filter_map(|name| servers.servers.get(*name).map(|e| ((*name).clone(), e.clone())))
+                .collect()
+        };
+        JournalChange::AgentConfig { agent_type: agent, before: pick(before), after: pick(after) }
+    }
+
+    fn inverted(&self) -> Self {
+        match self.clone() {
+            JournalChange::UserServer { server_id, before, after } => {
+                JournalChange::UserServer { server_id, before: after, after: before }
+            }
+            JournalChange::AgentConfig { agent_type, before, after } => {
+                JournalChange::AgentConfig { agent_type, before: after, after: before }
+            }
+            JournalChange::EnabledAgents { before, after } => {
+                JournalChange::EnabledAgents { before: after, after: before }
+            }
+            JournalChange::ServerDeployments { server_id, before, after } => {
+                JournalChange::ServerDeployments { server_id, before: after, after: before }
+            }
+            JournalChange::ProfileServers { profile_id, before, after } => {
+                JournalChange::ProfileServers { profile_id, before: after, after: before }
+            }
+        }
+    }
+
+    fn is_noop(&self) -> bool {
+        match self {
+            JournalChange::UserServer { before, after, .. } => same_server(before, after),
+            JournalChange::AgentConfig { before, after, .. } => before == after,
+            JournalChange::EnabledAgents { before, after } => before == after,
+            JournalChange::ServerDeployments { before, after, .. } => before == after,
+            JournalChange::ProfileServers { before, after, .. } => before == after,
+        }
+    }
+
+    /// Copy with secrets in server configs masked, for display
+    fn redacted(&self) -> Self {
+        let mask = |config: &AgentServerEntry| redact_config(config, &HashSet::new());
+        let mask_server = |server: &Option<Box<UserServer>>| {
+            server
+                .as_ref()
+                .map(|s| Box::new(UserServer { config: mask(&s.config), ..(**s).clone() }))
+        };
+        let mask_servers = |servers: &HashMap<String, AgentServerEntry>| {
+            servers.iter().map(|(name, config)| (name.clone(), mask(config))).collect()
+        };
+        let mask_deployments = |deployments: &Vec<ServerDeployment>| {
+            deployments
+                .iter()
+                .map(|d| ServerDeployment {
+                    deployed_config: mask(&d.deployed_config),
+                    ..d.clone()
+                })
+                .collect()
+        };
+
+        match self {
+            JournalChange::UserServer { server_id, before, after } => JournalChange::UserServer {
+                server_id: server_id.clone(),
+                before: mask_server(before),
+                after: mask_server(after),
+            },
+            JournalChange::AgentConfig { agent_type, before, after } => {
+                JournalChange::AgentConfig {
+                    agent_type: agent_type.clone(),
+                    before: mask_servers(before),
+                    after: mask_servers(after),
+                }
+            }
+            JournalChange::ServerDeployments { server_id, before, after } => {
+                JournalChange::ServerDeployments {
+                    server_id: server_id.clone(),
+                    before: mask_deployments(before),
+                    after: mask_deployments(after),
+                }
+            }
+            JournalChange::EnabledAgents { .. } | JournalChange::ProfileServers { .. } => {
+                self.clone()
+            }
+        }
+    }
+}
+
+impl JournalEntry {
+    /// Copy with secrets masked, for display
+    fn redacted(&self) -> Self {
+        JournalEntry {
+            changes: self.changes.iter().map(|c| c.redacted()).collect(),
+            ..self.clone()
+        }
+    }
+}
+
+fn same_server(a: &Option<Box<UserServer>>, b: &Option<Box<UserServer>>) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Enabled flags of all known clients
+pub fn enabled_flags(clients: &HashMap<String, ClientConfigItem>) -> HashMap<String, bool> {
+    clients.iter().map(|(name, item)| (name.clone(), item.enabled)).collect()
+}
+
+/// Read a file that may not exist
+fn read_optional(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok()
+}
+
+/// Servers of an agent config, empty when the file does not exist or cannot be parsed
+fn current_servers(agent: &AgentType) -> Result<AgentServers, String> {
+    let adapter = agent_adapters::get_adapter(agent)?;
+    if !adapter.config_path()?.exists() {
+        return Ok(AgentServers { servers: HashMap::new() });
+    }
+    adapter.read_config()
+}
+
+/// Append a `Do` entry; no-op changes are dropped and failures only logged
+pub fn record(operation: impl Into<String>, changes: Vec<JournalChange>) {
+    let changes: Vec<JournalChange> = changes.into_iter().filter(|c| !c.is_noop()).collect();
+    if changes.is_empty() {
+        return;
+    }
+    if let Err(e) = append(&new_entry(JournalAction::Do, operation.into(), None, changes)) {
+        warn!("Failed to record journal entry: {}", e);
+    }
+}
+
+fn new_entry(
+    action: JournalAction,
+    operation: String,
+    target: Option<String>,
+    changes: Vec<JournalChange>,
+) -> JournalEntry {
+    JournalEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        action,
+        operation,
+        target,
+        changes,
+    }
+}
+
+fn append(entry: &JournalEntry) -> Result<(), String> {
+    let path = get_journal_path().ok_or("Could not determine journal path")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let line = serde_json::to_string(&StoredEntry::seal(entry)?).map_err(|e| e.to_string())?;
+
+    let _guard = JOURNAL_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).map_err(|e| e.to_string())?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+    drop(file);
+
+    compact(&path)
+}
+
+/// Keep only the newest entries, and drop lines in an older format (which held
+/// plain-text configs) or that are otherwise unreadable
+fn compact(path: &Path) -> Result<(), String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let lines: Vec<&str> = content.lines().filter(|line| !line.trim().is_empty()).collect();
+    let valid: Vec<&str> = lines
+        .iter()
+        .copied()
+        .filter(|line| serde_json::from_str::<StoredEntry>(line).is_ok())
+        .collect();
+    if valid.len() == lines.len() && lines.len() <= MAX_ENTRIES {
+        return Ok(());
+    }
+
+    let keep = if valid.len() > MAX_ENTRIES { KEEP_ENTRIES } else { valid.len() };
+    let mut compacted = valid[valid.len() - keep..].join("\n");
+    compacted.push('\n');
+    write_private_file(path, &compacted)
+}
+
+/// All journal entries, oldest first (unreadable lines are skipped)
+pub fn load_journal() -> Vec<JournalEntry> {
+    let Some(content) = get_journal_path().and_then(|p| read_optional(&p)) else {
+        return Vec::new();
+    };
+    let key = match sealed::SealingKey::load() {
+        Ok(key) => key,
+        Err(e) => {
+            warn!("Cannot read journal: {}", e);
+            return Vec::new();
+        }
+    };
+
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            let entry = serde_json::from_str::<StoredEntry>(line)
+                .map_err(|e| e.to_string())
+                .and_then(|stored| stored.open(&key));
+            match entry {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    warn!("Skipping invalid journal line: {}", e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Replay the journal into (undo stack, redo stack) of original `Do` entries
+fn stacks(entries: &[JournalEntry]) -> (Vec<&JournalEntry>, Vec<&JournalEntry>) {
+    let mut done: Vec<&JournalEntry> = Vec::new();
+    let mut undone: Vec<&JournalEntry> = Vec::new();
+
+    for entry in entries {
+        let target = entry.target.as_ref();
+        match entry.action {
+            JournalAction::Do => {
+                done.push(entry);
+                undone.clear();
+            }
+            JournalAction::Undo => {
+                if let Some(pos) = done.iter().rposition(|e| Some(&e.id) == target) {
+                    undone.push(done.remove(pos));
+                }
+            }
+            JournalAction::Redo => {
+                if let Some(pos) = undone.iter().rposition(|e| Some(&e.id) == target) {
+                    done.push(undone.remove(pos));
+                }
+            }
+        }
+    }
+
+    (done, undone)
+}
+
+/// Newest entries first (redacted), with how many operations can be undone and redone
+pub fn journal_view(limit: Option<usize>) -> JournalView {
+    let entries = load_journal();
+    let (done, undone) = stacks(&entries);
+    let (can_undo, can_redo) = (done.len(), undone.len());
+
+    let limit = limit.unwrap_or(entries.len());
+    let entries = entries.iter().rev().take(limit).map(JournalEntry::redacted).collect();
+    JournalView { entries, can_undo, can_redo }
+}
+
+/// Undo up to `count` operations, newest first; returns the appended entries, redacted
+///
+/// Stops with an error at the first operation whose result was changed afterwards
+/// outside the journal; operations undone before that stay undone.
+pub fn undo(count: usize) -> Result<Vec<JournalEntry>, String> {
+    let entries = load_journal();
+    let (done, _) = stacks(&entries);
+
+    let mut applied = Vec::new();
+    for original in done.iter().rev().take(count) {
+        let changes: Vec<JournalChange> =
+            original.changes.iter().rev().map(|c| c.inverted()).collect();
+        apply_changes(&changes)?;
+
+        let entry = new_entry(
+            JournalAction::Undo,
+            format!("Undo: {}", original.operation),
+            Some(original.id.clone()),
+            changes,
+        );
+        append(&entry)?;
+        applied.push(entry.redacted());
+    }
+    Ok(applied)
+}
+
+/// Redo up to `count` undone operations, most recently undone first
+pub fn redo(count: usize) -> Result<Vec<JournalEntry>, String> {
+    let entries = load_journal();
+    let (_, undone) = stacks(&entries);
+
+    let mut applied = Vec::new();
+    for original in undone.iter().rev().take(count) {
+        apply_changes(&original.changes)?;
+
+        let entry = new_entry(
+            JournalAction::Redo,
+            format!("Redo: {}", original.operation),
+            Some(original.id.clone()),
+            original.changes.clone(),
+        );
+        append(&entry)?;
+        applied.push(entry.redacted());
+    }
+    Ok(applied)
+}
+
+/// Move every change from its `before` to its `after` state
+///
+/// All changes are checked against the current state first, so nothing is applied if
+/// any target was modified since.
+fn apply_changes(changes: &[JournalChange]) -> Result<(), String> {
+    let mut app_config = load_app_config();
+
+    for change in changes {
+        let matches = match change {
+            JournalChange::UserServer { server_id, before, .. } => {
+                let current = app_config
+                    .user_servers
+                    .iter()
+                    .find(|s| s.id == *server_id)
+                    .cloned()
+                    .map(Box::new);
+                same_server(&current, before)
+            }
+            JournalChange::AgentConfig { agent_type, before, after } => {
+                let current = current_servers(agent_type)?;
+                before
+                    .keys()
+                    .chain(after.keys())
+                    .all(|name| current.servers.get(name) == before.get(name))
+            }
+            JournalChange::EnabledAgents { before, .. } => {
+                enabled_flags(&app_config.clients) == *before
+            }
+            JournalChange::ServerDeployments { server_id, before, .. } => app_config
+                .server_deployments
+                .get(server_id)
+                .map_or(before.is_empty(), |current| current == before),
+            JournalChange::ProfileServers { profile_id, before, .. } => app_config
+                .profiles
+                .iter()
+                .find(|p| p.id == *profile_id)
+                .is_some_and(|p| p.server_ids == *before),
+        };
+        if !matches {
+            return Err(
+                "Configuration was changed since this operation; cannot apply it".to_string()
+            );
+        }
+    }
+
+    let mut app_config_changed = false;
+    for change in changes {
+        match change {
+            JournalChange::UserServer { server_id, after, .. } => {
+                let index = app_config.user_servers.iter().position(|s| s.id == *server_id);
+                match (index, after) {
+                    (Some(i), Some(server)) => app_config.user_servers[i] = (**server).clone(),
+                    (Some(i), None) => {
+                        app_config.user_servers.remove(i);
+                    }
+                    (None, Some(server)) => app_config.user_servers.push((**server).clone()),
+                    (None, None) => {}
+                }
+                app_config_changed = true;
+            }
+            JournalChange::AgentConfig { agent_type, before, after } => {
+                let mut current = current_servers(agent_type)?;
+                for name in before.keys().chain(after.keys()) {
+                    match after.get(name) {
+                        Some(entry) => current.servers.insert(name.clone(), entry.clone()),
+                        None => current.servers.remove(name),
+                    };
+                }
+                agent_adapters::get_adapter(agent_type)?.write_config(current)?;
+            }
+            JournalChange::EnabledAgents { after, .. } => {
+                app_config.clients.retain(|name, _| after.contains_key(name));
+                for (name, enabled) in after {
+                    app_config
+                        .clients
+                        .entry(name.clone())
+                        .and_modify(|c| c.enabled = *enabled)
+                        .or_insert(ClientConfigItem {
+                            enabled: *enabled,
+                            custom_config_path: None,
+                        });
+                }
+                app_config_changed = true;
+            }
+            JournalChange::ServerDeployments { server_id, after, .. } => {
+                if after.is_empty() {
+                    app_config.server_deployments.remove(server_id);
+                } else {
+                    app_config.server_deployments.insert(server_id.clone(), after.clone());
+                }
+                app_config_changed = true;
+            }
+            JournalChange::ProfileServers { profile_id, after, .. } => {
+                if let Some(profile) = app_config.profiles.iter_mut().find(|p| p.id == *profile_id)
+                {
+                    profile.server_ids = after.clone();
+                }
+                app_config_changed = true;
+            }
+        }
+    }
+
+    if app_config_changed {
+        save_app_config(&app_config)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//! Import servers already configured in agents into the user server library
+//!
+//! Every agent config is scanned, identical or near-identical entries (same command and
+//! args, or same URL) are clustered, and one `UserServer` proposal is made per cluster.
+//! Nothing is written until the user confirms the proposals via `import_user_servers`.
+
+use log::debug;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{load_app_config, save_app_config};
+
+use super::agent_config::read_agent_config;
+use super::journal::{self, JournalChange};
+use super::user_server::{OriginType, ServerOrigin, UserServer};
+use super::{get_all_agent_types, AgentServerEntry, AgentType};
+
+/// A server as it appears in one agent's config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentServerRef {
+    pub agent_type: AgentType,
+    pub server_name: String,
+}
+
+/// Proposed library entry for one cluster of agent servers
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportCandidate {
+    /// Proposed user server (config taken from the first agent in `sources`)
+    pub server: UserServer,
+
+    /// Agents that already have this server, and under which name
+    pub sources: Vec<AgentServerRef>,
+
+    /// True when the clustered entries differ in env, headers or timeout
+    pub has_variants: bool,
+
+    /// Id of a library server with the same command/args or URL, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub existing_id: Option<String>,
+}
+
+/// Identity used for clustering: command + args for local servers, URL for remote ones
+fn cluster_key(entry: &AgentServerEntry) -> String {
+    match entry {
+        AgentServerEntry::Local(local) => {
+            let args = local.args.as_deref().unwrap_or_default().join("\u{1f}");
+            format!("local:{}\u{1e}{}", local.command, args)
+        }
+        AgentServerEntry::Remote(remote) => {
+            format!("remote:{}", remote.url.trim_end_matches('/'))
+        }
+    }
+}
+
+/// Scan every agent config and propose library entries
+pub fn scan_agent_servers() -> Vec<ImportCandidate> {
+    let library = load_app_config().user_servers;
+    let mut clusters: Vec<(String, Vec<(AgentServerRef, AgentServerEntry)>)> = Vec::new();
+
+    for agent in get_all_agent_types() {
+        let config = match read_agent_config(agent.clone()) {
+            Ok(config) => config,
+            Err(e) => {
+                debug!("Skipping {} during import scan: {}", agent, e);
+                continue;
+            }
+        };
+
+        let mut names: Vec<_> = config.servers.keys().cloned().collect();
+        names.sort();
+        for name in names {
+            let entry = config.servers[&name].clone();
+            let key = cluster_key(&entry);
+            let source = AgentServerRef { agent_type: agent.clone(), server_name: name };
+
+            match clusters.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, members)) => members.push((source, entry)),
+                None => clusters.push((key, vec![(source, entry)])),
+            }
+        }
+    }
+
+    clusters
+        .into_iter()
+        .map(|(key, members)| {
+            let (first_source, config) = members[0].clone();
+            let has_variants = members.iter().any(|(_, entry)| *entry != config);
+            let existing_id =
+                library.iter().find(|s| cluster_key(&s.config) == key).map(|s| s.id.clone());
+
+            ImportCandidate {
+                server: UserServer {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    name: first_source.server_name,
+                    config,
+                    origin: Some(ServerOrigin {
+                        origin_type: OriginType::Custom,
+                        schema_name: None,
+                        package_id: None,
+                        version: None,
+                    }),
+                    created_at: None,
+                },
+                sources: members.into_iter().map(|(source, _)| source).collect(),
+                has_variants,
+                existing_id,
+            }
+        })
+        .collect()
+}
+
+/// Add confirmed servers to the library in one write
+///
+/// Fails without writing anything if any id is already taken.
+pub fn import_user_servers(servers: Vec<UserServer>) -> Result<Vec<UserServer>, String> {
+    let mut app_config = load_app_config();
+
+    for (index, server) in servers.iter().enumerate() {
+        let taken = app_config.user_servers.iter().any(|s| s.id == server.id)
+            || servers[..index].iter().any(|s| s.id == server.id);
+        if taken {
+            return Err(format!("Server with ID '{}' already exists", server.id));
+        }
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let imported: Vec<UserServer> = servers
+        .into_iter()
+        .map(|server| UserServer { created_at: server.created_at.or(Some(now.clone())), ..server })
+        .collect();
+
+    app_config.user_servers.extend(imported.iter().cloned());
+    save_app_config(&app_config)?;
+
+    journal::record(
+        format!("Import {} user servers", imported.len()),
+        imported.iter().map(|s| JournalChange::user_server(&s.id, None, Some(s.clone()))).collect(),
+    );
+
+    Ok(imported)
+}
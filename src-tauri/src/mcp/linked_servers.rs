@@ -0,0 +1,221 @@
+//! Links between user servers and the agent configs they were deployed to
+//!
+//! Every deploy records the agent, the server name and the exact config written. A sync
+//! pushes the current `UserServer` config to every linked agent, except copies that
+//! were edited by hand since the last deploy, which are reported for review instead.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{load_app_config, save_app_config, AppConfig};
+
+use super::agent_config::{read_agent_config, save_agent_config};
+use super::user_server::{ServerDeployment, UserServer};
+use super::vault::{self, config_for_agent};
+use super::{AgentServerEntry, AgentType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkState {
+    /// Agent copy matches the user server
+    InSync,
+    /// Agent copy is the last deployed config, but the user server changed since
+    Outdated,
+    /// Agent copy differs from what was deployed
+    HandEdited,
+    /// Server no longer exists in the agent config
+    Missing,
+    /// Agent copy cannot be compared because the server's secrets cannot be resolved,
+    /// usually because the vault is locked
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkStatus {
+    pub agent_type: AgentType,
+    pub server_name: String,
+    pub state: LinkState,
+    pub deployed_at: String,
+
+    /// Current entry in the agent config, for reviewing hand edits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_copy: Option<AgentServerEntry>,
+
+    /// Why the state is unknown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncResult {
+    pub agent_type: AgentType,
+    pub server_name: String,
+
+    /// State before the sync
+    pub state: LinkState,
+    pub synced: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Remember that `server` was written to `agent` under `server_name`
+///
+/// Replaces an earlier record for the same agent and name.
+pub fn record_deployment(
+    app_config: &mut AppConfig,
+    server: &UserServer,
+    agent: AgentType,
+    server_name: &str,
+) {
+    let deployment = ServerDeployment {
+        agent_type: agent,
+        server_name: server_name.to_string(),
+        deployed_config: server.config.clone(),
+        deployed_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let deployments = app_config.server_deployments.entry(server.id.clone()).or_default();
+    match deployments
+        .iter_mut()
+        .find(|d| d.agent_type == deployment.agent_type && d.server_name == deployment.server_name)
+    {
+        Some(existing) => *existing = deployment,
+        None => deployments.push(deployment),
+    }
+}
+
+/// State of an agent copy, or why it cannot be determined
+///
+/// Copies are compared in the form they were written to the agent, so a copy with
+/// resolved secrets cannot be judged while those secrets are unavailable.
+fn link_state(
+    server: &UserServer,
+    deployment: &ServerDeployment,
+    agent_copy: Option<&AgentServerEntry>,
+) -> Result<LinkState, String> {
+    let agent = &deployment.agent_type;
+    let Some(copy) = agent_copy else {
+        return Ok(LinkState::Missing);
+    };
+    if *copy == vault::agent_form(&server.config, agent)? {
+        return Ok(LinkState::InSync);
+    }
+    if *copy != vault::agent_form(&deployment.deployed_config, agent)? {
+        return Ok(LinkState::HandEdited);
+    }
+    Ok(LinkState::Outdated)
+}
+
+fn find_server<'a>(app_config: &'a AppConfig, server_id: &str) -> Result<&'a UserServer, String> {
+    app_config
+        .user_servers
+        .iter()
+        .find(|s| s.id == server_id)
+        .ok_or_else(|| format!("User server with ID '{}' not found", server_id))
+}
+
+/// Current state of every agent copy of a user server
+pub fn get_link_status(server_id: &str) -> Result<Vec<LinkStatus>, String> {
+    let app_config = load_app_config();
+    let server = find_server(&app_config, server_id)?;
+
+    let statuses = app_config
+        .server_deployments
+        .get(server_id)
+        .into_iter()
+        .flatten()
+        .map(|deployment| {
+            let agent_copy = read_agent_config(deployment.agent_type.clone())
+                .ok()
+                .and_then(|config| config.servers.get(&deployment.server_name).cloned());
+            let (state, error) = match link_state(server, deployment, agent_copy.as_ref()) {
+                Ok(state) => (state, None),
+                Err(e) => (LinkState::Unknown, Some(e)),
+            };
+            LinkStatus {
+                agent_type: deployment.agent_type.clone(),
+                server_name: deployment.server_name.clone(),
+                state,
+                deployed_at: deployment.deployed_at.clone(),
+                agent_copy,
+                error,
+            }
+        })
+        .collect();
+
+    Ok(statuses)
+}
+
+/// Push the user server config to every linked agent
+///
+/// Hand-edited copies are only overwritten when `force` is set; missing copies are
+/// never recreated.
+pub fn sync_user_server(server_id: &str, force: bool) -> Result<Vec<SyncResult>, String> {
+    let mut app_config = load_app_config();
+    let server = find_server(&app_config, server_id)?.clone();
+    let deployments = app_config.server_deployments.get(server_id).cloned().unwrap_or_default();
+
+    let mut results = Vec::new();
+    for deployment in deployments {
+        let mut result = SyncResult {
+            agent_type: deployment.agent_type.clone(),
+            server_name: deployment.server_name.clone(),
+            state: LinkState::Missing,
+            synced: false,
+            error: None,
+        };
+
+        let mut config = match read_agent_config(deployment.agent_type.clone()) {
+            Ok(config) => config,
+            Err(e) => {
+                result.error = Some(e);
+                results.push(result);
+                continue;
+            }
+        };
+
+        let state =
+            match link_state(&server, &deployment, config.servers.get(&deployment.server_name)) {
+                Ok(state) => state,
+                Err(e) => {
+                    result.state = LinkState::Unknown;
+                    result.error = Some(e);
+                    results.push(result);
+                    continue;
+                }
+            };
+        result.state = state;
+        let should_write = match result.state {
+            LinkState::Outdated => true,
+            LinkState::HandEdited => force,
+            LinkState::InSync | LinkState::Missing | LinkState::Unknown => false,
+        };
+
+        if should_write {
+            let written =
+                config_for_agent(&server.config, &deployment.agent_type).and_then(|entry| {
+                    config.servers.insert(deployment.server_name.clone(), entry);
+                    save_agent_config(deployment.agent_type.clone(), config)
+                });
+            match written {
+                Ok(()) => result.synced = true,
+                Err(e) => result.error = Some(e),
+            }
+        }
+        if result.synced {
+            record_deployment(
+                &mut app_config,
+                &server,
+                deployment.agent_type,
+                &deployment.server_name,
+            );
+        }
+        results.push(result);
+    }
+
+    save_app_config(&app_config)?;
+    Ok(results)
+}
@@ -5,9 +5,31 @@
 pub mod agent;
 pub mod agent_adapters;
 pub mod agent_config;
+pub mod agent_definition;
+pub mod bulk_ops;
+pub mod bundle;
+pub mod conflicts;
+pub mod deeplink;
+pub mod deploy;
+pub mod env_file;
+pub mod install_snippets;
+pub mod journal;
+pub mod library_import;
+pub mod linked_servers;
+pub mod profiles;
+pub mod redact;
 pub mod registry;
+pub mod schema_search;
+pub mod sealed;
+pub mod secret_scan;
 pub mod server_schema;
+pub mod server_updates;
+pub mod snippet_import;
+pub mod transaction;
+pub mod transfer;
 pub mod user_server;
+pub mod vault;
+pub mod watcher;
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -22,13 +44,13 @@ pub use agent::{get_all_agent_types, parse_agent_name, AgentType, SupportedAgent
 // Agent server entry types (internal representation of agent config)
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct BaseServerEntry {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout: Option<u32>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct LocalServerEntry {
     #[serde(flatten)]
     pub base: BaseServerEntry,
@@ -42,7 +64,7 @@ pub struct LocalServerEntry {
     pub env: Option<HashMap<String, String>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct RemoteServerEntry {
     #[serde(flatten)]
     pub base: BaseServerEntry,
@@ -53,18 +75,28 @@ pub struct RemoteServerEntry {
     pub headers: Option<HashMap<String, String>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum AgentServerEntry {
     Local(LocalServerEntry),
     Remote(RemoteServerEntry),
 }
 
-#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AgentServers {
     pub servers: HashMap<String, AgentServerEntry>,
 }
 
+/// Agents marked as enabled in the app config (unknown agent names are ignored)
+pub fn get_enabled_agents(app_config: &AppConfig) -> Vec<AgentType> {
+    app_config
+        .clients
+        .iter()
+        .filter(|(_, client_config)| client_config.enabled)
+        .filter_map(|(agent_name, _)| parse_agent_name(agent_name).ok())
+        .collect()
+}
+
 // ============================================================================
 // Tauri commands
 // ============================================================================
@@ -79,6 +111,17 @@ pub fn get_agent_mcp_config_command(agent_name: String) -> Result<AgentServers,
 pub fn get_server_raw_config_command(
     agent_name: String,
     server_name: String,
+) -> Result<String, String> {
+    let agent = parse_agent_name(&agent_name)?;
+    let raw = agent_config::get_server_raw_config(agent, &server_name)?;
+    Ok(redact::redact_text_with(&raw, &redact::registry_secret_names(&server_name)))
+}
+
+/// Raw config fragment with secrets left in place, for an explicit "reveal" action
+#[tauri::command]
+pub fn reveal_server_raw_config_command(
+    agent_name: String,
+    server_name: String,
 ) -> Result<String, String> {
     let agent = parse_agent_name(&agent_name)?;
     agent_config::get_server_raw_config(agent, &server_name)
@@ -91,14 +134,14 @@ pub fn get_supported_agents_command() -> Result<Vec<SupportedAgent>, String> {
     let mut supported_agents = Vec::new();
 
     for agent in agents {
-        let path = agent::get_global_config_path(agent)
-            .map_err(|e| format!("Error getting path for {:?}: {}", agent, e))?;
+        let path = agent::get_global_config_path(&agent)
+            .map_err(|e| format!("Error getting path for {}: {}", agent, e))?;
 
         let is_configured = path.exists();
         let mcp_config =
-            if is_configured { agent_config::read_agent_config(agent).ok() } else { None };
+            if is_configured { agent_config::read_agent_config(agent.clone()).ok() } else { None };
 
-        let agent_name = format!("{:?}", agent);
+        let agent_name = agent.to_string();
         let enabled = app_config.clients.get(&agent_name).map(|c| c.enabled).unwrap_or(false);
 
         supported_agents.push(SupportedAgent {
@@ -116,23 +159,13 @@ pub fn get_supported_agents_command() -> Result<Vec<SupportedAgent>, String> {
 
 #[tauri::command]
 pub fn get_enabled_agents_command() -> Result<Vec<AgentType>, String> {
-    let app_config = load_app_config();
-    let mut enabled_agents = Vec::new();
-
-    for (agent_name, client_config) in app_config.clients {
-        if client_config.enabled {
-            if let Ok(agent) = parse_agent_name(&agent_name) {
-                enabled_agents.push(agent);
-            }
-        }
-    }
-
-    Ok(enabled_agents)
+    Ok(get_enabled_agents(&load_app_config()))
 }
 
 #[tauri::command]
 pub fn update_enabled_agents_command(enabled_agents: Vec<String>) -> Result<(), String> {
     let mut app_config = load_app_config();
+    let before = journal::enabled_flags(&app_config.clients);
 
     // Parse all agent names first to validate them
     let mut parsed_agents = Vec::new();
@@ -146,7 +179,7 @@ pub fn update_enabled_agents_command(enabled_agents: Vec<String>) -> Result<(),
 
     // Update all agents' enabled status
     for agent in all_agents {
-        let agent_name = format!("{:?}", agent);
+        let agent_name = agent.to_string();
         let is_enabled = parsed_agents.iter().any(|(_, a)| *a == agent);
 
         app_config.clients.entry(agent_name).and_modify(|c| c.enabled = is_enabled).or_insert(
@@ -154,7 +187,15 @@ pub fn update_enabled_agents_command(enabled_agents: Vec<String>) -> Result<(),
         );
     }
 
-    save_app_config(&app_config)
+    save_app_config(&app_config)?;
+    journal::record(
+        "Update enabled agents",
+        vec![journal::JournalChange::EnabledAgents {
+            before,
+            after: journal::enabled_flags(&app_config.clients),
+        }],
+    );
+    Ok(())
 }
 
 #[tauri::command]
@@ -166,20 +207,56 @@ pub fn update_agent_mcp_config_command(
     agent_config::save_agent_config(agent, config)
 }
 
+#[tauri::command]
+pub fn preview_agent_mcp_config_command(
+    agent_name: String,
+    config: AgentServers,
+) -> Result<agent_config::ConfigPreview, String> {
+    let agent = parse_agent_name(&agent_name)?;
+    agent_config::preview_agent_config(agent, config)
+}
+
 #[tauri::command]
 pub fn get_app_config_command() -> AppConfig {
-    load_app_config()
+    // Provider keys and registry auth headers stay on the backend; use
+    // `get_openrouter_api_key_command` to check
+    let mut config = load_app_config();
+    config.openrouter_api_key = None;
+    for source in &mut config.registry_sources {
+        source.auth_header = None;
+    }
+    config
 }
 
 #[tauri::command]
-pub fn update_app_config_command(config: AppConfig) -> Result<(), String> {
+pub fn update_app_config_command(mut config: AppConfig) -> Result<(), String> {
+    // Keys are only changed through `set_openrouter_api_key_command` and
+    // `set_registry_auth_header_command`
+    let stored = load_app_config();
+    config.openrouter_api_key = stored.openrouter_api_key;
+    for source in &mut config.registry_sources {
+        source.auth_header = stored
+            .registry_sources
+            .iter()
+            .find(|s| s.url == source.url)
+            .and_then(|s| s.auth_header.clone());
+    }
     save_app_config(&config)
 }
 
+#[tauri::command]
+pub fn set_registry_auth_header_command(
+    url: String,
+    auth_header: Option<String>,
+) -> Result<(), String> {
+    registry::set_registry_auth_header(&url, auth_header.as_deref())
+}
+
 #[tauri::command]
 pub fn open_config_file_command(agent_name: String) -> Result<(), String> {
     let agent = parse_agent_name(&agent_name)?;
-    let path = agent::get_global_config_path(agent)?;
+    let definition = agent_definition::get_agent_definition(&agent)?;
+    let path = definition.config_path()?;
 
     if !path.exists() {
         if let Some(parent) = path.parent() {
@@ -187,8 +264,8 @@ pub fn open_config_file_command(agent_name: String) -> Result<(), String> {
         }
 
         // Initialize with valid empty config
-        match agent {
-            AgentType::OpenAiCodex => {
+        match definition.format {
+            agent_definition::AgentConfigFormat::Toml => {
                 std::fs::write(&path, "").map_err(|e| e.to_string())?;
             }
             _ => {
@@ -212,12 +289,76 @@ pub fn get_schema_store_command() -> Result<registry::SchemaStore, String> {
     registry::load_schema_store()
 }
 
+/// Every known version of a registry server, newest first
+#[tauri::command]
+pub fn list_server_versions_command(
+    name: String,
+) -> Result<Vec<server_schema::ServerSchema>, String> {
+    let versions = registry::load_schema_store()?.versions(&name);
+    if versions.is_empty() {
+        return Err(format!("Server '{}' not found in schema store", name));
+    }
+    Ok(versions)
+}
+
+/// User servers with a newer registry version than the one they were generated from
+#[tauri::command]
+pub fn get_server_updates_command() -> Result<Vec<server_updates::ServerUpdate>, String> {
+    server_updates::server_updates()
+}
+
+/// Regenerate a registry server's config from `version` (the latest when omitted)
+#[tauri::command]
+pub fn upgrade_user_server_command(
+    server_id: String,
+    version: Option<String>,
+) -> Result<user_server::UserServer, String> {
+    let server = load_app_config()
+        .user_servers
+        .into_iter()
+        .find(|s| s.id == server_id)
+        .ok_or_else(|| format!("Server with ID '{}' not found", server_id))?;
+    let store = registry::load_schema_store()?;
+    let upgraded = server_updates::upgraded_server(&server, &store, version.as_deref())?;
+    update_user_server_command(upgraded)
+}
+
+/// Config for running a registry package with the given env (or header) values
+#[tauri::command]
+pub fn package_config_command(
+    package: server_schema::Package,
+    values: HashMap<String, String>,
+) -> AgentServerEntry {
+    server_updates::package_config(&package, BaseServerEntry { timeout: None }, values)
+}
+
+/// Ranked, paginated search over the schema store
+#[tauri::command]
+pub fn search_schema_store_command(
+    query: schema_search::SchemaSearchQuery,
+) -> Result<schema_search::SchemaSearchPage, String> {
+    schema_search::search_schema_store(&query)
+}
+
 #[tauri::command]
 pub fn get_user_servers_command() -> Result<Vec<user_server::UserServer>, String> {
     let app_config = load_app_config();
     Ok(app_config.user_servers)
 }
 
+/// Record the registry version a server is added from, so later lookups use that
+/// version rather than whatever is latest then
+fn pin_origin_version(mut server: user_server::UserServer) -> user_server::UserServer {
+    if let Some(origin) = server.origin.as_mut().filter(|o| o.version.is_none()) {
+        if let (Some(name), Ok(store)) =
+            (origin.schema_name.as_deref(), registry::load_schema_store())
+        {
+            origin.version = store.latest(name).map(|s| s.version.clone());
+        }
+    }
+    server
+}
+
 #[tauri::command]
 pub fn add_user_server_command(
     server: user_server::UserServer,
@@ -235,9 +376,14 @@ pub fn add_user_server_command(
     } else {
         server
     };
+    let server = pin_origin_version(server);
 
     app_config.user_servers.push(server.clone());
     save_app_config(&app_config)?;
+    journal::record(
+        format!("Add user server '{}'", server.name),
+        vec![journal::JournalChange::user_server(&server.id, None, Some(server.clone()))],
+    );
 
     Ok(server)
 }
@@ -254,8 +400,12 @@ pub fn update_user_server_command(
         .position(|s| s.id == server.id)
         .ok_or_else(|| format!("Server with ID '{}' not found", server.id))?;
 
-    app_config.user_servers[index] = server.clone();
+    let before = std::mem::replace(&mut app_config.user_servers[index], server.clone());
     save_app_config(&app_config)?;
+    journal::record(
+        format!("Update user server '{}'", server.name),
+        vec![journal::JournalChange::user_server(&server.id, Some(before), Some(server.clone()))],
+    );
 
     Ok(server)
 }
@@ -270,8 +420,38 @@ pub fn delete_user_server_command(server_id: String) -> Result<(), String> {
         .position(|s| s.id == server_id)
         .ok_or_else(|| format!("Server with ID '{}' not found", server_id))?;
 
-    app_config.user_servers.remove(index);
+    let before = app_config.user_servers.remove(index);
+    let mut changes =
+        vec![journal::JournalChange::user_server(&server_id, Some(before.clone()), None)];
+
+    // Copies still present in agent configs stay tracked, so they can be removed later
+    // through `ServerSelector::UserServer`
+    if let Some(deployments) = app_config.server_deployments.get_mut(&server_id) {
+        let deployments_before = deployments.clone();
+        deployments.retain(|d| {
+            agent_config::read_agent_config(d.agent_type.clone())
+                .is_ok_and(|config| config.servers.contains_key(&d.server_name))
+        });
+        changes.push(journal::JournalChange::ServerDeployments {
+            server_id: server_id.clone(),
+            before: deployments_before,
+            after: deployments.clone(),
+        });
+        if deployments.is_empty() {
+            app_config.server_deployments.remove(&server_id);
+        }
+    }
+    for profile in &mut app_config.profiles {
+        let server_ids_before = profile.server_ids.clone();
+        profile.server_ids.retain(|id| *id != server_id);
+        changes.push(journal::JournalChange::ProfileServers {
+            profile_id: profile.id.clone(),
+            before: server_ids_before,
+            after: profile.server_ids.clone(),
+        });
+    }
     save_app_config(&app_config)?;
+    journal::record(format!("Delete user server '{}'", before.name), changes);
 
     Ok(())
 }
@@ -282,6 +462,42 @@ pub fn add_server_to_agent_command(
     server_id: String,
     server_name: Option<String>,
 ) -> Result<(), String> {
+    let agent = parse_agent_name(&agent_name)?;
+    let mut app_config = load_app_config();
+
+    let server = app_config
+        .user_servers
+        .iter()
+        .find(|s| s.id == server_id)
+        .cloned()
+        .ok_or_else(|| format!("User server with ID '{}' not found", server_id))?;
+
+    let name = server_name.unwrap_or_else(|| server.name.clone());
+    let config = agent_config::config_with_server(agent.clone(), &server, Some(name.clone()))?;
+    let agent_change = agent_config::write_agent_config(agent.clone(), config)?;
+
+    let deployments_before =
+        app_config.server_deployments.get(&server_id).cloned().unwrap_or_default();
+    linked_servers::record_deployment(&mut app_config, &server, agent.clone(), &name);
+    save_app_config(&app_config)?;
+
+    // One entry, so undoing the write also forgets the deployment
+    let mut changes: Vec<journal::JournalChange> = agent_change.into_iter().collect();
+    changes.push(journal::JournalChange::ServerDeployments {
+        server_id: server_id.clone(),
+        before: deployments_before,
+        after: app_config.server_deployments[&server_id].clone(),
+    });
+    journal::record(format!("Add '{}' to {}", name, agent), changes);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn preview_add_server_to_agent_command(
+    agent_name: String,
+    server_id: String,
+    server_name: Option<String>,
+) -> Result<agent_config::ConfigPreview, String> {
     let agent = parse_agent_name(&agent_name)?;
     let app_config = load_app_config();
 
@@ -291,7 +507,230 @@ pub fn add_server_to_agent_command(
         .find(|s| s.id == server_id)
         .ok_or_else(|| format!("User server with ID '{}' not found", server_id))?;
 
-    agent_config::add_server_to_agent(agent, server, server_name)
+    agent_config::preview_add_server_to_agent(agent, server, server_name)
+}
+
+#[tauri::command]
+pub fn copy_server_to_agents_command(
+    source_agent: String,
+    server_name: String,
+    target_agents: Vec<String>,
+    strategy: transfer::ConflictStrategy,
+) -> Result<transfer::TransferReport, String> {
+    let source = parse_agent_name(&source_agent)?;
+    let targets =
+        target_agents.iter().map(|name| parse_agent_name(name)).collect::<Result<_, _>>()?;
+    transfer::copy_server(source, &server_name, targets, strategy)
+}
+
+#[tauri::command]
+pub fn move_server_to_agents_command(
+    source_agent: String,
+    server_name: String,
+    target_agents: Vec<String>,
+    strategy: transfer::ConflictStrategy,
+) -> Result<transfer::TransferReport, String> {
+    let source = parse_agent_name(&source_agent)?;
+    let targets =
+        target_agents.iter().map(|name| parse_agent_name(name)).collect::<Result<_, _>>()?;
+    transfer::move_server(source, &server_name, targets, strategy)
+}
+
+#[tauri::command]
+pub fn scan_agent_servers_command() -> Result<Vec<library_import::ImportCandidate>, String> {
+    Ok(library_import::scan_agent_servers())
+}
+
+#[tauri::command]
+pub fn import_user_servers_command(
+    servers: Vec<user_server::UserServer>,
+) -> Result<Vec<user_server::UserServer>, String> {
+    library_import::import_user_servers(servers)
+}
+
+#[tauri::command]
+pub fn get_cross_agent_report_command() -> Result<Vec<conflicts::ServerDrift>, String> {
+    Ok(conflicts::get_cross_agent_report())
+}
+
+#[tauri::command]
+pub fn get_user_server_links_command(
+    server_id: String,
+) -> Result<Vec<linked_servers::LinkStatus>, String> {
+    linked_servers::get_link_status(&server_id)
+}
+
+#[tauri::command]
+pub fn sync_user_server_command(
+    server_id: String,
+    force: bool,
+) -> Result<Vec<linked_servers::SyncResult>, String> {
+    linked_servers::sync_user_server(&server_id, force)
+}
+
+#[tauri::command]
+pub fn remove_server_from_all_agents_command(
+    selector: bulk_ops::ServerSelector,
+) -> Result<Vec<bulk_ops::BulkResult>, String> {
+    bulk_ops::remove_server_everywhere(&selector)
+}
+
+#[tauri::command]
+pub fn rename_server_in_all_agents_command(
+    selector: bulk_ops::ServerSelector,
+    new_name: String,
+) -> Result<Vec<bulk_ops::BulkResult>, String> {
+    bulk_ops::rename_server_everywhere(&selector, &new_name)
+}
+
+#[tauri::command]
+pub fn get_profiles_command() -> Result<Vec<profiles::Profile>, String> {
+    Ok(load_app_config().profiles)
+}
+
+#[tauri::command]
+pub fn save_profile_command(profile: profiles::Profile) -> Result<profiles::Profile, String> {
+    profiles::save_profile(profile)
+}
+
+#[tauri::command]
+pub fn delete_profile_command(profile_id: String) -> Result<(), String> {
+    profiles::delete_profile(&profile_id)
+}
+
+#[tauri::command]
+pub fn activate_profile_command(
+    profile_id: String,
+) -> Result<Vec<profiles::ProfileAgentChanges>, String> {
+    profiles::activate_profile(&profile_id)
+}
+
+#[tauri::command]
+pub fn deploy_user_server_command(
+    server_id: String,
+    agent_names: Vec<String>,
+    server_name: Option<String>,
+) -> Result<deploy::DeployReport, String> {
+    let agents = agent_names.iter().map(|name| parse_agent_name(name)).collect::<Result<_, _>>()?;
+    deploy::deploy_user_server(&server_id, agents, server_name)
+}
+
+#[tauri::command]
+pub fn get_journal_command(limit: Option<usize>) -> Result<journal::JournalView, String> {
+    Ok(journal::journal_view(limit))
+}
+
+#[tauri::command]
+pub fn undo_command(count: usize) -> Result<Vec<journal::JournalEntry>, String> {
+    journal::undo(count)
+}
+
+#[tauri::command]
+pub fn redo_command(count: usize) -> Result<Vec<journal::JournalEntry>, String> {
+    journal::redo(count)
+}
+
+#[tauri::command]
+pub fn export_server_bundle_command(server_ids: Vec<String>) -> Result<String, String> {
+    bundle::export_bundle(&server_ids)
+}
+
+#[tauri::command]
+pub fn preview_server_bundle_command(
+    content: String,
+) -> Result<bundle::BundleImportPreview, String> {
+    bundle::preview_bundle_import(&content)
+}
+
+#[tauri::command]
+pub fn import_server_bundle_command(
+    content: String,
+    values: HashMap<String, String>,
+    strategy: transfer::ConflictStrategy,
+) -> Result<bundle::BundleImportResult, String> {
+    bundle::import_bundle(&content, &values, strategy)
+}
+
+#[tauri::command]
+pub fn parse_install_link_command(link: String) -> Result<deeplink::InstallLink, String> {
+    deeplink::parse_install_link(&link)
+}
+
+#[tauri::command]
+pub fn install_from_link_command(link: String) -> Result<user_server::UserServer, String> {
+    deeplink::install_from_link(&link)
+}
+
+#[tauri::command]
+pub fn parse_config_snippet_command(
+    snippet: String,
+) -> Result<Vec<snippet_import::SnippetCandidate>, String> {
+    snippet_import::parse_snippet(&snippet)
+}
+
+#[tauri::command]
+pub fn get_install_snippets_command(
+    server: user_server::UserServer,
+    server_name: Option<String>,
+) -> Result<Vec<install_snippets::AgentInstallSnippet>, String> {
+    install_snippets::generate_install_snippets(&server, server_name.as_deref())
+}
+
+#[tauri::command]
+pub fn get_vault_status_command() -> vault::VaultStatus {
+    vault::vault_status()
+}
+
+#[tauri::command]
+pub fn create_vault_command(passphrase: String) -> Result<vault::VaultStatus, String> {
+    vault::create_vault(&passphrase)
+}
+
+#[tauri::command]
+pub fn unlock_vault_command(passphrase: String) -> Result<vault::VaultStatus, String> {
+    vault::unlock_vault(&passphrase)
+}
+
+#[tauri::command]
+pub fn lock_vault_command() {
+    vault::lock_vault()
+}
+
+#[tauri::command]
+pub fn set_vault_secret_command(name: String, value: String) -> Result<(), String> {
+    vault::set_secret(&name, &value)
+}
+
+#[tauri::command]
+pub fn delete_vault_secret_command(name: String) -> Result<(), String> {
+    vault::delete_secret(&name)
+}
+
+#[tauri::command]
+pub fn get_secret_exports_command() -> Result<vault::SecretExports, String> {
+    vault::secret_exports()
+}
+
+#[tauri::command]
+pub fn import_env_file_command(
+    server: user_server::UserServer,
+    path: String,
+) -> Result<env_file::EnvFileImport, String> {
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    env_file::apply_env_file(&server, &content)
+}
+
+#[tauri::command]
+pub fn scan_plaintext_secrets_command() -> Vec<secret_scan::PlaintextSecret> {
+    secret_scan::scan_plaintext_secrets()
+}
+
+#[tauri::command]
+pub fn migrate_plaintext_secrets_command(
+    secrets: Vec<secret_scan::PlaintextSecret>,
+) -> Result<secret_scan::SecretMigrationReport, String> {
+    secret_scan::migrate_plaintext_secrets(&secrets)
 }
 
 #[cfg(test)]
@@ -0,0 +1,228 @@
+//! Server profiles: named sets of user servers deployed to a set of agents
+//!
+//! Activating a profile deploys its servers to its agents and removes servers that
+//! belong only to other profiles. All agent writes happen in one transaction.
+
+use std::collections::{BTreeMap, HashSet};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{load_app_config, save_app_config, AppConfig};
+
+use super::agent_config::read_agent_config_or_empty;
+use super::linked_servers::record_deployment;
+use super::transaction::AgentConfigTransaction;
+use super::vault::config_for_agent;
+use super::{AgentServers, AgentType};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+
+    /// User server ids in this profile
+    #[serde(default)]
+    pub server_ids: Vec<String>,
+
+    /// Agents the profile's servers are deployed to
+    #[serde(default)]
+    pub agents: Vec<AgentType>,
+}
+
+/// Changes made to one agent by a profile activation
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileAgentChanges {
+    pub agent_type: AgentType,
+    pub added: Vec<String>,
+
+    /// Deployed copies rewritten with the server's current config
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+
+    /// Names taken by agent servers that were not deployed from the profile's server;
+    /// those entries are left alone
+    pub conflicts: Vec<String>,
+}
+
+impl ProfileAgentChanges {
+    fn changes_config(&self) -> bool {
+        !(self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty())
+    }
+}
+
+/// Add or replace a profile (matched by id)
+pub fn save_profile(profile: Profile) -> Result<Profile, String> {
+    let mut app_config = load_app_config();
+
+    if let Some(missing) =
+        profile.server_ids.iter().find(|id| !app_config.user_servers.iter().any(|s| s.id == **id))
+    {
+        return Err(format!("User server with ID '{}' not found", missing));
+    }
+
+    match app_config.profiles.iter_mut().find(|p| p.id == profile.id) {
+        Some(existing) => *existing = profile.clone(),
+        None => app_config.profiles.push(profile.clone()),
+    }
+    save_app_config(&app_config)?;
+
+    Ok(profile)
+}
+
+pub fn delete_profile(profile_id: &str) -> Result<(), String> {
+    let mut app_config = load_app_config();
+
+    let index = app_config
+        .profiles
+        .iter()
+        .position(|p| p.id == profile_id)
+        .ok_or_else(|| format!("Profile with ID '{}' not found", profile_id))?;
+    app_config.profiles.remove(index);
+    if app_config.active_profile.as_deref() == Some(profile_id) {
+        app_config.active_profile = None;
+    }
+
+    save_app_config(&app_config)
+}
+
+/// Load `agent`'s config into `configs` once (a missing file counts as empty)
+fn agent_servers<'a>(
+    configs: &'a mut BTreeMap<String, (AgentType, AgentServers)>,
+    agent: &AgentType,
+) -> Result<&'a mut AgentServers, String> {
+    let key = agent.to_string();
+    if !configs.contains_key(&key) {
+        let config = read_agent_config_or_empty(agent.clone())?;
+        configs.insert(key.clone(), (agent.clone(), config));
+    }
+    Ok(&mut configs.get_mut(&key).expect("inserted above").1)
+}
+
+/// Activate a profile, returning the changes per agent
+pub fn activate_profile(profile_id: &str) -> Result<Vec<ProfileAgentChanges>, String> {
+    let mut app_config = load_app_config();
+    let profile = app_config
+        .profiles
+        .iter()
+        .find(|p| p.id == profile_id)
+        .cloned()
+        .ok_or_else(|| format!("Profile with ID '{}' not found", profile_id))?;
+
+    let mut configs = BTreeMap::new();
+    let mut changes = BTreeMap::new();
+
+    // Remove servers that belong only to other profiles, wherever they are deployed
+    for (agent, name) in exclusive_server_copies(&app_config, &profile) {
+        if agent_servers(&mut configs, &agent)?.servers.remove(&name).is_some() {
+            changes_for(&mut changes, &agent).removed.push(name);
+        }
+    }
+
+    let mut deployed = Vec::new();
+    for agent in &profile.agents {
+        for server_id in &profile.server_ids {
+            let server = app_config
+                .user_servers
+                .iter()
+                .find(|s| s.id == *server_id)
+                .ok_or_else(|| format!("User server with ID '{}' not found", server_id))?;
+            let name = linked_name(&app_config, server_id, agent).unwrap_or(server.name.clone());
+
+            let config = config_for_agent(&server.config, agent)?;
+            let tracked = is_deployment(&app_config, server_id, agent, &name);
+            let servers = &mut agent_servers(&mut configs, agent)?.servers;
+            match servers.get(&name) {
+                // Already deployed, or an identical copy added before deploys were tracked
+                Some(existing) if *existing == config => {}
+                Some(_) if !tracked => {
+                    changes_for(&mut changes, agent).conflicts.push(name);
+                    continue;
+                }
+                Some(_) => {
+                    servers.insert(name.clone(), config);
+                    changes_for(&mut changes, agent).updated.push(name.clone());
+                }
+                None => {
+                    servers.insert(name.clone(), config);
+                    changes_for(&mut changes, agent).added.push(name.clone());
+                }
+            }
+            deployed.push((server.clone(), agent.clone(), name));
+        }
+    }
+
+    let mut transaction = AgentConfigTransaction::new();
+    for (key, (agent, config)) in configs {
+        if changes.get(&key).is_some_and(ProfileAgentChanges::changes_config) {
+            transaction.stage(agent, config)?;
+        }
+    }
+    transaction.commit()?;
+
+    for (agent, name) in changes.values().flat_map(|c| c.removed.iter().map(|n| (&c.agent_type, n)))
+    {
+        for deployments in app_config.server_deployments.values_mut() {
+            deployments.retain(|d| !(d.agent_type == *agent && d.server_name == *name));
+        }
+    }
+    app_config.server_deployments.retain(|_, deployments| !deployments.is_empty());
+    for (server, agent, name) in deployed {
+        record_deployment(&mut app_config, &server, agent, &name);
+    }
+    app_config.active_profile = Some(profile.id);
+    save_app_config(&app_config)?;
+
+    Ok(changes.into_values().collect())
+}
+
+fn changes_for<'a>(
+    changes: &'a mut BTreeMap<String, ProfileAgentChanges>,
+    agent: &AgentType,
+) -> &'a mut ProfileAgentChanges {
+    changes.entry(agent.to_string()).or_insert_with(|| ProfileAgentChanges {
+        agent_type: agent.clone(),
+        added: Vec::new(),
+        updated: Vec::new(),
+        removed: Vec::new(),
+        conflicts: Vec::new(),
+    })
+}
+
+/// Whether `name` in `agent`'s config is a recorded deployment of `server_id`
+fn is_deployment(app_config: &AppConfig, server_id: &str, agent: &AgentType, name: &str) -> bool {
+    app_config
+        .server_deployments
+        .get(server_id)
+        .is_some_and(|d| d.iter().any(|d| d.agent_type == *agent && d.server_name == name))
+}
+
+/// Name under which `server_id` is deployed to `agent`, if linked
+fn linked_name(app_config: &AppConfig, server_id: &str, agent: &AgentType) -> Option<String> {
+    app_config
+        .server_deployments
+        .get(server_id)?
+        .iter()
+        .find(|d| d.agent_type == *agent)
+        .map(|d| d.server_name.clone())
+}
+
+/// Linked copies of servers that are in some other profile but not in `profile`
+fn exclusive_server_copies(app_config: &AppConfig, profile: &Profile) -> Vec<(AgentType, String)> {
+    let own: HashSet<&String> = profile.server_ids.iter().collect();
+    let exclusive: HashSet<&String> = app_config
+        .profiles
+        .iter()
+        .filter(|p| p.id != profile.id)
+        .flat_map(|p| p.server_ids.iter())
+        .filter(|id| !own.contains(id))
+        .collect();
+
+    exclusive
+        .into_iter()
+        .flat_map(|id| app_config.server_deployments.get(id).into_iter().flatten())
+        .map(|d| (d.agent_type.clone(), d.server_name.clone()))
+        .collect()
+}
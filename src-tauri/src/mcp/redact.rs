@@ -0,0 +1,392 @@
+//! Secret detection and masking
+//!
+//! A value counts as secret when its key names one (known name fragments or registry
+//! inputs marked `isSecret`) or when the value itself looks like a credential: a
+//! well-known token prefix such as `ghp_` or `sk-`, or a long high-entropy string.
+//! Masking keeps auth schemes and token prefixes so the kind of secret stays visible.
+//! Raw-config views, log lines and everything sent to the chat model go through here.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::config::load_app_config;
+
+use super::registry::{load_schema_store, SchemaStore};
+use super::user_server::ServerOrigin;
+use super::AgentServerEntry;
+
+/// Replacement for a masked value
+pub const MASK: &str = "********";
+
+/// Auth schemes kept in front of a masked header value
+pub const AUTH_SCHEMES: &[&str] = &["Bearer ", "Basic ", "token "];
+
+/// Name fragments that mark an env var, header or flag as secret
+const SECRET_NAME_HINTS: &[&str] =
+    &["TOKEN", "SECRET", "KEY", "PASSWORD", "PASSWD", "AUTH", "CREDENTIAL", "COOKIE"];
+
+/// Prefixes of well-known credential formats
+const TOKEN_PREFIXES: &[&str] = &[
+    "github_pat_",
+    "ghp_",
+    "gho_",
+    "ghu_",
+    "ghs_",
+    "ghr_",
+    "glpat-",
+    "sk-",
+    "sk_live_",
+    "sk_test_",
+    "rk_live_",
+    "xoxb-",
+    "xoxp-",
+    "xoxa-",
+    "xoxs-",
+    "xapp-",
+    "AKIA",
+    "AIza",
+    "hf_",
+    "npm_",
+    "pypi-",
+    "lin_api_",
+    "ntn_",
+    "eyJ",
+];
+
+/// Shortest value considered by the entropy heuristic
+const MIN_ENTROPY_LEN: usize = 20;
+
+/// Bits per character above which a token-like string is treated as random
+const MIN_ENTROPY_BITS: f64 = 3.5;
+
+/// Whether an env var, header or flag name refers to a secret
+pub fn is_secret_name(name: &str) -> bool {
+    // `AUTHOR` fields are common in docs and package metadata
+    let upper = name.to_uppercase().replace("AUTHORIZ", "AUTHZ").replace("AUTHOR", "");
+    SECRET_NAME_HINTS.iter().any(|hint| upper.contains(hint))
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_-+/.".contains(c)
+}
+
+/// Whether a value looks like a credential regardless of its key
+pub fn looks_like_token(value: &str) -> bool {
+    let value = value.trim().trim_end_matches('=');
+    if value.is_empty() || !value.chars().all(is_token_char) {
+        return false;
+    }
+
+    if let Some(prefix) = TOKEN_PREFIXES.iter().find(|p| value.starts_with(*p)) {
+        return value.len() >= prefix.len() + 8;
+    }
+
+    // Paths, URLs, file names and flags are not random even when long
+    if value.len() < MIN_ENTROPY_LEN
+        || value.starts_with(['/', '-'])
+        || value.contains("//")
+        || value.contains('.')
+    {
+        return false;
+    }
+    let has_digit = value.chars().any(|c| c.is_ascii_digit());
+    let has_upper = value.chars().any(|c| c.is_ascii_uppercase());
+    let has_lower = value.chars().any(|c| c.is_ascii_lowercase());
+    let is_hex = value.len() >= 32 && value.chars().all(|c| c.is_ascii_hexdigit());
+    has_digit && ((has_upper && has_lower) || is_hex) && shannon_entropy(value) >= MIN_ENTROPY_BITS
+}
+
+fn shannon_entropy(value: &str) -> f64 {
+    let mut counts = [0usize; 128];
+    for b in value.bytes() {
+        counts[(b & 0x7f) as usize] += 1;
+    }
+    let len = value.len() as f64;
+    counts
+        .iter()
+        .filter(|&&n| n > 0)
+        .map(|&n| {
+            let p = n as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// References (`${secret:NAME}`, `${env:VAR}`) and placeholders (`{{NAME}}`, `<token>`)
+/// hold no secret
+fn is_reference(value: &str) -> bool {
+    let rest = AUTH_SCHEMES.iter().find_map(|s| value.strip_prefix(s)).unwrap_or(value);
+    value.contains("${") || value.contains("{{") || (rest.starts_with('<') && rest.ends_with('>'))
+}
+
+/// Whether `value` stored under `key` should be masked
+///
+/// `secret_names` holds extra key names known to be secret, e.g. registry inputs
+/// marked `isSecret`.
+pub fn is_secret_value(key: &str, value: &str, secret_names: &HashSet<String>) -> bool {
+    let value = value.trim();
+    if value.is_empty() || is_reference(value) {
+        return false;
+    }
+    secret_names.contains(key) || is_secret_name(key) || looks_like_token(value)
+}
+
+/// Mask a secret, keeping a leading auth scheme or token prefix
+pub fn mask_value(value: &str) -> String {
+    let (scheme, rest) = AUTH_SCHEMES
+        .iter()
+        .find_map(|s| value.strip_prefix(s).map(|rest| (*s, rest)))
+        .unwrap_or(("", value));
+    let prefix = TOKEN_PREFIXES.iter().find(|p| rest.starts_with(*p)).copied().unwrap_or("");
+    format!("{}{}{}", scheme, prefix, MASK)
+}
+
+/// Copy of `config` with secret env values, headers and arguments masked
+pub fn redact_config(
+    config: &AgentServerEntry,
+    secret_names: &HashSet<String>,
+) -> AgentServerEntry {
+    let mut config = config.clone();
+    match &mut config {
+        AgentServerEntry::Local(local) => {
+            for (key, value) in local.env.iter_mut().flatten() {
+                if is_secret_value(key, value, secret_names) {
+                    *value = mask_value(value);
+                }
+            }
+            let mut secret_flag = false;
+            for arg in local.args.iter_mut().flatten() {
+                let is_flag = arg.starts_with('-');
+                if let Some((flag, value)) = arg.split_once('=').filter(|_| is_flag) {
+                    if is_secret_value(flag.trim_start_matches('-'), value, secret_names) {
+                        *arg = format!("{}={}", flag, mask_value(value));
+                    }
+                } else if (secret_flag && !is_flag && !is_reference(arg)) || looks_like_token(arg) {
+                    *arg = mask_value(arg);
+                }
+                secret_flag = is_flag && !arg.contains('=') && is_secret_name(arg);
+            }
+        }
+        AgentServerEntry::Remote(remote) => {
+            for (key, value) in remote.headers.iter_mut().flatten() {
+                if is_secret_value(key, value, secret_names) {
+                    *value = mask_value(value);
+                }
+            }
+        }
+    }
+    config
+}
+
+/// Mask secrets in free text such as config files, log lines and chat messages
+///
+/// Values assigned to secret keys (`"GITHUB_TOKEN": "..."`, `API_KEY = ...`,
+/// `Authorization: Bearer ...`, `?api_key=...`) are masked, as is any word that looks
+/// like a credential on its own.
+pub fn redact_text(text: &str) -> String {
+    redact_text_with(text, &HashSet::new())
+}
+
+/// [`redact_text`], also treating `secret_names` as secret keys
+pub fn redact_text_with(text: &str, secret_names: &HashSet<String>) -> String {
+    mask_token_words(&mask_assignments(text, secret_names))
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_-.".contains(c)
+}
+
+/// Mask values of `key: value` / `key = value` pairs whose key is secret
+fn mask_assignments(text: &str, secret_names: &HashSet<String>) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let at_boundary = i == 0 || !is_key_char(chars[i - 1]);
+        let masked = if at_boundary { mask_assignment_at(&chars, i, secret_names) } else { None };
+        match masked {
+            Some((replacement, end)) => {
+                out.push_str(&replacement);
+                i = end;
+            }
+            None => {
+                out.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// If a secret assignment starts at `start`, its masked text and end index
+fn mask_assignment_at(
+    chars: &[char],
+    start: usize,
+    secret_names: &HashSet<String>,
+) -> Option<(String, usize)> {
+    let mut i = start;
+    let key_quote = chars.get(i).copied().filter(|c| *c == '"' || *c == '\'');
+    if key_quote.is_some() {
+        i += 1;
+    }
+    let key_start = i;
+    while i < chars.len() && is_key_char(chars[i]) {
+        i += 1;
+    }
+    if i == key_start {
+        return None;
+    }
+    let key: String = chars[key_start..i].iter().collect();
+    if let Some(q) = key_quote {
+        if chars.get(i) != Some(&q) {
+            return None;
+        }
+        i += 1;
+    }
+    if !(secret_names.contains(&key) || is_secret_name(&key)) {
+        return None;
+    }
+
+    while chars.get(i).is_some_and(|c| *c == ' ' || *c == '\t') {
+        i += 1;
+    }
+    let separator = *chars.get(i)?;
+    let next = chars.get(i + 1).copied();
+    if !(separator == ':' || separator == '=') || next == Some('/') || next == Some(separator) {
+        return None;
+    }
+    i += 1;
+    while chars.get(i).is_some_and(|c| *c == ' ' || *c == '\t') {
+        i += 1;
+    }
+
+    let value_quote = chars.get(i).copied().filter(|c| *c == '"' || *c == '\'');
+    let (value_start, value_end, end) = match value_quote {
+        Some(q) => {
+            let value_start = i + 1;
+            let mut j = value_start;
+            while j < chars.len() && chars[j] != q && chars[j] != '\n' {
+                j += if chars[j] == '\\' { 2 } else { 1 };
+            }
+            let value_end = j.min(chars.len());
+            (value_start, value_end, (value_end + 1).min(chars.len()))
+        }
+        None => {
+            let value_end = bare_value_end(chars, i);
+            (i, value_end, value_end)
+        }
+    };
+
+    let value: String = chars[value_start..value_end].iter().collect();
+    let trimmed = value.trim();
+    let is_literal = trimmed.parse::<f64>().is_ok()
+        || matches!(trimmed, "true" | "false" | "null")
+        || trimmed.starts_with(['{', '[']);
+    if trimmed.is_empty() || is_literal || is_reference(trimmed) {
+        return None;
+    }
+
+    let mut replacement: String = chars[start..value_start].iter().collect();
+    replacement.push_str(&mask_value(&value));
+    replacement.extend(&chars[value_end..end]);
+    Some((replacement, end))
+}
+
+/// End of an unquoted value; an auth scheme takes the following word with it
+fn bare_value_end(chars: &[char], start: usize) -> usize {
+    let word_end = |from: usize| {
+        let mut j = from;
+        while j < chars.len() && !chars[j].is_whitespace() && !",;&\"'}]".contains(chars[j]) {
+            j += 1;
+        }
+        j
+    };
+    let end = word_end(start);
+    let word: String = chars[start..end].iter().collect();
+    let is_scheme = AUTH_SCHEMES.iter().any(|s| s.trim_end() == word);
+    if is_scheme && chars.get(end) == Some(&' ') {
+        word_end(end + 1)
+    } else {
+        end
+    }
+}
+
+/// Mask standalone words that look like credentials
+fn mask_token_words(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut word = String::new();
+    for c in text.chars() {
+        if is_token_char(c) {
+            word.push(c);
+            continue;
+        }
+        flush_word(&mut out, &mut word);
+        out.push(c);
+    }
+    flush_word(&mut out, &mut word);
+    out
+}
+
+fn flush_word(out: &mut String, word: &mut String) {
+    let trimmed = word.trim_end_matches('.');
+    if looks_like_token(trimmed) {
+        out.push_str(&mask_value(trimmed));
+        out.push_str(&word[trimmed.len()..]);
+    } else {
+        out.push_str(word);
+    }
+    word.clear();
+}
+
+/// Registry inputs marked `isSecret` for library servers named `server_name`
+pub fn registry_secret_names(server_name: &str) -> HashSet<String> {
+    RegistrySecrets::load().for_server(server_name).clone()
+}
+
+/// Registry inputs marked `isSecret` of every library server, by server name
+///
+/// Loads the library and schema store once, for operations that look up many servers.
+#[derive(Default)]
+pub struct RegistrySecrets {
+    by_name: HashMap<String, HashSet<String>>,
+    none: HashSet<String>,
+}
+
+impl RegistrySecrets {
+    pub fn load() -> Self {
+        let servers: Vec<(String, ServerOrigin)> = load_app_config()
+            .user_servers
+            .into_iter()
+            .filter_map(|s| Some((s.name, s.origin.filter(|o| o.schema_name.is_some())?)))
+            .collect();
+        // The store is large; most libraries have no registry servers to look up
+        if servers.is_empty() {
+            return Self::default();
+        }
+        let Ok(store) = load_schema_store() else {
+            return Self::default();
+        };
+
+        let mut by_name: HashMap<String, HashSet<String>> = HashMap::new();
+        for (name, origin) in servers {
+            by_name.entry(name).or_default().extend(origin_secret_names(&store, &origin));
+        }
+        RegistrySecrets { by_name, none: HashSet::new() }
+    }
+
+    pub fn for_server(&self, server_name: &str) -> &HashSet<String> {
+        self.by_name.get(server_name).unwrap_or(&self.none)
+    }
+}
+
+/// Registry inputs marked `isSecret` for a server added from `origin`
+pub fn origin_secret_names(store: &SchemaStore, origin: &ServerOrigin) -> HashSet<String> {
+    // Secret inputs rarely change between versions; the latest still beats not masking
+    // a server whose pinned version was pruned
+    origin
+        .schema_name
+        .as_deref()
+        .and_then(|name| store.schema_for_origin(origin).or(store.latest(name)))
+        .map(|s| s.secret_input_names())
+        .unwrap_or_default()
+}
@@ -3,13 +3,18 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use schemars::JsonSchema;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter};
 
+use crate::config::{load_app_config, save_app_config, AppConfig};
+use crate::mcp::sealed;
 use crate::mcp::server_schema::ServerSchema;
+use crate::mcp::server_updates::check_server_updates;
+use crate::mcp::user_server::ServerOrigin;
+use crate::mcp::vault::resolve_secret_references;
 
 // ============================================================================
 // Types
@@ -24,6 +29,72 @@ pub struct SchemaStore {
     /// Timestamp of last update (ISO 8601 format)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub updated_at: Option<String>,
+
+    /// Per source URL, the newest `updatedAt` the registry reported; the next refresh asks
+    /// for entries updated since then
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub synced_at: HashMap<String, String>,
+
+    /// Per source URL, why the last refresh of that source failed; its servers are the
+    /// ones cached before
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub source_errors: HashMap<String, String>,
+
+    /// URLs of the registry sources the store was built from, in precedence order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+
+    /// Superseded versions of each server, newest first
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub history: HashMap<String, Vec<ServerSchema>>,
+}
+
+impl SchemaStore {
+    /// Every known version of `name`, newest first
+    pub fn versions(&self, name: &str) -> Vec<ServerSchema> {
+        self.servers
+            .iter()
+            .filter(|s| s.name == name)
+            .chain(self.history.get(name).into_iter().flatten())
+            .cloned()
+            .collect()
+    }
+
+    /// The latest known version of `name`
+    pub fn latest(&self, name: &str) -> Option<&ServerSchema> {
+        self.servers.iter().find(|s| s.name == name)
+    }
+
+    /// The schema a registry-origin server was installed from: its pinned version, or
+    /// the latest for origins saved without one; `None` once the pinned version is gone
+    pub fn schema_for_origin(&self, origin: &ServerOrigin) -> Option<&ServerSchema> {
+        let name = origin.schema_name.as_deref()?;
+        let latest = self.latest(name);
+        let Some(version) = origin.version.as_deref() else {
+            return latest;
+        };
+        latest
+            .filter(|s| s.version == version)
+            .or_else(|| self.history.get(name)?.iter().find(|s| s.version == version))
+    }
+}
+
+/// A registry implementing the MCP Registry `/v0/servers` API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrySource {
+    /// Display name
+    pub name: String,
+
+    /// Base URL, e.g. `https://registry.modelcontextprotocol.io`
+    pub url: String,
+
+    /// `Authorization` header value, sealed at rest (see [`set_registry_auth_header`]);
+    /// may contain `${secret:NAME}` references
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_header: Option<String>,
+
+    pub enabled: bool,
 }
 
 /// API response from MCP Registry
@@ -36,6 +107,27 @@ pub struct RegistryResponse {
 #[derive(Debug, Clone, Deserialize)]
 pub struct ServerResponseItem {
     pub server: ServerSchema,
+
+    #[serde(rename = "_meta", default)]
+    pub meta: ResponseMeta,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ResponseMeta {
+    #[serde(rename = "io.modelcontextprotocol.registry/official", default)]
+    pub official: Option<OfficialMeta>,
+}
+
+/// Registry-managed metadata of one server version
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfficialMeta {
+    /// `active`, `deprecated` or `deleted`
+    #[serde(default)]
+    pub status: Option<String>,
+
+    #[serde(default)]
+    pub updated_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -49,8 +141,18 @@ pub struct RegistryMetadata {
 // Constants
 // ============================================================================
 
-const REGISTRY_API_URL: &str = "https://registry.modelcontextprotocol.io/v0/servers";
+pub const OFFICIAL_REGISTRY_URL: &str = "https://registry.modelcontextprotocol.io";
+const SERVERS_PATH: &str = "/v0/servers";
 const SCHEMA_STORE_FILENAME: &str = "schema_store.json";
+const AUTH_HEADER_AAD: &[u8] = b"rainmcp-registry-auth-v1";
+const STATUS_ACTIVE: &str = "active";
+
+/// Superseded versions kept per server
+const MAX_HISTORY_VERSIONS: usize = 20;
+const STATUS_DELETED: &str = "deleted";
+
+/// Overlap for stores synced by the local clock before registry timestamps were tracked
+const CLOCK_SKEW_MARGIN: chrono::Duration = chrono::Duration::minutes(10);
 
 /// Get the path to the schema store file
 pub fn get_schema_store_path() -> Result<PathBuf, String> {
@@ -67,38 +169,120 @@ pub fn get_schema_store_path() -> Result<PathBuf, String> {
     Ok(config_dir.join("rain-mcp").join(SCHEMA_STORE_FILENAME))
 }
 
-/// Fetch all servers from MCP Registry API (handles pagination with incremental deduplication)
-pub async fn fetch_registry_servers(app: &AppHandle) -> Result<Vec<ServerSchema>, String> {
-    info!("Starting to fetch registry servers");
+/// The official registry, used when no sources are configured
+pub fn official_registry_source() -> RegistrySource {
+    RegistrySource {
+        name: "MCP Registry".to_string(),
+        url: OFFICIAL_REGISTRY_URL.to_string(),
+        auth_header: None,
+        enabled: true,
+    }
+}
+
+/// Enabled registry sources in precedence order
+pub fn enabled_registry_sources(config: &AppConfig) -> Vec<RegistrySource> {
+    if config.registry_sources.is_empty() {
+        return vec![official_registry_source()];
+    }
+    config.registry_sources.iter().filter(|s| s.enabled).cloned().collect()
+}
+
+/// Store the `Authorization` header of the source with `url` sealed; `None` removes it
+pub fn set_registry_auth_header(url: &str, auth_header: Option<&str>) -> Result<(), String> {
+    let mut config = load_app_config();
+    let source = config
+        .registry_sources
+        .iter_mut()
+        .find(|s| s.url == url)
+        .ok_or_else(|| format!("Registry source '{}' not found", url))?;
+    source.auth_header = match auth_header.filter(|h| !h.is_empty()) {
+        Some(header) => Some(sealed::seal(header, AUTH_HEADER_AAD)?),
+        None => None,
+    };
+    save_app_config(&config)
+}
+
+/// Seal auth headers still stored in plain text (written by hand or by an older version)
+pub fn seal_plaintext_auth_headers() -> Result<(), String> {
+    let mut config = load_app_config();
+    let mut changed = false;
+    for source in &mut config.registry_sources {
+        if let Some(header) = source.auth_header.as_mut().filter(|h| !sealed::is_sealed(h)) {
+            *header = sealed::seal(header, AUTH_HEADER_AAD)?;
+            changed = true;
+        }
+    }
+    if changed {
+        save_app_config(&config)?;
+    }
+    Ok(())
+}
+
+/// The `Authorization` header to send to `source`, with secret references resolved
+pub fn resolve_auth_header(source: &RegistrySource) -> Result<Option<String>, String> {
+    let Some(stored) = source.auth_header.as_deref() else {
+        return Ok(None);
+    };
+    let header = if sealed::is_sealed(stored) {
+        sealed::open(stored, AUTH_HEADER_AAD)
+            .map_err(|e| format!("Cannot decrypt the auth header ({}). Please set it again", e))?
+    } else {
+        zeroize::Zeroizing::new(stored.to_string())
+    };
+    resolve_secret_references(&header).map(Some)
+}
+
+/// Source URL of a cached schema; schemas cached before sources existed are official
+fn source_of(server: &ServerSchema) -> &str {
+    server.registry_source.as_deref().unwrap_or(OFFICIAL_REGISTRY_URL)
+}
+
+/// Fetch servers from one registry source (handles pagination with incremental deduplication)
+///
+/// Fetched servers are tagged with the source and merged with `cached`; every version
+/// of every server is returned, with the newest `updatedAt` seen (or `updated_since` if
+/// nothing changed). With `updated_since` (RFC 3339) only entries changed after that time
+/// are requested, so `cached` should hold what was previously fetched from the source.
+pub async fn fetch_registry_servers(
+    progress: &(impl Fn(serde_json::Value) + Sync),
+    source: &RegistrySource,
+    cached: Vec<ServerSchema>,
+    updated_since: Option<&str>,
+) -> Result<(Vec<ServerSchema>, Option<String>), String> {
+    match updated_since {
+        Some(since) => info!("Fetching {} servers updated since {}", source.name, since),
+        None => info!("Starting to fetch {} servers", source.name),
+    }
     let client = reqwest::Client::new();
-    let mut server_map: HashMap<String, ServerSchema> = HashMap::new();
+    let endpoint = servers_endpoint(&source.url);
+    let auth_header = resolve_auth_header(source).map_err(|e| format!("{}: {}", source.name, e))?;
+    let mut server_map = HashMap::new();
+    let mut history = HashMap::new();
+    merge_servers_incremental(&mut server_map, &mut history, cached);
     let mut cursor: Option<String> = None;
     let mut page = 0;
+    let mut synced_at = updated_since.and_then(parse_timestamp);
 
     loop {
         page += 1;
-        let mut url = REGISTRY_API_URL.to_string();
-        if let Some(ref c) = cursor {
-            url = format!("{}?cursor={}", url, c);
-        }
+        let url = registry_page_url(&endpoint, cursor.as_deref(), updated_since)?;
 
-        let registry_response = fetch_single_page(&client, &url, page).await?;
+        let registry_response = fetch_single_page(&client, &url, auth_header.as_deref(), page)
+            .await
+            .map_err(|e| format!("{}: {}", source.name, e))?;
         let server_count = registry_response.servers.len();
         debug!("Page {} parsed successfully: {} servers", page, server_count);
 
         // Incremental deduplication - merge new servers immediately
-        let new_servers: Vec<ServerSchema> =
-            registry_response.servers.into_iter().map(|item| item.server).collect();
-        merge_servers_incremental(&mut server_map, new_servers);
-
-        let _ = app.emit(
-            "refresh-registry-progress",
-            serde_json::json!({
-                "page": page,
-                "fetched": server_count,
-                "total": server_map.len()
-            }),
-        );
+        let newest = merge_page(&mut server_map, &mut history, registry_response.servers, source);
+        synced_at = synced_at.max(newest);
+
+        progress(serde_json::json!({
+            "source": source.name,
+            "page": page,
+            "fetched": server_count,
+            "total": server_map.len()
+        }));
 
         match registry_response.metadata.next_cursor {
             Some(next) if !next.is_empty() => {
@@ -112,25 +296,117 @@ pub async fn fetch_registry_servers(app: &AppHandle) -> Result<Vec<ServerSchema>
         }
     }
 
-    let unique_servers: Vec<ServerSchema> = server_map.into_values().collect();
-    info!("Fetched {} unique servers from {} pages", unique_servers.len(), page);
+    info!("Fetched {} unique servers from {} pages of {}", server_map.len(), page, source.name);
+
+    let servers = server_map.into_values().chain(history.into_values().flatten()).collect();
+    Ok((servers, synced_at.map(format_timestamp)))
+}
+
+fn parse_timestamp(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(value).ok().map(|t| t.with_timezone(&chrono::Utc))
+}
 
-    Ok(unique_servers)
+/// Whole seconds, so an `updated_since` built from it re-fetches the same second
+fn format_timestamp(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// Merge one page from `source`, dropping versions the registry reports as deleted;
+/// returns the newest `updatedAt` on the page
+fn merge_page(
+    server_map: &mut HashMap<String, ServerSchema>,
+    history: &mut HashMap<String, Vec<ServerSchema>>,
+    items: Vec<ServerResponseItem>,
+    source: &RegistrySource,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    let mut newest = None;
+    for item in items {
+        let official =
+            item.meta.official.unwrap_or(OfficialMeta { status: None, updated_at: None });
+        newest = newest.max(official.updated_at.as_deref().and_then(parse_timestamp));
+
+        let status = official.status.filter(|s| s != STATUS_ACTIVE);
+        if status.as_deref() == Some(STATUS_DELETED) {
+            debug!("{} {} was deleted from {}", item.server.name, item.server.version, source.name);
+            remove_version(server_map, history, &item.server.name, &item.server.version);
+            continue;
+        }
+        let server = ServerSchema {
+            registry_source: Some(source.url.clone()),
+            registry_status: status,
+            ..item.server
+        };
+        merge_servers_incremental(server_map, history, vec![server]);
+    }
+    newest
+}
+
+/// Forget one version, promoting the newest remaining one if it was the latest
+fn remove_version(
+    server_map: &mut HashMap<String, ServerSchema>,
+    history: &mut HashMap<String, Vec<ServerSchema>>,
+    name: &str,
+    version: &str,
+) {
+    let versions = history.entry(name.to_string()).or_default();
+    versions.retain(|s| s.version != version);
+    if server_map.get(name).is_some_and(|s| s.version == version) {
+        server_map.remove(name);
+        if !versions.is_empty() {
+            server_map.insert(name.to_string(), versions.remove(0));
+        }
+    }
+    if versions.is_empty() {
+        history.remove(name);
+    }
+}
+
+/// The `/v0/servers` endpoint of a registry base URL
+fn servers_endpoint(url: &str) -> String {
+    let url = url.trim_end_matches('/');
+    if url.ends_with(SERVERS_PATH) {
+        url.to_string()
+    } else {
+        format!("{}{}", url, SERVERS_PATH)
+    }
+}
+
+/// URL of one registry page, with the cursor and `updated_since` filter as query params
+fn registry_page_url(
+    base: &str,
+    cursor: Option<&str>,
+    updated_since: Option<&str>,
+) -> Result<String, String> {
+    let mut url = url::Url::parse(base).map_err(|e| format!("Invalid registry URL: {}", e))?;
+    {
+        let mut query = url.query_pairs_mut();
+        if let Some(cursor) = cursor {
+            query.append_pair("cursor", cursor);
+        }
+        if let Some(since) = updated_since {
+            query.append_pair("updated_since", since);
+        }
+    }
+    Ok(url.to_string().trim_end_matches('?').to_string())
 }
 
 /// Fetch a single page from the registry API
 async fn fetch_single_page(
     client: &reqwest::Client,
     url: &str,
+    auth_header: Option<&str>,
     page: usize,
 ) -> Result<RegistryResponse, String> {
     debug!("Fetching page {} from: {}", page, url);
 
-    let response =
-        client.get(url).header("Accept", "application/json").send().await.map_err(|e| {
-            error!("Failed to fetch registry page {}: {}", page, e);
-            format!("Failed to fetch registry: {}", e)
-        })?;
+    let mut request = client.get(url).header("Accept", "application/json");
+    if let Some(auth_header) = auth_header {
+        request = request.header("Authorization", auth_header);
+    }
+    let response = request.send().await.map_err(|e| {
+        error!("Failed to fetch registry page {}: {}", page, e);
+        format!("Failed to fetch registry: {}", e)
+    })?;
 
     let status = response.status();
     debug!("Page {} response status: {}", page, status);
@@ -177,25 +453,55 @@ fn parse_registry_response(text: &str, page: usize) -> Result<RegistryResponse,
 }
 
 /// Incrementally merge new servers into the map, keeping latest versions
+///
+/// Older versions go to `history`. A server with the same version as a known one
+/// replaces it, so metadata edited in place by the registry is picked up by incremental
+/// refreshes.
 fn merge_servers_incremental(
     server_map: &mut HashMap<String, ServerSchema>,
+    history: &mut HashMap<String, Vec<ServerSchema>>,
     new_servers: Vec<ServerSchema>,
 ) {
     for server in new_servers {
         let name = server.name.clone();
-        if let Some(existing) = server_map.get(&name) {
-            if is_version_newer(&server.version, &existing.version) {
+        match server_map.get(&name) {
+            Some(existing) if is_version_newer(&existing.version, &server.version) => {
+                record_version(history, server);
+            }
+            Some(existing) if existing.version == server.version => {
+                server_map.insert(name, server);
+            }
+            Some(_) => {
+                if let Some(previous) = server_map.insert(name, server) {
+                    record_version(history, previous);
+                }
+            }
+            None => {
                 server_map.insert(name, server);
             }
-        } else {
-            server_map.insert(name, server);
         }
     }
 }
 
+/// Add a superseded version to the history, keeping it ordered newest first and
+/// dropping the oldest beyond `MAX_HISTORY_VERSIONS`
+fn record_version(history: &mut HashMap<String, Vec<ServerSchema>>, server: ServerSchema) {
+    let versions = history.entry(server.name.clone()).or_default();
+    if let Some(known) = versions.iter_mut().find(|s| s.version == server.version) {
+        *known = server;
+        return;
+    }
+    let position = versions
+        .iter()
+        .position(|s| is_version_newer(&server.version, &s.version))
+        .unwrap_or(versions.len());
+    versions.insert(position, server);
+    versions.truncate(MAX_HISTORY_VERSIONS);
+}
+
 /// Check if version1 is newer than version2 using proper semver parsing
 /// Falls back to string comparison if semver parsing fails
-fn is_version_newer(v1: &str, v2: &str) -> bool {
+pub fn is_version_newer(v1: &str, v2: &str) -> bool {
     match (Version::parse(v1), Version::parse(v2)) {
         (Ok(ver1), Ok(ver2)) => ver1 > ver2,
         _ => {
@@ -259,17 +565,182 @@ pub fn load_schema_store() -> Result<SchemaStore, String> {
     serde_json::from_str(&content).map_err(|e| format!("Failed to parse schema store: {}", e))
 }
 
-/// Refresh schema store by fetching from registry
-pub async fn refresh_schema_store_impl(app: &AppHandle) -> Result<SchemaStore, String> {
-    let servers = fetch_registry_servers(app).await?;
+/// Per source URL, the time to fetch updates from, when an incremental refresh is possible
+///
+/// Returns `None` (full refresh) when the store is missing, empty, unreadable, has no
+/// valid timestamp or was built from other sources than `source_urls`.
+fn incremental_base(
+    store: Result<SchemaStore, String>,
+    source_urls: &[String],
+) -> Option<HashMap<String, String>> {
+    let store = match store {
+        Ok(store) => store,
+        Err(e) => {
+            warn!("Schema store is unreadable, doing a full refresh: {}", e);
+            return None;
+        }
+    };
+    if store.servers.is_empty() {
+        return None;
+    }
+    let store_sources = if store.sources.is_empty() {
+        vec![OFFICIAL_REGISTRY_URL.to_string()]
+    } else {
+        store.sources
+    };
+    if store_sources != source_urls {
+        info!("Registry sources changed, doing a full refresh");
+        return None;
+    }
+
+    // Stores from before per-source sync times only have the local refresh time
+    if store.synced_at.is_empty() {
+        let updated_at = parse_timestamp(store.updated_at.as_deref()?)? - CLOCK_SKEW_MARGIN;
+        return Some(
+            source_urls.iter().map(|url| (url.clone(), format_timestamp(updated_at))).collect(),
+        );
+    }
+    Some(store.synced_at)
+}
+
+/// Latest servers and version history of all sources, keeping the first source's
+/// versions when names clash
+fn resolve_name_clashes(
+    per_source: Vec<Vec<ServerSchema>>,
+) -> (Vec<ServerSchema>, HashMap<String, Vec<ServerSchema>>) {
+    let mut owners: HashMap<String, usize> = HashMap::new();
+    for (index, servers) in per_source.iter().enumerate() {
+        for server in servers {
+            owners.entry(server.name.clone()).or_insert(index);
+        }
+    }
+
+    let mut server_map = HashMap::new();
+    let mut history = HashMap::new();
+    for (index, servers) in per_source.into_iter().enumerate() {
+        let (owned, shadowed): (Vec<_>, Vec<_>) =
+            servers.into_iter().partition(|s| owners[&s.name] == index);
+        for server in shadowed {
+            debug!("{} from {} is shadowed by another source", server.name, source_of(&server));
+        }
+        merge_servers_incremental(&mut server_map, &mut history, owned);
+    }
 
-    let store = SchemaStore { servers, updated_at: Some(chrono::Utc::now().to_rfc3339()) };
+    let mut servers: Vec<ServerSchema> = server_map.into_values().collect();
+    servers.sort_by(|a, b| a.name.cmp(&b.name));
+    (servers, history)
+}
 
+/// Every cached version fetched from the source with `url`
+fn cached_from_source(store: &SchemaStore, url: &str) -> Vec<ServerSchema> {
+    store
+        .servers
+        .iter()
+        .chain(store.history.values().flatten())
+        .filter(|s| source_of(s) == url)
+        .map(|s| ServerSchema { registry_source: Some(url.to_string()), ..s.clone() })
+        .collect()
+}
+
+/// Cached servers and `updated_since` for fetching the source with `url`
+///
+/// Fetches are incremental only when the source has a sync time and cached servers;
+/// a full fetch starts from nothing, so servers the registry dropped disappear.
+fn fetch_start<'a>(
+    since: Option<&'a HashMap<String, String>>,
+    previous: &SchemaStore,
+    url: &str,
+) -> (Vec<ServerSchema>, Option<&'a str>) {
+    let Some(since) = since.and_then(|since| since.get(url)) else {
+        return (Vec::new(), None);
+    };
+    let cached = cached_from_source(previous, url);
+    if cached.is_empty() {
+        return (Vec::new(), None);
+    }
+    (cached, Some(since))
+}
+
+/// Refresh schema store by fetching from every enabled registry source
+///
+/// The new store is saved, and user servers with a newer version are then reported in a
+/// `server-updates-available` event.
+pub async fn refresh_schema_store_impl(app: &AppHandle) -> Result<SchemaStore, String> {
+    let sources = enabled_registry_sources(&load_app_config());
+    if sources.is_empty() {
+        return Err("No registry sources are enabled".to_string());
+    }
+
+    let previous = load_schema_store();
+    let progress = |payload| {
+        let _ = app.emit("refresh-registry-progress", payload);
+    };
+    let store = fetch_schema_store(&sources, previous.clone(), &progress).await?;
     save_schema_store(&store)?;
 
+    match check_server_updates(&previous.unwrap_or_default(), &store) {
+        Ok(updates) => {
+            let _ = app.emit("server-updates-available", &updates);
+        }
+        Err(e) => warn!("Failed to check user servers for updates: {}", e),
+    }
+
     Ok(store)
 }
 
+/// Fetch `sources` into a new store, starting from `previous`
+///
+/// Only servers updated since the last refresh are fetched when the store is usable.
+/// A source that cannot be fetched keeps its cached servers and is listed in
+/// `source_errors`; the refresh only fails when every source does.
+pub async fn fetch_schema_store(
+    sources: &[RegistrySource],
+    previous: Result<SchemaStore, String>,
+    progress: &(impl Fn(serde_json::Value) + Sync),
+) -> Result<SchemaStore, String> {
+    let source_urls: Vec<String> = sources.iter().map(|s| s.url.clone()).collect();
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let base = incremental_base(previous.clone(), &source_urls);
+    let previous_store = previous.unwrap_or_default();
+
+    let mut per_source = Vec::new();
+    let mut synced_at = HashMap::new();
+    let mut source_errors = HashMap::new();
+    for source in sources {
+        let (cached, updated_since) = fetch_start(base.as_ref(), &previous_store, &source.url);
+        match fetch_registry_servers(progress, source, cached, updated_since).await {
+            Ok((servers, source_synced_at)) => {
+                if let Some(time) = source_synced_at {
+                    synced_at.insert(source.url.clone(), time);
+                }
+                per_source.push(servers);
+            }
+            Err(e) => {
+                warn!("Keeping cached servers of {}: {}", source.name, e);
+                if let Some(time) = previous_store.synced_at.get(&source.url) {
+                    synced_at.insert(source.url.clone(), time.clone());
+                }
+                per_source.push(cached_from_source(&previous_store, &source.url));
+                source_errors.insert(source.url.clone(), e);
+            }
+        }
+    }
+    if source_errors.len() == sources.len() {
+        let errors: Vec<String> = source_errors.into_values().collect();
+        return Err(errors.join("; "));
+    }
+
+    let (servers, history) = resolve_name_clashes(per_source);
+    Ok(SchemaStore {
+        servers,
+        updated_at: Some(started_at),
+        synced_at,
+        source_errors,
+        sources: source_urls,
+        history,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -297,6 +768,243 @@ mod tests {
         assert!(!is_version_newer("1.0", "1.0.1"));
     }
 
+    #[test]
+    fn test_registry_page_url() {
+        let endpoint = servers_endpoint(OFFICIAL_REGISTRY_URL);
+        assert_eq!(endpoint, "https://registry.modelcontextprotocol.io/v0/servers");
+        assert_eq!(
+            servers_endpoint("http://localhost:8080/v0/servers/"),
+            "http://localhost:8080/v0/servers"
+        );
+
+        assert_eq!(registry_page_url(&endpoint, None, None).unwrap(), endpoint);
+        assert_eq!(
+            registry_page_url(&endpoint, Some("a/b"), Some("2025-01-01T00:00:00+00:00")).unwrap(),
+            format!("{}?cursor=a%2Fb&updated_since=2025-01-01T00%3A00%3A00%2B00%3A00", endpoint)
+        );
+    }
+
+    #[test]
+    fn test_enabled_registry_sources() {
+        assert_eq!(
+            enabled_registry_sources(&AppConfig::default()),
+            vec![official_registry_source()]
+        );
+
+        let private = RegistrySource {
+            name: "Acme".to_string(),
+            url: "https://mcp.acme.internal".to_string(),
+            auth_header: Some("Bearer ${secret:ACME_REGISTRY}".to_string()),
+            enabled: true,
+        };
+        let disabled = RegistrySource { enabled: false, ..official_registry_source() };
+        let config =
+            AppConfig { registry_sources: vec![private.clone(), disabled], ..Default::default() };
+        assert_eq!(enabled_registry_sources(&config), vec![private]);
+    }
+
+    #[test]
+    fn test_resolve_name_clashes() {
+        let tagged = |name: &str, source: &str| ServerSchema {
+            registry_source: Some(source.to_string()),
+            ..schema(name, "1.0.0", source)
+        };
+
+        let (servers, history) = resolve_name_clashes(vec![
+            vec![tagged("a", "private"), tagged("b", "private")],
+            vec![
+                tagged("b", "official"),
+                ServerSchema { version: "2.0.0".to_string(), ..tagged("b", "official") },
+                tagged("c", "official"),
+            ],
+        ]);
+
+        let summary: Vec<_> = servers.iter().map(|s| (s.name.as_str(), source_of(s))).collect();
+        assert_eq!(summary, vec![("a", "private"), ("b", "private"), ("c", "official")]);
+        assert_eq!(servers[1].version, "1.0.0");
+        assert!(history.is_empty());
+    }
+
+    fn schema(name: &str, version: &str, description: &str) -> ServerSchema {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "description": description,
+            "version": version
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_incremental_base() {
+        let official = vec![OFFICIAL_REGISTRY_URL.to_string()];
+        let servers = vec![schema("a", "1.0.0", "old"), schema("b", "1.0.0", "b")];
+        let history = HashMap::from([("a".to_string(), vec![schema("a", "0.9.0", "older")])]);
+        let store = |updated_at: Option<&str>| SchemaStore {
+            servers: servers.clone(),
+            updated_at: updated_at.map(str::to_string),
+            history: history.clone(),
+            ..Default::default()
+        };
+
+        // Without registry timestamps the local refresh time is used, with an overlap
+        let since =
+            incremental_base(Ok(store(Some("2025-01-02T03:04:05+02:00"))), &official).unwrap();
+        assert_eq!(since, HashMap::from([(official[0].clone(), "2025-01-02T00:54:05Z".into())]));
+
+        let synced_at = HashMap::from([(official[0].clone(), "2025-01-01T12:00:00Z".to_string())]);
+        let synced =
+            SchemaStore { synced_at: synced_at.clone(), ..store(Some("2025-01-02T00:00:00Z")) };
+        assert_eq!(incremental_base(Ok(synced), &official).unwrap(), synced_at);
+
+        assert!(incremental_base(Ok(store(None)), &official).is_none());
+        assert!(incremental_base(Ok(store(Some("yesterday"))), &official).is_none());
+        assert!(incremental_base(Ok(SchemaStore::default()), &official).is_none());
+        assert!(
+            incremental_base(Err("Failed to parse schema store".to_string()), &official).is_none()
+        );
+
+        // Adding or reordering sources changes precedence, so everything is fetched again
+        let sources = vec!["https://mcp.acme.internal".to_string(), official[0].clone()];
+        let store =
+            SchemaStore { sources: official.clone(), ..store(Some("2025-01-02T00:00:00Z")) };
+        assert!(incremental_base(Ok(store.clone()), &official).is_some());
+        assert!(incremental_base(Ok(store), &sources).is_none());
+    }
+
+    #[test]
+    fn test_cached_from_source() {
+        let tagged = |name: &str, version: &str, source: Option<&str>| ServerSchema {
+            registry_source: source.map(str::to_string),
+            ..schema(name, version, "")
+        };
+        let store = SchemaStore {
+            servers: vec![tagged("a", "2.0.0", None), tagged("b", "1.0.0", Some("private"))],
+            history: HashMap::from([("a".to_string(), vec![tagged("a", "1.0.0", None)])]),
+            ..Default::default()
+        };
+
+        // Schemas cached before sources existed count as official and get tagged
+        let official = cached_from_source(&store, OFFICIAL_REGISTRY_URL);
+        let summary: Vec<_> =
+            official.iter().map(|s| (s.name.as_str(), s.version.as_str())).collect();
+        assert_eq!(summary, vec![("a", "2.0.0"), ("a", "1.0.0")]);
+        assert!(official.iter().all(|s| source_of(s) == OFFICIAL_REGISTRY_URL));
+        assert_eq!(cached_from_source(&store, "private").len(), 1);
+
+        // Only sources with a sync time start from their cache
+        let since = HashMap::from([("private".to_string(), "2025-01-01T00:00:00Z".to_string())]);
+        let (cached, updated_since) = fetch_start(Some(&since), &store, "private");
+        assert_eq!((cached.len(), updated_since), (1, Some("2025-01-01T00:00:00Z")));
+        let (cached, updated_since) = fetch_start(Some(&since), &store, OFFICIAL_REGISTRY_URL);
+        assert!(cached.is_empty() && updated_since.is_none());
+        let (cached, updated_since) = fetch_start(None, &store, "private");
+        assert!(cached.is_empty() && updated_since.is_none());
+    }
+
+    #[test]
+    fn test_merge_updates_into_cached_servers() {
+        let mut map = HashMap::new();
+        let mut history = HashMap::new();
+        merge_servers_incremental(
+            &mut map,
+            &mut history,
+            vec![schema("a", "1.0.0", "old"), schema("b", "2.0.0", "b")],
+        );
+        merge_servers_incremental(
+            &mut map,
+            &mut history,
+            vec![
+                schema("a", "1.0.0", "edited"),
+                schema("b", "1.0.0", "older"),
+                schema("c", "0.1.0", "new"),
+            ],
+        );
+
+        assert_eq!(map["a"].description, "edited");
+        assert_eq!(map["b"].version, "2.0.0");
+        assert_eq!(map["c"].version, "0.1.0");
+        assert_eq!(history["b"][0].description, "older");
+        assert!(!history.contains_key("a"));
+    }
+
+    #[test]
+    fn test_merge_keeps_version_history() {
+        let mut map = HashMap::new();
+        let mut history = HashMap::new();
+        for version in ["1.1.0", "2.0.0", "1.0.0", "1.2.0", "1.1.0"] {
+            merge_servers_incremental(&mut map, &mut history, vec![schema("a", version, version)]);
+        }
+
+        assert_eq!(map["a"].version, "2.0.0");
+        let versions: Vec<_> = history["a"].iter().map(|s| s.version.as_str()).collect();
+        assert_eq!(versions, vec!["1.2.0", "1.1.0", "1.0.0"]);
+
+        // Only the newest superseded versions are kept
+        for patch in 0..30 {
+            let version = format!("0.0.{}", patch);
+            merge_servers_incremental(&mut map, &mut history, vec![schema("a", &version, "")]);
+        }
+        assert_eq!(history["a"].len(), MAX_HISTORY_VERSIONS);
+        assert_eq!(history["a"][0].version, "1.2.0");
+        assert_eq!(history["a"].last().unwrap().version, "0.0.13");
+    }
+
+    fn item(name: &str, version: &str, status: &str, updated_at: &str) -> ServerResponseItem {
+        serde_json::from_value(serde_json::json!({
+            "server": { "name": name, "description": "", "version": version },
+            "_meta": {
+                "io.modelcontextprotocol.registry/official": {
+                    "status": status,
+                    "updatedAt": updated_at
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_merge_page_applies_registry_status() {
+        let source = official_registry_source();
+        let mut map = HashMap::new();
+        let mut history = HashMap::new();
+        let newest = merge_page(
+            &mut map,
+            &mut history,
+            vec![
+                item("a", "1.0.0", "active", "2025-01-01T00:00:00Z"),
+                item("a", "2.0.0", "active", "2025-01-03T00:00:00.5Z"),
+                item("b", "1.0.0", "active", "2025-01-02T00:00:00Z"),
+            ],
+            &source,
+        );
+        assert_eq!(newest.map(format_timestamp).as_deref(), Some("2025-01-03T00:00:00Z"));
+
+        let newest = merge_page(
+            &mut map,
+            &mut history,
+            vec![
+                item("a", "2.0.0", "deleted", "2025-01-04T00:00:00Z"),
+                item("b", "1.0.0", "deprecated", "2025-01-04T00:00:00Z"),
+            ],
+            &source,
+        );
+        assert_eq!(newest.map(format_timestamp).as_deref(), Some("2025-01-04T00:00:00Z"));
+
+        // The deleted latest version gives way to the one before it
+        assert_eq!(map["a"].version, "1.0.0");
+        assert!(!history.contains_key("a"));
+        assert_eq!(map["b"].registry_status.as_deref(), Some("deprecated"));
+        assert_eq!(map["b"].registry_source.as_deref(), Some(OFFICIAL_REGISTRY_URL));
+
+        // Entries without registry metadata are kept as they are
+        let plain: ServerResponseItem = serde_json::from_value(serde_json::json!({
+            "server": { "name": "c", "description": "", "version": "1.0.0" }
+        }))
+        .unwrap();
+        assert!(merge_page(&mut map, &mut history, vec![plain], &source).is_none());
+        assert!(map["c"].registry_status.is_none());
+    }
+
     #[test]
     fn test_edge_cases() {
         // Same versions
@@ -0,0 +1,295 @@
+//! Ranked search over the schema store
+//!
+//! The store is indexed once per version of `schema_store.json`: an inverted index maps
+//! each token of a server's name, title, description and package identifiers to the
+//! servers containing it, weighted by field. Queries are scored with BM25 over those
+//! weighted term frequencies; every query token must match a term exactly or as a
+//! prefix, and prefix matches score lower than exact ones.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::registry::{get_schema_store_path, load_schema_store};
+use super::server_schema::{LocalTransport, ServerSchema};
+
+const NAME_WEIGHT: f32 = 3.0;
+const TITLE_WEIGHT: f32 = 2.5;
+const IDENTIFIER_WEIGHT: f32 = 2.0;
+const DESCRIPTION_WEIGHT: f32 = 1.0;
+
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+
+/// Score factor for terms matched by prefix only
+const PREFIX_FACTOR: f32 = 0.6;
+/// Shorter query tokens only match whole terms
+const MIN_PREFIX_LEN: usize = 2;
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TransportFilter {
+    /// Has a package run over stdio
+    Stdio,
+    /// Has a hosted remote endpoint
+    Remote,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaSearchQuery {
+    /// Free text; empty lists every server matching the filters, sorted by name
+    #[serde(default)]
+    pub text: String,
+
+    /// Package registry types to include (`npm`, `pypi`, `oci`, ...); empty means any
+    #[serde(default)]
+    pub registry_types: Vec<String>,
+
+    #[serde(default)]
+    pub transport: Option<TransportFilter>,
+
+    /// Only servers that do (or do not) declare secret inputs
+    #[serde(default)]
+    pub has_secrets: Option<bool>,
+
+    #[serde(default)]
+    pub offset: usize,
+
+    /// Page size, 50 by default
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaSearchHit {
+    pub server: ServerSchema,
+
+    /// Relevance; 0 when the query has no text
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaSearchPage {
+    /// Number of matching servers across all pages
+    pub total: usize,
+    pub offset: usize,
+    pub hits: Vec<SchemaSearchHit>,
+}
+
+/// Lowercase alphanumeric runs: `io.github.acme/mcp-server` → `io`, `github`, `acme`, ...
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric()).filter(|t| !t.is_empty()).map(str::to_lowercase)
+}
+
+/// Per-server facts used by the filters
+struct Facets {
+    registry_types: Vec<String>,
+    stdio: bool,
+    remote: bool,
+    has_secrets: bool,
+}
+
+impl Facets {
+    fn of(server: &ServerSchema) -> Self {
+        let secret_argument = server
+            .packages
+            .iter()
+            .flat_map(|p| p.package_arguments.iter().chain(&p.runtime_arguments))
+            .any(|a| a.is_secret);
+        Facets {
+            registry_types: server
+                .packages
+                .iter()
+                .map(|p| p.registry_type.to_lowercase())
+                .collect(),
+            stdio: server.packages.iter().any(|p| matches!(p.transport, LocalTransport::Stdio)),
+            remote: !server.remotes.is_empty(),
+            has_secrets: secret_argument || !server.secret_input_names().is_empty(),
+        }
+    }
+
+    fn matches(&self, query: &SchemaSearchQuery) -> bool {
+        let registry_type = query.registry_types.is_empty()
+            || query
+                .registry_types
+                .iter()
+                .any(|wanted| self.registry_types.contains(&wanted.to_lowercase()));
+        let transport = match query.transport {
+            None => true,
+            Some(TransportFilter::Stdio) => self.stdio,
+            Some(TransportFilter::Remote) => self.remote,
+        };
+        let secrets = query.has_secrets.is_none_or(|wanted| wanted == self.has_secrets);
+        registry_type && transport && secrets
+    }
+}
+
+/// Inverted index over a snapshot of the schema store
+pub struct SchemaIndex {
+    servers: Vec<ServerSchema>,
+    facets: Vec<Facets>,
+    /// Weighted field length of each server
+    lengths: Vec<f32>,
+    average_length: f32,
+    /// Term → (server, weighted term frequency)
+    postings: HashMap<String, Vec<(u32, f32)>>,
+    /// Sorted terms, for prefix lookups
+    terms: Vec<String>,
+}
+
+impl SchemaIndex {
+    pub fn build(servers: Vec<ServerSchema>) -> Self {
+        let mut postings: HashMap<String, Vec<(u32, f32)>> = HashMap::new();
+        let mut lengths = Vec::with_capacity(servers.len());
+
+        for (id, server) in servers.iter().enumerate() {
+            let mut frequencies: HashMap<String, f32> = HashMap::new();
+            let mut length = 0.0;
+            let identifiers: Vec<&str> =
+                server.packages.iter().map(|p| p.identifier.as_str()).collect();
+            let fields = [
+                (server.name.as_str(), NAME_WEIGHT),
+                (server.title.as_deref().unwrap_or(""), TITLE_WEIGHT),
+                (server.description.as_str(), DESCRIPTION_WEIGHT),
+            ]
+            .into_iter()
+            .chain(identifiers.into_iter().map(|i| (i, IDENTIFIER_WEIGHT)));
+
+            for (text, weight) in fields {
+                for token in tokenize(text) {
+                    *frequencies.entry(token).or_default() += weight;
+                    length += weight;
+                }
+            }
+            for (term, frequency) in frequencies {
+                postings.entry(term).or_default().push((id as u32, frequency));
+            }
+            lengths.push(length);
+        }
+
+        let average_length = if lengths.is_empty() {
+            1.0
+        } else {
+            (lengths.iter().sum::<f32>() / lengths.len() as f32).max(1.0)
+        };
+        let mut terms: Vec<String> = postings.keys().cloned().collect();
+        terms.sort_unstable();
+        let facets = servers.iter().map(Facets::of).collect();
+
+        SchemaIndex { servers, facets, lengths, average_length, postings, terms }
+    }
+
+    /// Indexed terms starting with `prefix`
+    fn terms_with_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a String> {
+        let start = self.terms.partition_point(|t| t.as_str() < prefix);
+        self.terms[start..].iter().take_while(move |t| t.starts_with(prefix))
+    }
+
+    /// BM25 score of each server for one query token; exact and prefix matches combined
+    fn token_scores(&self, token: &str) -> HashMap<u32, f32> {
+        let n = self.servers.len() as f32;
+        let mut scores: HashMap<u32, f32> = HashMap::new();
+        let mut add_term = |term: &str, factor: f32| {
+            let Some(postings) = self.postings.get(term) else {
+                return;
+            };
+            let df = postings.len() as f32;
+            let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+            for &(id, tf) in postings {
+                let norm = K1 * (1.0 - B + B * self.lengths[id as usize] / self.average_length);
+                let score = factor * idf * tf * (K1 + 1.0) / (tf + norm);
+                let best = scores.entry(id).or_default();
+                *best = best.max(score);
+            }
+        };
+
+        add_term(token, 1.0);
+        if token.chars().count() >= MIN_PREFIX_LEN {
+            for term in self.terms_with_prefix(token).filter(|t| t.as_str() != token) {
+                add_term(term, PREFIX_FACTOR);
+            }
+        }
+        scores
+    }
+
+    pub fn search(&self, query: &SchemaSearchQuery) -> SchemaSearchPage {
+        let tokens: Vec<String> = tokenize(&query.text).collect();
+
+        let mut ranked: Vec<(u32, f32)> = if tokens.is_empty() {
+            (0..self.servers.len() as u32).map(|id| (id, 0.0)).collect()
+        } else {
+            // Every token must match; start from the rarest to keep the candidate set small
+            let mut per_token: Vec<HashMap<u32, f32>> =
+                tokens.iter().map(|t| self.token_scores(t)).collect();
+            per_token.sort_by_key(|scores| scores.len());
+            let (first, rest) = per_token.split_first().expect("tokens is not empty");
+            first
+                .iter()
+                .filter_map(|(&id, &score)| {
+                    rest.iter()
+                        .try_fold(score, |total, scores| scores.get(&id).map(|s| total + s))
+                        .map(|total| (id, total))
+                })
+                .collect()
+        };
+        ranked.retain(|(id, _)| self.facets[*id as usize].matches(query));
+        ranked.sort_by(|a, b| {
+            b.1.total_cmp(&a.1)
+                .then_with(|| self.servers[a.0 as usize].name.cmp(&self.servers[b.0 as usize].name))
+        });
+
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+        let hits = ranked
+            .iter()
+            .skip(query.offset)
+            .take(limit)
+            .map(|&(id, score)| SchemaSearchHit {
+                server: self.servers[id as usize].clone(),
+                score,
+            })
+            .collect();
+        SchemaSearchPage { total: ranked.len(), offset: query.offset, hits }
+    }
+}
+
+/// Identifies one version of the store file
+type StoreVersion = (PathBuf, Option<SystemTime>, u64);
+type CachedIndex = (StoreVersion, Arc<SchemaIndex>);
+
+static INDEX: Lazy<Mutex<Option<CachedIndex>>> = Lazy::new(|| Mutex::new(None));
+
+/// Index of the current schema store, rebuilt when the store file changed
+pub fn schema_index() -> Result<Arc<SchemaIndex>, String> {
+    let path = get_schema_store_path()?;
+    let metadata = std::fs::metadata(&path).ok();
+    let version = (
+        path,
+        metadata.as_ref().and_then(|m| m.modified().ok()),
+        metadata.as_ref().map_or(0, |m| m.len()),
+    );
+
+    let mut cached = INDEX.lock().map_err(|e| e.to_string())?;
+    if let Some((cached_version, index)) = cached.as_ref() {
+        if *cached_version == version {
+            return Ok(index.clone());
+        }
+    }
+    let index = Arc::new(SchemaIndex::build(load_schema_store()?.servers));
+    *cached = Some((version, index.clone()));
+    Ok(index)
+}
+
+pub fn search_schema_store(query: &SchemaSearchQuery) -> Result<SchemaSearchPage, String> {
+    Ok(schema_index()?.search(query))
+}
@@ -0,0 +1,143 @@
+//! Values sealed at rest with a machine-local key
+//!
+//! Sealed values are `enc:v1:<base64 nonce + ciphertext>`, encrypted with AES-256-GCM
+//! under a random key kept in a 0600 file in the local data directory. Each kind of
+//! value uses its own associated data, so a value sealed for one purpose cannot be
+//! opened as another. Unlike the vault, no passphrase is involved: sealing protects
+//! files that may be synced or shared, not against the local user.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use zeroize::Zeroizing;
+
+use crate::config::get_machine_key_path;
+
+const SEALED_PREFIX: &str = "enc:v1:";
+const KEY_LEN: usize = 32;
+
+/// How long a reader waits for a concurrent first use to finish writing the key file
+const KEY_WRITE_RETRIES: u32 = 50;
+const KEY_WRITE_DELAY: Duration = Duration::from_millis(10);
+
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_PREFIX)
+}
+
+fn random_bytes(len: usize) -> Result<Zeroizing<Vec<u8>>, String> {
+    let mut bytes = Zeroizing::new(vec![0u8; len]);
+    SystemRandom::new().fill(&mut bytes).map_err(|_| "Failed to generate random bytes")?;
+    Ok(bytes)
+}
+
+/// The machine-local key, created on first use
+///
+/// The key file is created exclusively, so concurrent first uses agree on one key: whoever
+/// loses the race reads the winner's file instead of writing its own.
+fn machine_key() -> Result<LessSafeKey, String> {
+    let path = get_machine_key_path().ok_or("Could not determine machine key path")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let key = match options.open(&path) {
+        Ok(mut file) => {
+            let key = random_bytes(KEY_LEN)?;
+            file.write_all(STANDARD.encode(&*key).as_bytes()).map_err(|e| {
+                // An empty key file would block every later use
+                let _ = fs::remove_file(&path);
+                e.to_string()
+            })?;
+            key
+        }
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => read_machine_key(&path)?,
+        Err(e) => return Err(e.to_string()),
+    };
+
+    UnboundKey::new(&AES_256_GCM, &key)
+        .map(LessSafeKey::new)
+        .map_err(|_| "Invalid machine key file".to_string())
+}
+
+/// Read an existing key file, waiting briefly while its creator is still writing it
+fn read_machine_key(path: &Path) -> Result<Zeroizing<Vec<u8>>, String> {
+    for _ in 0..KEY_WRITE_RETRIES {
+        let content = Zeroizing::new(fs::read_to_string(path).map_err(|e| e.to_string())?);
+        if !content.trim().is_empty() {
+            return STANDARD
+                .decode(content.trim())
+                .map(Zeroizing::new)
+                .map_err(|e| format!("Invalid machine key file: {}", e));
+        }
+        thread::sleep(KEY_WRITE_DELAY);
+    }
+    Err("Machine key file is empty".to_string())
+}
+
+/// The machine key, read once for sealing or opening many values
+pub struct SealingKey(LessSafeKey);
+
+impl SealingKey {
+    pub fn load() -> Result<Self, String> {
+        machine_key().map(SealingKey)
+    }
+
+    /// Encrypt `plaintext`, binding it to `aad`
+    pub fn seal(&self, plaintext: &str, aad: &'static [u8]) -> Result<String, String> {
+        let nonce_bytes = random_bytes(NONCE_LEN)?;
+        let nonce = Nonce::try_assume_unique_for_key(&nonce_bytes).map_err(|_| "Invalid nonce")?;
+        let mut data = plaintext.as_bytes().to_vec();
+        self.0
+            .seal_in_place_append_tag(nonce, Aad::from(aad), &mut data)
+            .map_err(|_| "Failed to encrypt value")?;
+
+        let mut sealed = nonce_bytes.to_vec();
+        sealed.extend_from_slice(&data);
+        Ok(format!("{}{}", SEALED_PREFIX, STANDARD.encode(sealed)))
+    }
+
+    /// Decrypt a value sealed with the same `aad`
+    ///
+    /// Fails when the value was sealed on another machine (or its key file was replaced).
+    pub fn open(&self, sealed: &str, aad: &'static [u8]) -> Result<Zeroizing<String>, String> {
+        let encoded = sealed.strip_prefix(SEALED_PREFIX).ok_or("Value is not sealed")?;
+        let mut data =
+            STANDARD.decode(encoded).map_err(|e| format!("Invalid sealed value: {}", e))?;
+        if data.len() < NONCE_LEN {
+            return Err("Invalid sealed value".to_string());
+        }
+        let mut ciphertext = data.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&data).map_err(|_| "Invalid sealed value")?;
+        let plaintext = self
+            .0
+            .open_in_place(nonce, Aad::from(aad), &mut ciphertext)
+            .map_err(|_| "Value was sealed on another machine".to_string())?;
+        String::from_utf8(plaintext.to_vec())
+            .map(Zeroizing::new)
+            .map_err(|_| "Invalid sealed value".to_string())
+    }
+}
+
+/// Encrypt `plaintext`, binding it to `aad`
+pub fn seal(plaintext: &str, aad: &'static [u8]) -> Result<String, String> {
+    SealingKey::load()?.seal(plaintext, aad)
+}
+
+/// Decrypt a value sealed with the same `aad`
+pub fn open(sealed: &str, aad: &'static [u8]) -> Result<Zeroizing<String>, String> {
+    SealingKey::load()?.open(sealed, aad)
+}
@@ -0,0 +1,300 @@
+//! Hardcoded credentials in agent configs
+//!
+//! The scan walks every agent config and reports env values and headers that look like
+//! secrets. Migration moves a value into the rainMcp-managed `secrets.env` file and
+//! rewrites the entry to the agent's env reference syntax (e.g. `${GITHUB_TOKEN}`), so
+//! the agent keeps working once the file is sourced by the shell it starts from. Agents
+//! launched as GUI apps never see that file and are left alone. The rewrite is not
+//! journaled, since the journal would otherwise keep the plaintext values.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use log::warn;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::get_secrets_env_path;
+
+use super::agent_config::read_agent_config;
+use super::agent_definition::get_agent_definition;
+use super::env_file::parse_env_file;
+use super::redact::{is_secret_value, mask_value, RegistrySecrets, AUTH_SCHEMES};
+use super::transaction::{AgentConfigTransaction, AgentWriteOutcome};
+use super::vault::write_private_file;
+use super::{get_all_agent_types, AgentServerEntry, AgentType};
+
+/// A credential stored in plain text in an agent config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaintextSecret {
+    pub agent_type: AgentType,
+    pub server_name: String,
+
+    /// `env` or `headers`
+    pub field: String,
+    pub key: String,
+
+    /// Masked value, for display
+    pub preview: String,
+
+    /// Whether the agent can read the value from `secrets.env`, i.e. the secret can be migrated
+    pub migratable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MigratedSecret {
+    pub agent_type: AgentType,
+    pub server_name: String,
+    pub key: String,
+
+    /// Variable in `secrets.env` now holding the value
+    pub env_var: String,
+
+    /// New value of the config entry
+    pub reference: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedSecret {
+    pub agent_type: AgentType,
+    pub server_name: String,
+    pub key: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretMigrationReport {
+    /// The managed env file; it must be sourced in the shell profile
+    pub env_file: PathBuf,
+    pub migrated: Vec<MigratedSecret>,
+    pub skipped: Vec<SkippedSecret>,
+    pub outcomes: Vec<AgentWriteOutcome>,
+}
+
+/// Env values of a local server or header values of a remote one, with the field name
+fn secret_fields(config: &AgentServerEntry) -> (&'static str, Vec<(&String, &String)>) {
+    match config {
+        AgentServerEntry::Local(local) => ("env", local.env.iter().flatten().collect()),
+        AgentServerEntry::Remote(remote) => ("headers", remote.headers.iter().flatten().collect()),
+    }
+}
+
+/// Hardcoded credentials in every readable agent config, sorted by agent, server and key
+pub fn scan_plaintext_secrets() -> Vec<PlaintextSecret> {
+    let registry_secrets = RegistrySecrets::load();
+    let mut findings = Vec::new();
+    for agent in get_all_agent_types() {
+        let Ok(definition) = get_agent_definition(&agent) else {
+            continue;
+        };
+        if !definition.config_path().is_ok_and(|p| p.exists()) {
+            continue;
+        }
+        let config = match read_agent_config(agent.clone()) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Skipping secret scan for {}: {}", agent, e);
+                continue;
+            }
+        };
+
+        let mut servers: Vec<_> = config.servers.iter().collect();
+        servers.sort_by(|a, b| a.0.cmp(b.0));
+        for (server_name, entry) in servers {
+            let registry_secrets = registry_secrets.for_server(server_name);
+            let (field, mut values) = secret_fields(entry);
+            values.sort();
+            for (key, value) in values {
+                if !is_secret_value(key, value, registry_secrets) {
+                    continue;
+                }
+                findings.push(PlaintextSecret {
+                    agent_type: agent.clone(),
+                    server_name: server_name.clone(),
+                    field: field.to_string(),
+                    key: key.clone(),
+                    preview: mask_value(value),
+                    migratable: definition.shell_env_reference().is_some(),
+                });
+            }
+        }
+    }
+    findings
+}
+
+/// `GITHUB_TOKEN` from `GITHUB_TOKEN`, `JIRA_AUTHORIZATION` from `jira` / `Authorization`
+fn env_var_base(server_name: &str, field: &str, key: &str) -> String {
+    let raw = if field == "env" { key.to_string() } else { format!("{}_{}", server_name, key) };
+    let name: String = raw
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+/// Pick a variable name for `value`, reusing one that already holds it
+///
+/// `base` is tried first, then `with_server` (the name prefixed with the server) and
+/// numbered variants of it.
+fn allocate_env_var(
+    env: &BTreeMap<String, String>,
+    base: &str,
+    with_server: &str,
+    value: &str,
+) -> String {
+    [base.to_string(), with_server.to_string()]
+        .into_iter()
+        .chain((2..).map(|n| format!("{}_{}", with_server, n)))
+        .find(|name| env.get(name).is_none_or(|existing| existing == value))
+        .expect("candidate names are unbounded")
+}
+
+/// Move the given secrets into `secrets.env` and point the agent configs at them
+///
+/// Values are re-read from the configs; entries that changed since the scan or belong
+/// to agents that cannot read `secrets.env` are reported as skipped.
+pub fn migrate_plaintext_secrets(
+    secrets: &[PlaintextSecret],
+) -> Result<SecretMigrationReport, String> {
+    let env_file = get_secrets_env_path().ok_or("Could not determine secrets env path")?;
+    let mut env = load_managed_env(&env_file)?;
+    let mut migrated = Vec::new();
+    let mut skipped = Vec::new();
+    let mut transaction = AgentConfigTransaction::new().without_journal();
+    let registry_secrets = RegistrySecrets::load();
+
+    let mut by_agent: Vec<(AgentType, Vec<&PlaintextSecret>)> = Vec::new();
+    for secret in secrets {
+        match by_agent.iter_mut().find(|(agent, _)| *agent == secret.agent_type) {
+            Some((_, list)) => list.push(secret),
+            None => by_agent.push((secret.agent_type.clone(), vec![secret])),
+        }
+    }
+
+    for (agent, agent_secrets) in by_agent {
+        let skip = |secret: &PlaintextSecret, reason: String| SkippedSecret {
+            agent_type: agent.clone(),
+            server_name: secret.server_name.clone(),
+            key: secret.key.clone(),
+            reason,
+        };
+
+        let definition = get_agent_definition(&agent);
+        let template = match definition.as_ref().map(|d| d.shell_env_reference()) {
+            Ok(Some(template)) => template.to_string(),
+            Ok(None) => {
+                let reason = if definition.is_ok_and(|d| d.env_reference.is_some()) {
+                    format!("{} is not started from a shell, so it cannot read secrets.env", agent)
+                } else {
+                    format!("{} cannot reference environment variables", agent)
+                };
+                skipped.extend(agent_secrets.iter().map(|s| skip(s, reason.clone())));
+                continue;
+            }
+            Err(e) => {
+                skipped.extend(agent_secrets.iter().map(|s| skip(s, e.clone())));
+                continue;
+            }
+        };
+        let mut config = match read_agent_config(agent.clone()) {
+            Ok(config) => config,
+            Err(e) => {
+                skipped.extend(agent_secrets.iter().map(|s| skip(s, e.clone())));
+                continue;
+            }
+        };
+
+        let mut changed = false;
+        for secret in agent_secrets {
+            let registry_secrets = registry_secrets.for_server(&secret.server_name);
+            let value = config.servers.get_mut(&secret.server_name).and_then(|entry| match entry {
+                AgentServerEntry::Local(local) if secret.field == "env" => {
+                    local.env.as_mut()?.get_mut(&secret.key)
+                }
+                AgentServerEntry::Remote(remote) if secret.field == "headers" => {
+                    remote.headers.as_mut()?.get_mut(&secret.key)
+                }
+                _ => None,
+            });
+            let Some(value) = value else {
+                skipped.push(skip(secret, "Entry no longer exists".to_string()));
+                continue;
+            };
+            if !is_secret_value(&secret.key, value, registry_secrets) {
+                skipped.push(skip(secret, "Value is no longer a plaintext secret".to_string()));
+                continue;
+            }
+
+            let scheme = AUTH_SCHEMES.iter().find(|s| value.starts_with(*s)).copied().unwrap_or("");
+            let secret_value = value[scheme.len()..].to_string();
+            let base = env_var_base(&secret.server_name, &secret.field, &secret.key);
+            let with_server = env_var_base(&secret.server_name, "headers", &secret.key);
+            let env_var = allocate_env_var(&env, &base, &with_server, &secret_value);
+            let reference = format!("{}{}", scheme, template.replace("VAR", &env_var));
+
+            env.insert(env_var.clone(), secret_value);
+            *value = reference.clone();
+            changed = true;
+            migrated.push(MigratedSecret {
+                agent_type: agent.clone(),
+                server_name: secret.server_name.clone(),
+                key: secret.key.clone(),
+                env_var,
+                reference,
+            });
+        }
+
+        if changed {
+            transaction.stage(agent.clone(), config)?;
+        }
+    }
+
+    // Values are saved before any config stops containing them
+    if !migrated.is_empty() {
+        write_private_file(&env_file, &render_managed_env(&env))?;
+    }
+    let outcomes = transaction.commit_with_report();
+
+    Ok(SecretMigrationReport { env_file, migrated, skipped, outcomes })
+}
+
+/// Variables in the managed env file
+pub fn load_managed_env(path: &std::path::Path) -> Result<BTreeMap<String, String>, String> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Ok(parse_env_file(&content)?.into_iter().collect())
+}
+
+/// Add or update variables in the managed env file, keeping the others
+pub fn export_managed_env(vars: &BTreeMap<String, String>) -> Result<(), String> {
+    let env_file = get_secrets_env_path().ok_or("Could not determine secrets env path")?;
+    let mut env = load_managed_env(&env_file)?;
+    let before = env.clone();
+    env.extend(vars.iter().map(|(name, value)| (name.clone(), value.clone())));
+    if env != before {
+        write_private_file(&env_file, &render_managed_env(&env))?;
+    }
+    Ok(())
+}
+
+fn render_managed_env(env: &BTreeMap<String, String>) -> String {
+    let mut content = String::from(
+        "# Managed by rainMcp: secrets referenced from agent configs.\n\
+         # Source this file from your shell profile so agents can read them.\n",
+    );
+    for (name, value) in env {
+        content.push_str(&format!("export {}='{}'\n", name, value.replace('\'', r"'\''")));
+    }
+    content
+}
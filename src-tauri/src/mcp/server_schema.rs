@@ -8,6 +8,7 @@
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 /// Main server schema structure from MCP Registry
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -48,6 +49,39 @@ pub struct ServerSchema {
     /// Remote transport configurations
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub remotes: Vec<RemoteTransport>,
+
+    /// URL of the registry source the schema was fetched from (set by rainMcp)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry_source: Option<String>,
+
+    /// Registry status of this version when it is not active, e.g. `deprecated` (set by
+    /// rainMcp)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry_status: Option<String>,
+}
+
+impl ServerSchema {
+    /// Names of env vars and headers marked `isSecret` in any package or remote
+    pub fn secret_input_names(&self) -> HashSet<String> {
+        let package_inputs = self.packages.iter().flat_map(|p| {
+            let headers = match &p.transport {
+                LocalTransport::Stdio => &[][..],
+                LocalTransport::Sse { headers, .. }
+                | LocalTransport::StreamableHttp { headers, .. } => &headers[..],
+            };
+            p.environment_variables.iter().chain(headers.iter())
+        });
+        let remote_inputs = self.remotes.iter().flat_map(|r| match r {
+            RemoteTransport::Sse { headers, .. }
+            | RemoteTransport::StreamableHttp { headers, .. } => headers.iter(),
+        });
+
+        package_inputs
+            .chain(remote_inputs)
+            .filter(|input| input.is_secret)
+            .map(|input| input.name.clone())
+            .collect()
+    }
 }
 
 /// Repository metadata
@@ -0,0 +1,542 @@
+//! Newer registry versions of user servers
+//!
+//! A registry-origin server records the version its config was generated from in
+//! `ServerOrigin.version`. After a refresh, every server whose schema has a newer latest
+//! version is reported together with what changed between the two versions, and
+//! upgrading regenerates the config from the new version while keeping the user's env
+//! and header values and any arguments they appended.
+
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{load_app_config, save_app_config};
+
+use super::journal;
+use super::registry::{is_version_newer, load_schema_store, SchemaStore};
+use super::server_schema::{
+    Argument, EnvironmentVariable, LocalTransport, Package, RemoteTransport, ServerSchema,
+};
+use super::user_server::{OriginType, ServerOrigin, UserServer};
+use super::{AgentServerEntry, BaseServerEntry, LocalServerEntry, RemoteServerEntry};
+
+/// An argument added, removed or changed between two versions
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ArgumentChange {
+    /// Flag name for named arguments, value hint or position for positional ones
+    pub key: String,
+
+    /// Whether the argument is passed to the runtime rather than the package
+    pub runtime: bool,
+    pub before: Option<Argument>,
+    pub after: Option<Argument>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerUpdateDiff {
+    /// Required env vars (headers for remote servers) the new version adds, without a
+    /// default; the user has to provide them before upgrading
+    pub new_required_env_vars: Vec<String>,
+
+    /// Env vars or headers the new version no longer declares
+    pub removed_env_vars: Vec<String>,
+    pub changed_arguments: Vec<ArgumentChange>,
+
+    /// URLs of remote endpoints the new version adds
+    pub added_remotes: Vec<String>,
+}
+
+/// A user server with a newer registry version available
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerUpdate {
+    pub server_id: String,
+    pub server_name: String,
+    pub schema_name: String,
+    pub installed_version: String,
+    pub latest_version: String,
+
+    /// None when the installed version left the history or its package can't be matched,
+    /// so what the upgrade changes is unknown
+    pub diff: Option<ServerUpdateDiff>,
+}
+
+/// The package or remote of a schema a server config runs
+enum Target<'a> {
+    Package(&'a Package),
+    Remote(&'a RemoteTransport),
+}
+
+fn package_id(package: &Package) -> String {
+    format!("{}:{}", package.registry_type, package.identifier)
+}
+
+fn remote_url(remote: &RemoteTransport) -> &str {
+    match remote {
+        RemoteTransport::Sse { url, .. } | RemoteTransport::StreamableHttp { url, .. } => url,
+    }
+}
+
+/// Which package or remote of `schema` the server uses
+fn resolve_target<'a>(
+    schema: &'a ServerSchema,
+    origin: &ServerOrigin,
+    config: &AgentServerEntry,
+) -> Option<Target<'a>> {
+    if let Some(id) = &origin.package_id {
+        return schema.packages.iter().find(|p| package_id(p) == *id).map(Target::Package);
+    }
+    match config {
+        AgentServerEntry::Local(local) => {
+            let args = local.args.as_deref().unwrap_or_default();
+            let runs = |p: &&Package| {
+                args.iter()
+                    .any(|a| a == &p.identifier || a.starts_with(&format!("{}@", p.identifier)))
+            };
+            schema
+                .packages
+                .iter()
+                .find(runs)
+                .or_else(|| schema.packages.first().filter(|_| schema.packages.len() == 1))
+                .map(Target::Package)
+        }
+        AgentServerEntry::Remote(remote) => schema
+            .remotes
+            .iter()
+            .find(|r| remote_url(r) == remote.url)
+            .map(Target::Remote)
+            .or_else(|| {
+                schema
+                    .packages
+                    .iter()
+                    .find(|p| match &p.transport {
+                        LocalTransport::Stdio => false,
+                        LocalTransport::Sse { url, .. }
+                        | LocalTransport::StreamableHttp { url, .. } => *url == remote.url,
+                    })
+                    .map(Target::Package)
+            })
+            .or_else(|| schema.remotes.first().map(Target::Remote)),
+    }
+}
+
+/// The same package or remote in another version of the schema
+fn matching_target<'a>(schema: &'a ServerSchema, target: &Target) -> Option<Target<'a>> {
+    match target {
+        Target::Package(package) => schema
+            .packages
+            .iter()
+            .find(|p| package_id(p) == package_id(package))
+            .or_else(|| schema.packages.first().filter(|_| schema.packages.len() == 1))
+            .map(Target::Package),
+        Target::Remote(remote) => schema
+            .remotes
+            .iter()
+            .find(|r| remote_url(r) == remote_url(remote))
+            .or_else(|| schema.remotes.first())
+            .map(Target::Remote),
+    }
+}
+
+/// Env vars of a package, plus headers for packages served over HTTP; headers of a remote
+fn inputs<'a>(target: &Target<'a>) -> Vec<&'a EnvironmentVariable> {
+    match target {
+        Target::Package(package) => {
+            let headers = match &package.transport {
+                LocalTransport::Stdio => &[][..],
+                LocalTransport::Sse { headers, .. }
+                | LocalTransport::StreamableHttp { headers, .. } => &headers[..],
+            };
+            package.environment_variables.iter().chain(headers).collect()
+        }
+        Target::Remote(
+            RemoteTransport::Sse { headers, .. } | RemoteTransport::StreamableHttp { headers, .. },
+        ) => headers.iter().collect(),
+    }
+}
+
+/// Arguments keyed by flag name, value hint or position
+fn keyed_arguments<'a>(target: &Target<'a>) -> Vec<(String, bool, &'a Argument)> {
+    let Target::Package(package) = target else {
+        return Vec::new();
+    };
+    let key = |index: usize, argument: &Argument| {
+        argument
+            .name
+            .clone()
+            .or_else(|| argument.value_hint.clone())
+            .unwrap_or_else(|| format!("#{}", index + 1))
+    };
+    let package_args =
+        package.package_arguments.iter().enumerate().map(|(i, a)| (key(i, a), false, a));
+    let runtime_args =
+        package.runtime_arguments.iter().enumerate().map(|(i, a)| (key(i, a), true, a));
+    package_args.chain(runtime_args).collect()
+}
+
+fn diff_targets(
+    installed: &Target,
+    latest: &Target,
+    installed_schema: &ServerSchema,
+    latest_schema: &ServerSchema,
+) -> ServerUpdateDiff {
+    let installed_inputs = inputs(installed);
+    let latest_inputs = inputs(latest);
+
+    let new_required_env_vars = latest_inputs
+        .iter()
+        .filter(|v| v.is_required && v.value.is_none() && v.default.is_none())
+        .filter(|v| !installed_inputs.iter().any(|i| i.name == v.name && i.is_required))
+        .map(|v| v.name.clone())
+        .collect();
+    let removed_env_vars = installed_inputs
+        .iter()
+        .filter(|i| !latest_inputs.iter().any(|v| v.name == i.name))
+        .map(|i| i.name.clone())
+        .collect();
+
+    let before = keyed_arguments(installed);
+    let after = keyed_arguments(latest);
+    let as_json = |a: &Argument| serde_json::to_value(a).ok();
+    let mut changed_arguments: Vec<ArgumentChange> = after
+        .iter()
+        .filter_map(|(key, runtime, argument)| {
+            let previous = before.iter().find(|(k, r, _)| k == key && r == runtime).map(|b| b.2);
+            if previous.is_some_and(|p| as_json(p) == as_json(argument)) {
+                return None;
+            }
+            Some(ArgumentChange {
+                key: key.clone(),
+                runtime: *runtime,
+                before: previous.cloned(),
+                after: Some((*argument).clone()),
+            })
+        })
+        .collect();
+    changed_arguments.extend(
+        before
+            .iter()
+            .filter(|(key, runtime, _)| !after.iter().any(|(k, r, _)| k == key && r == runtime))
+            .map(|(key, runtime, argument)| ArgumentChange {
+                key: key.clone(),
+                runtime: *runtime,
+                before: Some((*argument).clone()),
+                after: None,
+            }),
+    );
+
+    let added_remotes = latest_schema
+        .remotes
+        .iter()
+        .map(remote_url)
+        .filter(|url| !installed_schema.remotes.iter().any(|r| remote_url(r) == *url))
+        .map(str::to_string)
+        .collect();
+
+    ServerUpdateDiff { new_required_env_vars, removed_env_vars, changed_arguments, added_remotes }
+}
+
+/// Registry origin of a server, if it has a schema name
+fn registry_origin(server: &UserServer) -> Option<&ServerOrigin> {
+    server
+        .origin
+        .as_ref()
+        .filter(|o| matches!(o.origin_type, OriginType::Registry) && o.schema_name.is_some())
+}
+
+/// Servers with a newer latest version than the one they are pinned to
+pub fn compute_server_updates(servers: &[UserServer], store: &SchemaStore) -> Vec<ServerUpdate> {
+    let mut updates = Vec::new();
+    for server in servers {
+        let Some(origin) = registry_origin(server) else {
+            continue;
+        };
+        let (Some(schema_name), Some(installed_version)) = (&origin.schema_name, &origin.version)
+        else {
+            continue;
+        };
+        let Some(latest) = store.servers.iter().find(|s| s.name == *schema_name) else {
+            continue;
+        };
+        if !is_version_newer(&latest.version, installed_version) {
+            continue;
+        }
+
+        let installed =
+            store.versions(schema_name).into_iter().find(|s| s.version == *installed_version);
+        let diff = installed.as_ref().and_then(|installed| {
+            let installed_target = resolve_target(installed, origin, &server.config)?;
+            let latest_target = matching_target(latest, &installed_target)?;
+            Some(diff_targets(&installed_target, &latest_target, installed, latest))
+        });
+
+        updates.push(ServerUpdate {
+            server_id: server.id.clone(),
+            server_name: server.name.clone(),
+            schema_name: schema_name.clone(),
+            installed_version: installed_version.clone(),
+            latest_version: latest.version.clone(),
+            diff,
+        });
+    }
+    updates
+}
+
+/// Record the version of registry servers added before versions were tracked
+///
+/// Their config was generated from what was the latest version in `previous`, the store
+/// before the refresh.
+pub fn pin_installed_versions(previous: &SchemaStore) -> Result<(), String> {
+    let mut app_config = load_app_config();
+    let mut changes = Vec::new();
+    for server in &mut app_config.user_servers {
+        let Some(schema_name) = registry_origin(server)
+            .filter(|o| o.version.is_none())
+            .and_then(|o| o.schema_name.clone())
+        else {
+            continue;
+        };
+        let Some(latest) = previous.servers.iter().find(|s| s.name == schema_name) else {
+            continue;
+        };
+        let before = server.clone();
+        if let Some(origin) = server.origin.as_mut() {
+            origin.version = Some(latest.version.clone());
+        }
+        changes.push(journal::JournalChange::user_server(
+            &server.id,
+            Some(before),
+            Some(server.clone()),
+        ));
+    }
+
+    if !changes.is_empty() {
+        save_app_config(&app_config)?;
+        journal::record("Record installed registry versions", changes);
+    }
+    Ok(())
+}
+
+/// Pin servers without a version against `previous`, then compute updates against `store`
+pub fn check_server_updates(
+    previous: &SchemaStore,
+    store: &SchemaStore,
+) -> Result<Vec<ServerUpdate>, String> {
+    pin_installed_versions(previous)?;
+    Ok(compute_server_updates(&load_app_config().user_servers, store))
+}
+
+/// Value for a regenerated env var or header: the user's, else the registry's
+fn input_value(input: &EnvironmentVariable, existing: &HashMap<String, String>) -> String {
+    existing
+        .get(&input.name)
+        .or(input.value.as_ref())
+        .or(input.default.as_ref())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Config for running a registry package, used by both the add-server form and upgrades
+///
+/// `values` become the env of a stdio package or the headers of one served over HTTP.
+/// Runtime arguments go before the package identifier, package arguments after it.
+pub fn package_config(
+    package: &Package,
+    base: BaseServerEntry,
+    values: HashMap<String, String>,
+) -> AgentServerEntry {
+    let values = if values.is_empty() { None } else { Some(values) };
+    match &package.transport {
+        LocalTransport::Sse { url, .. } | LocalTransport::StreamableHttp { url, .. } => {
+            return AgentServerEntry::Remote(RemoteServerEntry {
+                base,
+                url: url.clone(),
+                headers: values,
+            });
+        }
+        LocalTransport::Stdio => {}
+    }
+
+    let (command, mut args): (&str, Vec<&str>) =
+        match (package.runtime_hint.as_deref(), package.registry_type.as_str()) {
+            (Some("npx"), _) | (None, "npm") => ("npx", vec!["-y"]),
+            (Some("uvx"), _) | (None, "pypi") => ("uvx", vec![]),
+            (Some("docker"), _) | (None, "oci") => ("docker", vec!["run", "-i"]),
+            (Some("dnx"), _) => ("dnx", vec![]),
+            _ => ("npx", vec!["-y"]),
+        };
+    fn push_arguments<'a>(args: &mut Vec<&'a str>, arguments: &'a [Argument]) {
+        for argument in arguments {
+            if let (true, Some(name)) = (argument.arg_type == "named", &argument.name) {
+                args.push(name);
+            }
+            if let Some(value) = &argument.value {
+                args.push(value);
+            }
+        }
+    }
+    push_arguments(&mut args, &package.runtime_arguments);
+    let identifier = match &package.version {
+        Some(version) => format!("{}@{}", package.identifier, version),
+        None => package.identifier.clone(),
+    };
+    args.push(&identifier);
+    push_arguments(&mut args, &package.package_arguments);
+
+    AgentServerEntry::Local(LocalServerEntry {
+        base,
+        command: command.to_string(),
+        args: Some(args.into_iter().map(str::to_string).collect()),
+        env: values,
+    })
+}
+
+fn config_args(config: &AgentServerEntry) -> &[String] {
+    match config {
+        AgentServerEntry::Local(local) => local.args.as_deref().unwrap_or_default(),
+        AgentServerEntry::Remote(_) => &[],
+    }
+}
+
+/// Arguments generated for `package`: the current layout, then the one the add-server
+/// form used before runtime arguments were included
+fn generated_arguments(package: &Package, base: BaseServerEntry) -> Vec<Vec<String>> {
+    let mut layouts =
+        vec![config_args(&package_config(package, base.clone(), HashMap::new())).to_vec()];
+    if !package.runtime_arguments.is_empty() {
+        let legacy = Package { runtime_arguments: Vec::new(), ..package.clone() };
+        layouts.push(config_args(&package_config(&legacy, base, HashMap::new())).to_vec());
+    }
+    layouts
+}
+
+/// Arguments the user appended to the ones generated from `installed`
+///
+/// Fails when generated arguments were edited, since regenerating would drop the edits.
+fn added_arguments(server: &UserServer, installed: Option<&Target>) -> Result<Vec<String>, String> {
+    let args = config_args(&server.config);
+    let layouts = match installed {
+        Some(Target::Package(package)) => generated_arguments(package, server_base(server)),
+        Some(Target::Remote(_)) => vec![Vec::new()],
+        None if args.is_empty() => vec![Vec::new()],
+        None => {
+            return Err(format!(
+                "The installed version of '{}' is no longer known, so its arguments cannot be \
+                 carried over; re-add it from the registry instead",
+                server.name
+            ))
+        }
+    };
+    layouts
+        .iter()
+        .find_map(|generated| args.strip_prefix(&generated[..]))
+        .map(<[String]>::to_vec)
+        .ok_or_else(|| {
+            format!(
+                "Arguments of '{}' were edited by hand and would be lost; revert them or \
+                 re-add the server from the registry instead",
+                server.name
+            )
+        })
+}
+
+fn server_base(server: &UserServer) -> BaseServerEntry {
+    match &server.config {
+        AgentServerEntry::Local(local) => local.base.clone(),
+        AgentServerEntry::Remote(remote) => remote.base.clone(),
+    }
+}
+
+/// `server` regenerated from `version` of its schema (the latest when `None`)
+///
+/// Env and header values the user set are kept, inputs the new version adds get their
+/// registry value or default, and entries the old version declared but the new one
+/// drops are removed. Entries the user added by hand are kept, and so are arguments the
+/// user appended; an upgrade is refused when generated arguments were edited.
+pub fn upgraded_server(
+    server: &UserServer,
+    store: &SchemaStore,
+    version: Option<&str>,
+) -> Result<UserServer, String> {
+    let origin = registry_origin(server)
+        .ok_or_else(|| format!("Server '{}' was not added from the registry", server.name))?;
+    let schema_name = origin.schema_name.as_deref().unwrap_or_default();
+    let versions = store.versions(schema_name);
+    let target_schema = match version {
+        Some(version) => versions.iter().find(|s| s.version == version),
+        None => versions.first(),
+    }
+    .ok_or_else(|| match version {
+        Some(version) => {
+            format!("Version {} of '{}' not found in schema store", version, schema_name)
+        }
+        None => format!("Server '{}' not found in schema store", schema_name),
+    })?;
+
+    let installed_schema = store.schema_for_origin(origin);
+    let installed_target =
+        installed_schema.and_then(|schema| resolve_target(schema, origin, &server.config));
+    let target = match &installed_target {
+        Some(installed) => matching_target(target_schema, installed),
+        None => resolve_target(target_schema, origin, &server.config),
+    }
+    .ok_or_else(|| {
+        format!(
+            "Version {} of '{}' has no matching package or remote",
+            target_schema.version, schema_name
+        )
+    })?;
+
+    let added_args = added_arguments(server, installed_target.as_ref())?;
+    let base = server_base(server);
+    let existing = match &server.config {
+        AgentServerEntry::Local(local) => local.env.clone().unwrap_or_default(),
+        AgentServerEntry::Remote(remote) => remote.headers.clone().unwrap_or_default(),
+    };
+    let installed_inputs: Vec<&EnvironmentVariable> =
+        installed_target.as_ref().map(inputs).unwrap_or_default();
+    let target_inputs = inputs(&target);
+    let mut values: HashMap<String, String> = existing
+        .iter()
+        .filter(|(key, _)| !installed_inputs.iter().any(|i| i.name == **key))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    for input in &target_inputs {
+        values.insert(input.name.clone(), input_value(input, &existing));
+    }
+
+    let (mut config, package_id) = match target {
+        Target::Package(package) => {
+            (package_config(package, base, values), Some(package_id(package)))
+        }
+        Target::Remote(remote) => (
+            AgentServerEntry::Remote(RemoteServerEntry {
+                base,
+                url: remote_url(remote).to_string(),
+                headers: if values.is_empty() { None } else { Some(values) },
+            }),
+            None,
+        ),
+    };
+
+    if let AgentServerEntry::Local(local) = &mut config {
+        local.args.get_or_insert_with(Vec::new).extend(added_args);
+    }
+
+    let mut upgraded = server.clone();
+    upgraded.config = config;
+    upgraded.origin = Some(ServerOrigin {
+        package_id,
+        version: Some(target_schema.version.clone()),
+        ..origin.clone()
+    });
+    Ok(upgraded)
+}
+
+/// Updates for the current schema store
+pub fn server_updates() -> Result<Vec<ServerUpdate>, String> {
+    Ok(compute_server_updates(&load_app_config().user_servers, &load_schema_store()?))
+}
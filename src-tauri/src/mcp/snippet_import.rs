@@ -0,0 +1,494 @@
+//! Import servers from pasted config snippets
+//!
+//! READMEs show server configs in many shapes. A pasted snippet is classified as an
+//! `mcpServers` (or VS Code `servers`) JSON block, an OpenCode `mcp` block, Codex
+//! `[mcp_servers.x]` TOML, a bare or named server entry, a URL or a shell command, and
+//! parsed with the matching agent parser. Each candidate carries a confidence note so the
+//! UI can ask for review before anything is added to the library.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::agent_adapters::common::parse_server_entry;
+use super::agent_adapters::opencode_adapter::{strip_jsonc_comments, OpenCodeAdapter};
+use super::agent_adapters::toml_adapter::parse_toml_server_entry;
+use super::user_server::{OriginType, ServerOrigin, UserServer};
+use super::{AgentServerEntry, BaseServerEntry, LocalServerEntry, RemoteServerEntry};
+
+/// Commands commonly used to launch MCP servers
+const LAUNCHERS: &[&str] = &[
+    "npx", "bunx", "pnpx", "pnpm", "yarn", "uvx", "pipx", "uv", "node", "bun", "deno", "python",
+    "python3", "docker", "podman",
+];
+
+/// Launcher sub-commands and wrappers that precede the package name
+const LAUNCHER_SUBCOMMANDS: &[&str] = &["run", "dlx", "exec", "x", "tool", "-m"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SnippetFormat {
+    /// `{"mcpServers": {...}}` as used by Claude Desktop, Claude Code, Cursor, ...
+    McpServersJson,
+    /// `{"servers": {...}}` as used by VS Code
+    VsCodeJson,
+    /// `{"mcp": {...}}` as used by OpenCode
+    OpenCodeJson,
+    /// `[mcp_servers.<name>]` tables as used by Codex
+    CodexToml,
+    /// `{"<name>": {...}}` without a wrapper key
+    NamedEntries,
+    /// A single server object without a name
+    BareEntry,
+    Url,
+    ShellCommand,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Confidence {
+    High,
+    Medium,
+    Low,
+}
+
+/// A server proposed from a snippet; nothing is saved until the user confirms it
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetCandidate {
+    pub server: UserServer,
+    pub format: SnippetFormat,
+    pub confidence: Confidence,
+
+    /// Why the snippet was read this way and what to double-check
+    pub note: String,
+}
+
+impl SnippetFormat {
+    fn describe(self) -> &'static str {
+        match self {
+            SnippetFormat::McpServersJson => "Detected an mcpServers JSON block",
+            SnippetFormat::VsCodeJson => "Detected a VS Code servers JSON block",
+            SnippetFormat::OpenCodeJson => "Detected an OpenCode mcp block",
+            SnippetFormat::CodexToml => "Detected Codex mcp_servers TOML",
+            SnippetFormat::NamedEntries => "Detected named server entries without a wrapper key",
+            SnippetFormat::BareEntry => {
+                "Detected a bare server entry; the name was derived from its command or URL"
+            }
+            SnippetFormat::Url => "Detected a URL; assumed to be a remote server",
+            SnippetFormat::ShellCommand => "Parsed as a shell command",
+        }
+    }
+}
+
+fn candidate(
+    name: String,
+    config: AgentServerEntry,
+    format: SnippetFormat,
+    confidence: Confidence,
+    note: String,
+) -> SnippetCandidate {
+    SnippetCandidate {
+        server: UserServer {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            config,
+            origin: Some(ServerOrigin {
+                origin_type: OriginType::Custom,
+                schema_name: None,
+                package_id: None,
+                version: None,
+            }),
+            created_at: None,
+        },
+        format,
+        confidence,
+        note,
+    }
+}
+
+/// Detect the format of a pasted snippet and propose one user server per entry
+pub fn parse_snippet(snippet: &str) -> Result<Vec<SnippetCandidate>, String> {
+    let text = strip_code_fence(snippet);
+    if text.is_empty() {
+        return Err("Snippet is empty".to_string());
+    }
+
+    let candidates = if let Some(obj) = parse_json_object(&text) {
+        from_json(obj)?
+    } else if let Some(table) = parse_toml_servers(&text) {
+        from_toml(table)?
+    } else {
+        from_shell(&text)?
+    };
+
+    if candidates.is_empty() {
+        return Err("Snippet does not contain any MCP server".to_string());
+    }
+    Ok(candidates)
+}
+
+/// Drop surrounding Markdown code fences (```json ... ```)
+fn strip_code_fence(snippet: &str) -> String {
+    snippet
+        .trim()
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Parse JSON(C), also accepting a `"name": {...}` fragment without outer braces
+fn parse_json_object(text: &str) -> Option<Map<String, Value>> {
+    let cleaned = strip_jsonc_comments(text);
+    let cleaned = cleaned.trim();
+    let parsed = serde_json::from_str::<Value>(cleaned).ok().or_else(|| {
+        if !cleaned.starts_with('"') {
+            return None;
+        }
+        serde_json::from_str(&format!("{{{}}}", cleaned.trim_end_matches(','))).ok()
+    });
+
+    match parsed {
+        Some(Value::Object(obj)) => Some(obj),
+        _ => None,
+    }
+}
+
+/// Parse TOML only when it holds server tables; `KEY='value'` lines are valid TOML too
+fn parse_toml_servers(text: &str) -> Option<toml::Table> {
+    let table = toml::from_str::<toml::Table>(text).ok()?;
+    let has_servers = table.contains_key("mcp_servers")
+        || matches!(serde_json::to_value(&table), Ok(Value::Object(obj)) if has_servers(&obj));
+    has_servers.then_some(table)
+}
+
+fn is_entry(obj: &Map<String, Value>) -> bool {
+    obj.contains_key("command") || obj.contains_key("url") || obj.contains_key("httpUrl")
+}
+
+fn is_named_entries(obj: &Map<String, Value>) -> bool {
+    !obj.is_empty() && obj.values().all(|v| v.as_object().is_some_and(is_entry))
+}
+
+/// Whether `from_json` would find servers in `obj`
+fn has_servers(obj: &Map<String, Value>) -> bool {
+    ["mcpServers", "servers", "mcp"].iter().any(|key| obj.get(*key).is_some_and(Value::is_object))
+        || is_entry(obj)
+        || is_named_entries(obj)
+}
+
+/// OpenCode entries carry `"type": "local" | "remote"`; everything else uses the common shape
+fn parse_any_entry(value: &Value) -> Result<AgentServerEntry, String> {
+    match value.get("type").and_then(Value::as_str) {
+        Some("local") | Some("remote") => OpenCodeAdapter::parse_server_entry(value),
+        _ => parse_server_entry(value),
+    }
+}
+
+fn named_entries(
+    servers: &Map<String, Value>,
+    format: SnippetFormat,
+    confidence: Confidence,
+    parse: fn(&Value) -> Result<AgentServerEntry, String>,
+) -> Result<Vec<SnippetCandidate>, String> {
+    servers
+        .iter()
+        .map(|(name, value)| {
+            let config =
+                parse(value).map_err(|e| format!("Failed to parse server '{}': {}", name, e))?;
+            Ok(candidate(name.clone(), config, format, confidence, format.describe().to_string()))
+        })
+        .collect()
+}
+
+fn from_json(obj: Map<String, Value>) -> Result<Vec<SnippetCandidate>, String> {
+    if let Some(servers) = obj.get("mcpServers").and_then(Value::as_object) {
+        return named_entries(
+            servers,
+            SnippetFormat::McpServersJson,
+            Confidence::High,
+            parse_server_entry,
+        );
+    }
+    if let Some(servers) = obj.get("servers").and_then(Value::as_object) {
+        return named_entries(
+            servers,
+            SnippetFormat::VsCodeJson,
+            Confidence::High,
+            parse_any_entry,
+        );
+    }
+    if let Some(servers) = obj.get("mcp").and_then(Value::as_object) {
+        return named_entries(
+            servers,
+            SnippetFormat::OpenCodeJson,
+            Confidence::High,
+            OpenCodeAdapter::parse_server_entry,
+        );
+    }
+
+    if is_entry(&obj) {
+        let config = parse_any_entry(&Value::Object(obj))?;
+        let format = SnippetFormat::BareEntry;
+        return Ok(vec![candidate(
+            guess_name(&config),
+            config,
+            format,
+            Confidence::Medium,
+            format.describe().to_string(),
+        )]);
+    }
+    if is_named_entries(&obj) {
+        return named_entries(
+            &obj,
+            SnippetFormat::NamedEntries,
+            Confidence::Medium,
+            parse_any_entry,
+        );
+    }
+
+    Err("JSON snippet does not contain an MCP server config".to_string())
+}
+
+fn from_toml(table: toml::Table) -> Result<Vec<SnippetCandidate>, String> {
+    if let Some(servers) = table.get("mcp_servers").and_then(toml::Value::as_table) {
+        let format = SnippetFormat::CodexToml;
+        return servers
+            .iter()
+            .map(|(name, value)| {
+                Ok(candidate(
+                    name.clone(),
+                    parse_toml_server_entry(name, value)?,
+                    format,
+                    Confidence::High,
+                    format.describe().to_string(),
+                ))
+            })
+            .collect();
+    }
+
+    // Bare or named entries written as TOML have the same shape as their JSON form
+    match serde_json::to_value(&table) {
+        Ok(Value::Object(obj)) => from_json(obj),
+        _ => Err("TOML snippet does not contain an MCP server config".to_string()),
+    }
+}
+
+/// One candidate per command line; `\` line continuations are joined first
+fn from_shell(text: &str) -> Result<Vec<SnippetCandidate>, String> {
+    let joined = text.replace("\\\r\n", " ").replace("\\\n", " ");
+    joined
+        .lines()
+        .map(|line| line.trim().trim_start_matches("$ ").trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_command_line)
+        .collect()
+}
+
+fn parse_command_line(line: &str) -> Result<SnippetCandidate, String> {
+    let tokens = split_shell_words(line)?;
+
+    if let [url] = tokens.as_slice() {
+        if url.starts_with("http://") || url.starts_with("https://") {
+            let config = AgentServerEntry::Remote(RemoteServerEntry {
+                base: BaseServerEntry { timeout: None },
+                url: url.clone(),
+                headers: None,
+            });
+            let format = SnippetFormat::Url;
+            return Ok(candidate(
+                guess_name(&config),
+                config,
+                format,
+                Confidence::Medium,
+                format.describe().to_string(),
+            ));
+        }
+    }
+
+    // Leading `NAME=value` assignments become env vars
+    let mut env = HashMap::new();
+    let mut rest = tokens.into_iter().peekable();
+    while let Some((key, value)) = rest.peek().and_then(|t| env_assignment(t)) {
+        env.insert(key, value);
+        rest.next();
+    }
+
+    let command = rest.next().ok_or("Could not detect the snippet format")?;
+    let args: Vec<String> = rest.collect();
+
+    let known = LAUNCHERS.contains(&program_name(&command).as_str());
+    let (confidence, note) = if known {
+        (
+            Confidence::Medium,
+            format!("{}; review the arguments", SnippetFormat::ShellCommand.describe()),
+        )
+    } else {
+        (
+            Confidence::Low,
+            format!(
+                "{}; '{}' is not a known MCP launcher",
+                SnippetFormat::ShellCommand.describe(),
+                command
+            ),
+        )
+    };
+
+    let config = AgentServerEntry::Local(LocalServerEntry {
+        base: BaseServerEntry { timeout: None },
+        command,
+        args: if args.is_empty() { None } else { Some(args) },
+        env: if env.is_empty() { None } else { Some(env) },
+    });
+    Ok(candidate(guess_name(&config), config, SnippetFormat::ShellCommand, confidence, note))
+}
+
+fn env_assignment(token: &str) -> Option<(String, String)> {
+    let (key, value) = token.split_once('=')?;
+    let mut chars = key.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then(|| (key.to_string(), value.to_string()))
+}
+
+/// POSIX-style word splitting with single quotes, double quotes and backslash escapes
+fn split_shell_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => current.push(ch),
+                        None => return Err("Unterminated single quote in command".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(ch @ ('"' | '\\' | '$' | '`')) => current.push(ch),
+                            Some(ch) => {
+                                current.push('\\');
+                                current.push(ch);
+                            }
+                            None => return Err("Unterminated double quote in command".to_string()),
+                        },
+                        Some(ch) => current.push(ch),
+                        None => return Err("Unterminated double quote in command".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(ch) = chars.next() {
+                    current.push(ch);
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+
+    Ok(words)
+}
+
+/// `npx` from `/usr/local/bin/npx` or `npx.cmd`
+fn program_name(command: &str) -> String {
+    Path::new(command)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| command.to_lowercase())
+}
+
+/// Server name derived from the package, script or host of a config
+fn guess_name(config: &AgentServerEntry) -> String {
+    match config {
+        AgentServerEntry::Local(local) => {
+            let program = program_name(&local.command);
+            let args = local.args.as_deref().unwrap_or_default();
+            let mut positional = args
+                .iter()
+                .filter(|a| !a.starts_with('-') && !LAUNCHER_SUBCOMMANDS.contains(&a.as_str()));
+
+            let target = if matches!(program.as_str(), "docker" | "podman") {
+                args.iter().find(|a| !a.starts_with('-') && a.contains('/'))
+            } else if LAUNCHERS.contains(&program.as_str()) {
+                positional.next()
+            } else {
+                None
+            };
+
+            target.map(|t| package_name(t)).filter(|n| !n.is_empty()).unwrap_or(program)
+        }
+        AgentServerEntry::Remote(remote) => {
+            let host = url::Url::parse(&remote.url)
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_string))
+                .unwrap_or_default();
+            host.split('.')
+                .find(|label| !matches!(*label, "mcp" | "api" | "www" | "server"))
+                .unwrap_or(&host)
+                .to_string()
+        }
+    }
+}
+
+/// `context7` from `@upstash/context7-mcp@latest`, `github` from
+/// `@modelcontextprotocol/server-github` or `ghcr.io/github/github-mcp-server:latest`
+fn package_name(package: &str) -> String {
+    let unversioned = match package.strip_prefix('@') {
+        Some(rest) => rest.split('@').next().unwrap_or(rest),
+        None => package.split(['@', '=']).next().unwrap_or(package),
+    };
+    let mut segments: Vec<&str> = unversioned.split('/').filter(|s| !s.is_empty()).collect();
+    let last = segments.pop().unwrap_or_default();
+    let last = last.split(':').next().unwrap_or(last);
+    let last = Path::new(last).file_stem().map(|s| s.to_string_lossy()).unwrap_or(last.into());
+
+    let mut name = last.to_lowercase();
+    for prefix in ["mcp-server-", "server-", "mcp-"] {
+        if let Some(stripped) = name.strip_prefix(prefix) {
+            name = stripped.to_string();
+            break;
+        }
+    }
+    for suffix in ["-mcp-server", "-server", "-mcp", "_mcp"] {
+        if let Some(stripped) = name.strip_suffix(suffix) {
+            name = stripped.to_string();
+            break;
+        }
+    }
+
+    if name.is_empty() || name == "mcp" || name == "server" {
+        segments.pop().map(|scope| scope.to_lowercase()).unwrap_or(name)
+    } else {
+        name
+    }
+}
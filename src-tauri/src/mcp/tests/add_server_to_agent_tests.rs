@@ -30,6 +30,7 @@ fn create_remote_user_server() -> UserServer {
             origin_type: OriginType::Custom,
             schema_name: None,
             package_id: None,
+            version: None,
         }),
         created_at: None,
     }
@@ -52,6 +53,7 @@ fn create_custom_command_user_server() -> UserServer {
             origin_type: OriginType::Custom,
             schema_name: None,
             package_id: None,
+            version: None,
         }),
         created_at: None,
     }
@@ -74,6 +76,7 @@ fn create_registry_user_server() -> UserServer {
             origin_type: OriginType::Registry,
             schema_name: Some("io.jina/mcp-jina".to_string()),
             package_id: Some("npm:@jina-ai/mcp-server".to_string()),
+            version: None,
         }),
         created_at: None,
     }
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::fs;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::config::get_user_agents_dir;
+use crate::mcp::agent::get_global_config_path;
+use crate::mcp::agent_config::{read_agent_config, save_agent_config};
+use crate::mcp::agent_definition::load_agent_definitions;
+use crate::mcp::{
+    get_all_agent_types, parse_agent_name, AgentServerEntry, AgentServers, AgentType,
+    BaseServerEntry, LocalServerEntry, RemoteServerEntry,
+};
+
+use super::fixtures::test_env;
+use super::EnvGuard;
+
+const NICHE_AGENT_DEFINITION: &str = r#"{
+  "id": "NicheAgent",
+  "displayName": "Niche Agent",
+  "aliases": ["niche"],
+  "configPath": "~/.niche/agent.json",
+  "serversKey": "tools",
+  "typeField": { "key": "transport", "local": "stdio", "remote": "sse" },
+  "urlKey": "endpoint",
+  "envKey": "environment",
+  "defaultFields": { "autoStart": true }
+}"#;
+
+fn write_user_definition(file_name: &str, content: &str) {
+    let dir = get_user_agents_dir().unwrap();
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join(file_name), content).unwrap();
+}
+
+#[rstest]
+fn builtin_definitions_cover_builtin_agent_types(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+
+    let definitions = load_agent_definitions();
+    assert_eq!(definitions.len(), 15);
+    for definition in &definitions {
+        assert!(!matches!(definition.agent_type(), AgentType::Custom(_)), "{}", definition.id);
+    }
+}
+
+#[rstest]
+fn user_definition_adds_custom_agent(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+
+    write_user_definition("niche.json", NICHE_AGENT_DEFINITION);
+
+    let custom = AgentType::Custom("NicheAgent".to_string());
+    assert!(get_all_agent_types().contains(&custom));
+    assert_eq!(parse_agent_name("niche").unwrap(), custom);
+    assert_eq!(parse_agent_name("nicheagent").unwrap(), custom);
+    assert_eq!(
+        get_global_config_path(&custom).unwrap(),
+        temp_dir.path().join(".niche").join("agent.json")
+    );
+}
+
+#[rstest]
+fn custom_agent_round_trips_through_adapter(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+
+    write_user_definition("niche.json", NICHE_AGENT_DEFINITION);
+    let custom = AgentType::Custom("NicheAgent".to_string());
+
+    let mut servers = HashMap::new();
+    servers.insert(
+        "local".to_string(),
+        AgentServerEntry::Local(LocalServerEntry {
+            base: BaseServerEntry { timeout: None },
+            command: "npx".to_string(),
+            args: Some(vec!["-y".to_string(), "server".to_string()]),
+            env: Some(HashMap::from([("TOKEN".to_string(), "abc".to_string())])),
+        }),
+    );
+    servers.insert(
+        "remote".to_string(),
+        AgentServerEntry::Remote(RemoteServerEntry {
+            base: BaseServerEntry { timeout: None },
+            url: "https://mcp.example.com/sse".to_string(),
+            headers: None,
+        }),
+    );
+    save_agent_config(custom.clone(), AgentServers { servers }).unwrap();
+
+    let written: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(temp_dir.path().join(".niche").join("agent.json")).unwrap(),
+    )
+    .unwrap();
+    let tools = written.get("tools").and_then(|v| v.as_object()).unwrap();
+
+    let local = tools.get("local").and_then(|v| v.as_object()).unwrap();
+    assert_eq!(local.get("transport").and_then(|v| v.as_str()), Some("stdio"));
+    assert_eq!(local.get("autoStart").and_then(|v| v.as_bool()), Some(true));
+    assert!(local.get("env").is_none());
+    assert_eq!(
+        local.get("environment").and_then(|v| v.get("TOKEN")).and_then(|v| v.as_str()),
+        Some("abc")
+    );
+
+    let remote = tools.get("remote").and_then(|v| v.as_object()).unwrap();
+    assert_eq!(remote.get("transport").and_then(|v| v.as_str()), Some("sse"));
+    assert!(remote.get("url").is_none());
+    assert_eq!(
+        remote.get("endpoint").and_then(|v| v.as_str()),
+        Some("https://mcp.example.com/sse")
+    );
+
+    let config = read_agent_config(custom).unwrap();
+    match config.servers.get("local").unwrap() {
+        AgentServerEntry::Local(local) => {
+            assert_eq!(local.env.as_ref().unwrap().get("TOKEN").unwrap(), "abc");
+        }
+        _ => panic!("Expected Local config"),
+    }
+    match config.servers.get("remote").unwrap() {
+        AgentServerEntry::Remote(remote) => {
+            assert_eq!(remote.url, "https://mcp.example.com/sse");
+        }
+        _ => panic!("Expected Remote config"),
+    }
+}
+
+#[rstest]
+fn user_definition_overrides_builtin(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+
+    write_user_definition(
+        "cursor.json",
+        r#"{ "id": "Cursor", "configPath": { "default": "~/.cursor-nightly/mcp.json" } }"#,
+    );
+
+    assert_eq!(get_all_agent_types().len(), 15);
+    assert_eq!(
+        get_global_config_path(&AgentType::Cursor).unwrap(),
+        temp_dir.path().join(".cursor-nightly").join("mcp.json")
+    );
+}
+
+#[rstest]
+fn invalid_user_definition_is_skipped(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+
+    write_user_definition("broken.json", "{ not json");
+    write_user_definition("empty-id.json", r#"{ "id": "", "configPath": "~/x.json" }"#);
+
+    assert_eq!(get_all_agent_types().len(), 15);
+}
+
+#[test]
+fn custom_agent_type_serializes_as_plain_id() {
+    let custom = AgentType::Custom("NicheAgent".to_string());
+    assert_eq!(serde_json::to_string(&custom).unwrap(), r#""NicheAgent""#);
+    assert_eq!(serde_json::to_string(&AgentType::ClaudeCode).unwrap(), r#""ClaudeCode""#);
+
+    let parsed: AgentType = serde_json::from_str(r#""NicheAgent""#).unwrap();
+    assert_eq!(parsed, custom);
+    let parsed: AgentType = serde_json::from_str(r#""Cursor""#).unwrap();
+    assert_eq!(parsed, AgentType::Cursor);
+}
+
+#[rstest]
+fn user_definitions_reload_when_directory_changes(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+
+    write_user_definition("niche.json", NICHE_AGENT_DEFINITION);
+    let custom = AgentType::Custom("NicheAgent".to_string());
+    assert!(get_all_agent_types().contains(&custom));
+
+    write_user_definition(
+        "niche.json",
+        r#"{ "id": "NicheAgent", "configPath": "~/.niche-beta/agent-config.json" }"#,
+    );
+    assert_eq!(
+        get_global_config_path(&custom).unwrap(),
+        temp_dir.path().join(".niche-beta").join("agent-config.json")
+    );
+
+    fs::remove_file(get_user_agents_dir().unwrap().join("niche.json")).unwrap();
+    assert!(!get_all_agent_types().contains(&custom));
+}
+
+#[rstest]
+fn json_only_fields_are_rejected_for_other_formats(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+
+    write_user_definition(
+        "codex-fork.json",
+        r#"{ "id": "CodexFork", "configPath": "~/.fork/config.toml", "format": "toml", "urlKey": "endpoint" }"#,
+    );
+    write_user_definition(
+        "opencode-fork.json",
+        r#"{ "id": "OpenCodeFork", "configPath": "~/.fork/opencode.json", "format": "opencode" }"#,
+    );
+
+    let types = get_all_agent_types();
+    assert!(!types.contains(&AgentType::Custom("CodexFork".to_string())));
+    assert!(types.contains(&AgentType::Custom("OpenCodeFork".to_string())));
+}
+
+#[test]
+fn agent_type_schema_accepts_custom_ids() {
+    let schema = serde_json::to_value(schemars::schema_for!(AgentType)).unwrap();
+    let variants = schema["anyOf"].as_array().unwrap();
+    assert_eq!(variants[0]["enum"].as_array().unwrap().len(), 15);
+    assert_eq!(variants[1]["type"], "string");
+}
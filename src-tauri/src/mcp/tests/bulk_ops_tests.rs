@@ -0,0 +1,139 @@
+use std::fs;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::config::{load_app_config, save_app_config};
+use crate::mcp::agent_config::read_agent_config;
+use crate::mcp::bulk_ops::ServerSelector;
+use crate::mcp::user_server::UserServer;
+use crate::mcp::{
+    add_server_to_agent_command, get_user_server_links_command,
+    remove_server_from_all_agents_command, rename_server_in_all_agents_command, AgentServerEntry,
+    AgentType, BaseServerEntry, RemoteServerEntry,
+};
+
+use super::fixtures::{test_env, CLAUDE_CODE_CONFIG_JSON};
+use super::EnvGuard;
+
+const CURSOR_CONFIG_JSON: &str = r#"{
+  "mcpServers": {
+    "remote": {
+      "url": "https://mcp.example.com/mcp"
+    },
+    "other": {
+      "url": "https://other.example.com/mcp"
+    }
+  }
+}
+"#;
+
+fn write_agent_configs(temp_dir: &TempDir) {
+    fs::write(temp_dir.path().join(".claude.json"), CLAUDE_CODE_CONFIG_JSON).unwrap();
+    fs::create_dir_all(temp_dir.path().join(".cursor")).unwrap();
+    fs::write(temp_dir.path().join(".cursor/mcp.json"), CURSOR_CONFIG_JSON).unwrap();
+}
+
+#[rstest]
+fn remove_by_name_touches_every_agent(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    write_agent_configs(&temp_dir);
+
+    let results =
+        remove_server_from_all_agents_command(ServerSelector::Name { name: "remote".to_string() })
+            .unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.success));
+    assert!(!read_agent_config(AgentType::ClaudeCode).unwrap().servers.contains_key("remote"));
+    let cursor = read_agent_config(AgentType::Cursor).unwrap();
+    assert!(!cursor.servers.contains_key("remote"));
+    assert!(cursor.servers.contains_key("other"));
+}
+
+#[rstest]
+fn rename_by_name_reports_collisions(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    write_agent_configs(&temp_dir);
+
+    let results = rename_server_in_all_agents_command(
+        ServerSelector::Name { name: "remote".to_string() },
+        "other".to_string(),
+    )
+    .unwrap();
+
+    let claude = results.iter().find(|r| r.agent_type == AgentType::ClaudeCode).unwrap();
+    let cursor = results.iter().find(|r| r.agent_type == AgentType::Cursor).unwrap();
+    assert!(claude.success);
+    assert!(!cursor.success);
+    assert!(cursor.error.as_ref().unwrap().contains("already exists"));
+
+    assert!(read_agent_config(AgentType::ClaudeCode).unwrap().servers.contains_key("other"));
+    assert!(read_agent_config(AgentType::Cursor).unwrap().servers.contains_key("remote"));
+}
+
+#[rstest]
+fn rename_to_current_name_is_a_no_op(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    write_agent_configs(&temp_dir);
+    let cursor_before = fs::read_to_string(temp_dir.path().join(".cursor/mcp.json")).unwrap();
+
+    let results = rename_server_in_all_agents_command(
+        ServerSelector::Name { name: "remote".to_string() },
+        "remote".to_string(),
+    )
+    .unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.success && r.error.is_none()));
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join(".cursor/mcp.json")).unwrap(),
+        cursor_before
+    );
+}
+
+#[rstest]
+fn bulk_ops_by_user_server_follow_links(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    write_agent_configs(&temp_dir);
+
+    let mut app_config = load_app_config();
+    app_config.user_servers.push(UserServer {
+        id: "docs".to_string(),
+        name: "docs".to_string(),
+        config: AgentServerEntry::Remote(RemoteServerEntry {
+            base: BaseServerEntry { timeout: None },
+            url: "https://docs.example.com/mcp".to_string(),
+            headers: None,
+        }),
+        origin: None,
+        created_at: None,
+    });
+    save_app_config(&app_config).unwrap();
+    add_server_to_agent_command("claude-code".to_string(), "docs".to_string(), None).unwrap();
+    add_server_to_agent_command("cursor".to_string(), "docs".to_string(), Some("d".to_string()))
+        .unwrap();
+
+    let selector = ServerSelector::UserServer { server_id: "docs".to_string() };
+    let results =
+        rename_server_in_all_agents_command(selector.clone(), "docs-v2".to_string()).unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.success));
+
+    let links = get_user_server_links_command("docs".to_string()).unwrap();
+    assert!(links.iter().all(|l| l.server_name == "docs-v2"));
+
+    let results = remove_server_from_all_agents_command(selector).unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(!read_agent_config(AgentType::ClaudeCode).unwrap().servers.contains_key("docs-v2"));
+    assert!(!read_agent_config(AgentType::Cursor).unwrap().servers.contains_key("docs-v2"));
+    assert!(load_app_config().server_deployments.is_empty());
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::config::{load_app_config, save_app_config};
+use crate::mcp::bundle::{BundleConflictKind, ServerBundle};
+use crate::mcp::registry::{save_schema_store, SchemaStore};
+use crate::mcp::transfer::ConflictStrategy;
+use crate::mcp::user_server::{OriginType, ServerOrigin, UserServer};
+use crate::mcp::{
+    export_server_bundle_command, import_server_bundle_command, preview_server_bundle_command,
+    AgentServerEntry, BaseServerEntry, LocalServerEntry, RemoteServerEntry,
+};
+
+use super::fixtures::test_env;
+use super::EnvGuard;
+
+fn registry_server() -> UserServer {
+    UserServer {
+        id: "acme".to_string(),
+        name: "acme".to_string(),
+        config: AgentServerEntry::Local(LocalServerEntry {
+            base: BaseServerEntry { timeout: None },
+            command: "npx".to_string(),
+            args: Some(vec!["-y".to_string(), "@acme/mcp".to_string()]),
+            env: Some(HashMap::from([
+                ("ACME_ENDPOINT_ID".to_string(), "ep-123".to_string()),
+                ("DEBUG".to_string(), "1".to_string()),
+            ])),
+        }),
+        origin: Some(ServerOrigin {
+            origin_type: OriginType::Registry,
+            schema_name: Some("io.acme/mcp".to_string()),
+            package_id: Some("npm:@acme/mcp".to_string()),
+            version: None,
+        }),
+        created_at: None,
+    }
+}
+
+fn remote_server() -> UserServer {
+    UserServer {
+        id: "jira".to_string(),
+        name: "jira".to_string(),
+        config: AgentServerEntry::Remote(RemoteServerEntry {
+            base: BaseServerEntry { timeout: None },
+            url: "https://jira.example.com/mcp".to_string(),
+            headers: Some(HashMap::from([(
+                "Authorization".to_string(),
+                "Bearer jira-secret".to_string(),
+            )])),
+        }),
+        origin: None,
+        created_at: None,
+    }
+}
+
+fn setup() {
+    let schema = serde_json::from_value(serde_json::json!({
+        "name": "io.acme/mcp",
+        "description": "Acme",
+        "version": "1.0.0",
+        "packages": [{
+            "registryType": "npm",
+            "identifier": "@acme/mcp",
+            "transport": { "type": "stdio" },
+            "environmentVariables": [
+                { "name": "ACME_ENDPOINT_ID", "description": "Endpoint id", "isSecret": true },
+                { "name": "DEBUG" }
+            ]
+        }]
+    }))
+    .unwrap();
+    save_schema_store(&SchemaStore { servers: vec![schema], ..Default::default() }).unwrap();
+
+    let mut app_config = load_app_config();
+    app_config.user_servers = vec![registry_server(), remote_server()];
+    save_app_config(&app_config).unwrap();
+}
+
+#[rstest]
+fn export_replaces_secrets_with_placeholders(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    setup();
+
+    let content =
+        export_server_bundle_command(vec!["acme".to_string(), "jira".to_string()]).unwrap();
+    assert!(!content.contains("ep-123"));
+    assert!(!content.contains("jira-secret"));
+
+    let bundle: ServerBundle = serde_json::from_str(&content).unwrap();
+    assert_eq!(bundle.version, 1);
+    assert_eq!(bundle.placeholders.len(), 2);
+    assert_eq!(bundle.placeholders[0].name, "ACME_ENDPOINT_ID");
+    assert_eq!(bundle.placeholders[0].description.as_deref(), Some("Endpoint id"));
+    assert_eq!(bundle.placeholders[1].field, "headers.Authorization");
+
+    let AgentServerEntry::Local(local) = &bundle.servers[0].config else { panic!() };
+    let env = local.env.as_ref().unwrap();
+    assert_eq!(env["ACME_ENDPOINT_ID"], "{{ACME_ENDPOINT_ID}}");
+    assert_eq!(env["DEBUG"], "1");
+    let AgentServerEntry::Remote(remote) = &bundle.servers[1].config else { panic!() };
+    assert_eq!(remote.headers.as_ref().unwrap()["Authorization"], "Bearer {{AUTHORIZATION}}");
+    assert!(bundle.servers[0].origin.is_some());
+}
+
+#[rstest]
+fn import_fills_placeholders_and_resolves_conflicts(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    setup();
+    let content =
+        export_server_bundle_command(vec!["acme".to_string(), "jira".to_string()]).unwrap();
+
+    let preview = preview_server_bundle_command(content.clone()).unwrap();
+    assert_eq!(preview.conflicts.len(), 2);
+    assert!(preview.conflicts.iter().all(|c| c.kind == BundleConflictKind::Id));
+
+    let values = HashMap::from([
+        ("ACME_ENDPOINT_ID".to_string(), "ep-new".to_string()),
+        ("AUTHORIZATION".to_string(), "mine".to_string()),
+    ]);
+    let missing =
+        import_server_bundle_command(content.clone(), HashMap::new(), ConflictStrategy::Skip);
+    assert!(missing.unwrap_err().contains("ACME_ENDPOINT_ID, AUTHORIZATION"));
+
+    let skipped =
+        import_server_bundle_command(content.clone(), values.clone(), ConflictStrategy::Skip)
+            .unwrap();
+    assert!(skipped.imported.is_empty());
+    assert_eq!(skipped.skipped.len(), 2);
+
+    let renamed = import_server_bundle_command(content, values, ConflictStrategy::Rename).unwrap();
+    assert_eq!(renamed.imported.len(), 2);
+    assert_eq!(renamed.imported[1].name, "jira-2");
+    assert_ne!(renamed.imported[1].id, "jira");
+    let AgentServerEntry::Remote(remote) = &renamed.imported[1].config else { panic!() };
+    assert_eq!(remote.headers.as_ref().unwrap()["Authorization"], "Bearer mine");
+    assert_eq!(load_app_config().user_servers.len(), 4);
+}
+
+#[rstest]
+fn invalid_bundles_are_rejected(test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>)) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+
+    let wrong_format = r#"{"format": "other", "version": 1, "exportedAt": "", "servers": []}"#;
+    assert!(preview_server_bundle_command(wrong_format.to_string()).unwrap_err().contains("Not a"));
+
+    let future = r#"{"format": "rainmcp-bundle", "version": 99, "exportedAt": "", "servers": []}"#;
+    assert!(preview_server_bundle_command(future.to_string()).unwrap_err().contains("version"));
+
+    let undeclared = r#"{
+      "format": "rainmcp-bundle", "version": 1, "exportedAt": "",
+      "servers": [{"id": "a", "name": "a", "config": {"type": "remote", "url": "https://a",
+        "headers": {"X-Key": "{{X_KEY}}"}}}]
+    }"#;
+    assert!(preview_server_bundle_command(undeclared.to_string()).unwrap_err().contains("X_KEY"));
+}
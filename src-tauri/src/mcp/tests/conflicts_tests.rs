@@ -0,0 +1,78 @@
+use std::fs;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::mcp::{get_cross_agent_report_command, update_enabled_agents_command, AgentType};
+
+use super::fixtures::{test_env, CLAUDE_CODE_CONFIG_JSON};
+use super::EnvGuard;
+
+const CURSOR_CONFIG_JSON: &str = r#"{
+  "mcpServers": {
+    "server-name": {
+      "command": "npx",
+      "args": ["-y", "mcp-server@2"],
+      "env": {
+        "API_KEY": "sk-cursor-secret"
+      }
+    },
+    "remote": {
+      "url": "https://mcp.example.com/mcp",
+      "headers": {
+        "Authorization": "Bearer <redacted>"
+      }
+    }
+  }
+}
+"#;
+
+#[rstest]
+fn report_lists_field_differences_with_redacted_secrets(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+
+    fs::write(temp_dir.path().join(".claude.json"), CLAUDE_CODE_CONFIG_JSON).unwrap();
+    fs::create_dir_all(temp_dir.path().join(".cursor")).unwrap();
+    fs::write(temp_dir.path().join(".cursor/mcp.json"), CURSOR_CONFIG_JSON).unwrap();
+    update_enabled_agents_command(vec!["claude-code".to_string(), "cursor".to_string()]).unwrap();
+
+    let report = get_cross_agent_report_command().unwrap();
+    assert_eq!(report.len(), 2);
+
+    let remote = report.iter().find(|d| d.server_name == "remote").unwrap();
+    assert_eq!(remote.agents.len(), 2);
+    assert!(remote.differences.is_empty());
+
+    let drifted = report.iter().find(|d| d.server_name == "server-name").unwrap();
+    let fields: Vec<&str> = drifted.differences.iter().map(|d| d.field.as_str()).collect();
+    assert_eq!(fields, vec!["args", "env.API_KEY", "timeout"]);
+
+    let env_diff = &drifted.differences[1];
+    assert!(env_diff.values.iter().all(|v| v.value.as_deref() == Some("<redacted>")));
+
+    let timeout_diff = &drifted.differences[2];
+    let cursor_timeout =
+        timeout_diff.values.iter().find(|v| v.agent_type == AgentType::Cursor).unwrap();
+    assert_eq!(cursor_timeout.value, None);
+
+    let serialized = serde_json::to_string(&report).unwrap();
+    assert!(!serialized.contains("sk-cursor-secret"));
+}
+
+#[rstest]
+fn report_ignores_disabled_agents(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+
+    fs::write(temp_dir.path().join(".claude.json"), CLAUDE_CODE_CONFIG_JSON).unwrap();
+    fs::create_dir_all(temp_dir.path().join(".cursor")).unwrap();
+    fs::write(temp_dir.path().join(".cursor/mcp.json"), CURSOR_CONFIG_JSON).unwrap();
+    update_enabled_agents_command(vec!["claude-code".to_string()]).unwrap();
+
+    let report = get_cross_agent_report_command().unwrap();
+    assert!(report.iter().all(|d| d.agents == vec![AgentType::ClaudeCode]));
+    assert!(report.iter().all(|d| d.differences.is_empty()));
+}
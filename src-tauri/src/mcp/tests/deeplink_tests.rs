@@ -0,0 +1,185 @@
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::config::load_app_config;
+use crate::mcp::deeplink::{receive_install_link, InstallLinkSource};
+use crate::mcp::journal::load_journal;
+use crate::mcp::{
+    install_from_link_command, parse_install_link_command, AgentServerEntry, BaseServerEntry,
+    LocalServerEntry, RemoteServerEntry,
+};
+
+use super::fixtures::test_env;
+use super::EnvGuard;
+
+const CONTEXT7_CONFIG: &str = r#"{"command":"npx","args":["-y","@upstash/context7-mcp"]}"#;
+
+fn cursor_link(name: &str, config: &str) -> String {
+    format!(
+        "cursor://anysphere.cursor-deeplink/mcp/install?name={}&config={}",
+        name,
+        STANDARD.encode(config)
+    )
+}
+
+fn context7_entry() -> AgentServerEntry {
+    AgentServerEntry::Local(LocalServerEntry {
+        base: BaseServerEntry { timeout: None },
+        command: "npx".to_string(),
+        args: Some(vec!["-y".to_string(), "@upstash/context7-mcp".to_string()]),
+        env: None,
+    })
+}
+
+#[test]
+fn test_parse_cursor_link() {
+    let link = parse_install_link_command(cursor_link("context7", CONTEXT7_CONFIG)).unwrap();
+
+    assert_eq!(link.source, InstallLinkSource::Cursor);
+    assert_eq!(link.server.name, "context7");
+    assert_eq!(link.server.config, context7_entry());
+    assert!(!link.server.id.is_empty());
+}
+
+#[test]
+fn test_parse_cursor_link_with_url_safe_base64() {
+    let config = r#"{"url":"https://mcp.example.com/sse?a=1","headers":{"X-Key":"k>?"}}"#;
+    let link = format!(
+        "cursor://anysphere.cursor-deeplink/mcp/install?name=example&config={}",
+        URL_SAFE_NO_PAD.encode(config)
+    );
+
+    let server = parse_install_link_command(link).unwrap().server;
+    match server.config {
+        AgentServerEntry::Remote(remote) => {
+            assert_eq!(remote.url, "https://mcp.example.com/sse?a=1");
+            assert_eq!(remote.headers.unwrap()["X-Key"], "k>?");
+        }
+        other => panic!("expected remote server, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_vscode_link() {
+    let payload = r#"{"name":"github","type":"http","url":"https://api.githubcopilot.com/mcp/"}"#;
+    let encoded: String = url::form_urlencoded::byte_serialize(payload.as_bytes()).collect();
+
+    let link = parse_install_link_command(format!("vscode:mcp/install?{}", encoded)).unwrap();
+
+    assert_eq!(link.source, InstallLinkSource::VsCode);
+    assert_eq!(link.server.name, "github");
+    assert_eq!(
+        link.server.config,
+        AgentServerEntry::Remote(RemoteServerEntry {
+            base: BaseServerEntry { timeout: None },
+            url: "https://api.githubcopilot.com/mcp/".to_string(),
+            headers: None,
+        })
+    );
+}
+
+#[test]
+fn test_parse_vscode_link_with_percent_encoded_local_server() {
+    let link = "vscode-insiders:mcp/install?%7B%22name%22%3A%22context7%22%2C%22command%22%3A%22npx%22%2C%22args%22%3A%5B%22-y%22%2C%22%40upstash%2Fcontext7-mcp%22%5D%7D";
+
+    let link = parse_install_link_command(link.to_string()).unwrap();
+
+    assert_eq!(link.source, InstallLinkSource::VsCode);
+    assert_eq!(link.server.name, "context7");
+    assert_eq!(link.server.config, context7_entry());
+}
+
+#[test]
+fn test_parse_rainmcp_link_accepts_both_payloads() {
+    let cursor_style =
+        format!("rainmcp://install?name=context7&config={}", STANDARD.encode(CONTEXT7_CONFIG));
+    let json_style = "rainmcp://install?%7B%22name%22%3A%22context7%22%2C%22command%22%3A%22npx%22%2C%22args%22%3A%5B%22-y%22%2C%22%40upstash%2Fcontext7-mcp%22%5D%7D";
+
+    for link in [cursor_style, json_style.to_string()] {
+        let link = parse_install_link_command(link).unwrap();
+        assert_eq!(link.source, InstallLinkSource::RainMcp);
+        assert_eq!(link.server.name, "context7");
+        assert_eq!(link.server.config, context7_entry());
+    }
+}
+
+#[rstest]
+#[case::unknown_scheme("https://example.com/mcp/install?name=x", "Unsupported")]
+#[case::wrong_path("cursor://anysphere.cursor-deeplink/settings?name=x", "Not an MCP install link")]
+#[case::no_payload("vscode:mcp/install", "no payload")]
+#[case::missing_config("cursor://anysphere.cursor-deeplink/mcp/install?name=x", "'config'")]
+#[case::bad_base64("rainmcp://install?name=x&config=%%%", "base64")]
+#[case::missing_name("vscode:mcp/install?%7B%22command%22%3A%22npx%22%7D", "'name'")]
+#[case::no_command_or_url("vscode:mcp/install?%7B%22name%22%3A%22x%22%7D", "'command' or 'url'")]
+fn test_parse_invalid_links(#[case] link: &str, #[case] expected: &str) {
+    let error = parse_install_link_command(link.to_string()).unwrap_err();
+    assert!(error.contains(expected), "unexpected error: {}", error);
+}
+
+#[rstest]
+fn test_received_link_waits_for_confirmation(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    let link =
+        format!("rainmcp://install?name=context7&config={}", STANDARD.encode(CONTEXT7_CONFIG));
+
+    let event = receive_install_link(&link).unwrap();
+    assert_eq!(event.link, link);
+    assert_eq!(event.install.source, InstallLinkSource::RainMcp);
+    assert_eq!(event.install.server.config, context7_entry());
+    let payload = serde_json::to_value(&event).unwrap();
+    assert_eq!(payload["server"]["name"], "context7");
+
+    assert!(load_app_config().user_servers.is_empty());
+    assert!(load_journal().is_empty());
+
+    // Confirming installs the previewed server
+    install_from_link_command(event.link).unwrap();
+    assert_eq!(load_app_config().user_servers.len(), 1);
+}
+
+#[rstest]
+fn test_install_from_link_adds_to_library(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+
+    let server = install_from_link_command(cursor_link("context7", CONTEXT7_CONFIG)).unwrap();
+
+    let library = load_app_config().user_servers;
+    assert_eq!(library.len(), 1);
+    assert_eq!(library[0].id, server.id);
+    assert_eq!(library[0].config, context7_entry());
+    assert!(library[0].created_at.is_some());
+    assert_eq!(load_journal().len(), 1);
+}
+
+#[rstest]
+fn test_install_from_link_reuses_identical_server(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+
+    let first = install_from_link_command(cursor_link("context7", CONTEXT7_CONFIG)).unwrap();
+    let second = install_from_link_command(cursor_link("context7", CONTEXT7_CONFIG)).unwrap();
+
+    assert_eq!(first.id, second.id);
+    assert_eq!(load_app_config().user_servers.len(), 1);
+}
+
+#[rstest]
+fn test_install_from_link_renames_on_name_clash(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+
+    install_from_link_command(cursor_link("context7", CONTEXT7_CONFIG)).unwrap();
+    let other = r#"{"command":"uvx","args":["context7"]}"#;
+    let renamed = install_from_link_command(cursor_link("context7", other)).unwrap();
+
+    assert_eq!(renamed.name, "context7-2");
+    assert_eq!(load_app_config().user_servers.len(), 2);
+}
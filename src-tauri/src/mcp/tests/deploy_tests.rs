@@ -0,0 +1,110 @@
+use std::fs;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::config::{load_app_config, save_app_config};
+use crate::mcp::agent_config::read_agent_config;
+use crate::mcp::transaction::WriteStatus;
+use crate::mcp::user_server::UserServer;
+use crate::mcp::{
+    deploy_user_server_command, AgentServerEntry, AgentType, BaseServerEntry, LocalServerEntry,
+};
+
+use super::fixtures::{test_env, CLAUDE_CODE_CONFIG_WITH_FIELDS_JSON};
+use super::EnvGuard;
+
+fn save_fetch_server() {
+    let mut app_config = load_app_config();
+    app_config.user_servers.push(UserServer {
+        id: "fetch".to_string(),
+        name: "fetch".to_string(),
+        config: AgentServerEntry::Local(LocalServerEntry {
+            base: BaseServerEntry { timeout: None },
+            command: "uvx".to_string(),
+            args: Some(vec!["mcp-server-fetch".to_string()]),
+            env: None,
+        }),
+        origin: None,
+        created_at: None,
+    });
+    save_app_config(&app_config).unwrap();
+}
+
+#[rstest]
+fn deploy_writes_all_agents_and_links_them(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    fs::write(temp_dir.path().join(".claude.json"), CLAUDE_CODE_CONFIG_WITH_FIELDS_JSON).unwrap();
+    save_fetch_server();
+
+    let report = deploy_user_server_command(
+        "fetch".to_string(),
+        vec!["claude-code".to_string(), "cursor".to_string(), "codex".to_string()],
+        None,
+    )
+    .unwrap();
+
+    assert!(report.committed);
+    assert!(report.agents.iter().all(|o| o.status == WriteStatus::Written));
+    for agent in [AgentType::ClaudeCode, AgentType::Cursor, AgentType::OpenAiCodex] {
+        assert!(read_agent_config(agent).unwrap().servers.contains_key("fetch"));
+    }
+    assert_eq!(load_app_config().server_deployments["fetch"].len(), 3);
+}
+
+#[rstest]
+fn deploy_prepare_failure_writes_nothing(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    fs::write(temp_dir.path().join(".claude.json"), CLAUDE_CODE_CONFIG_WITH_FIELDS_JSON).unwrap();
+    fs::create_dir_all(temp_dir.path().join(".cursor")).unwrap();
+    fs::write(temp_dir.path().join(".cursor/mcp.json"), "{ not json").unwrap();
+    save_fetch_server();
+
+    let report = deploy_user_server_command(
+        "fetch".to_string(),
+        vec!["claude-code".to_string(), "cursor".to_string()],
+        None,
+    )
+    .unwrap();
+
+    assert!(!report.committed);
+    assert_eq!(report.agents[0].status, WriteStatus::NotAttempted);
+    assert_eq!(report.agents[1].status, WriteStatus::Failed);
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join(".claude.json")).unwrap(),
+        CLAUDE_CODE_CONFIG_WITH_FIELDS_JSON
+    );
+    assert!(load_app_config().server_deployments.is_empty());
+}
+
+#[rstest]
+fn deploy_write_failure_rolls_back_earlier_agents(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    fs::write(temp_dir.path().join(".claude.json"), CLAUDE_CODE_CONFIG_WITH_FIELDS_JSON).unwrap();
+    // A file where Cursor's config directory should be: reading sees no config, writing fails
+    fs::write(temp_dir.path().join(".cursor"), "").unwrap();
+    save_fetch_server();
+
+    let report = deploy_user_server_command(
+        "fetch".to_string(),
+        vec!["claude-code".to_string(), "windsurf".to_string(), "cursor".to_string()],
+        None,
+    )
+    .unwrap();
+
+    assert!(!report.committed);
+    assert_eq!(report.agents[0].status, WriteStatus::RolledBack);
+    assert_eq!(report.agents[1].status, WriteStatus::RolledBack);
+    assert_eq!(report.agents[2].status, WriteStatus::Failed);
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join(".claude.json")).unwrap(),
+        CLAUDE_CODE_CONFIG_WITH_FIELDS_JSON
+    );
+    assert!(read_agent_config(AgentType::Windsurf).is_err());
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::fs;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::mcp::env_file::{apply_env_file, parse_env_file};
+use crate::mcp::registry::{save_schema_store, SchemaStore};
+use crate::mcp::user_server::{OriginType, ServerOrigin, UserServer};
+use crate::mcp::{
+    import_env_file_command, AgentServerEntry, BaseServerEntry, LocalServerEntry, RemoteServerEntry,
+};
+
+use super::fixtures::test_env;
+use super::EnvGuard;
+
+fn local_server(env: &[(&str, &str)], origin: Option<ServerOrigin>) -> UserServer {
+    UserServer {
+        id: "acme".to_string(),
+        name: "acme".to_string(),
+        config: AgentServerEntry::Local(LocalServerEntry {
+            base: BaseServerEntry { timeout: None },
+            command: "npx".to_string(),
+            args: Some(vec!["-y".to_string(), "@acme/mcp".to_string()]),
+            env: if env.is_empty() {
+                None
+            } else {
+                Some(env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect())
+            },
+        }),
+        origin,
+        created_at: None,
+    }
+}
+
+fn env_of(server: &UserServer) -> HashMap<String, String> {
+    match &server.config {
+        AgentServerEntry::Local(local) => local.env.clone().unwrap_or_default(),
+        other => panic!("expected local server, got {:?}", other),
+    }
+}
+
+#[test]
+fn parses_dotenv_dialect() {
+    let content = r#"
+# Credentials
+export API_KEY=abc123
+PLAIN = value with spaces   # trailing comment
+SINGLE='literal $HOME \n'
+DOUBLE="line1\nline2 \"quoted\""
+MULTI="first
+second"
+EMPTY=
+HASH=abc#def
+JOINED='it'\''s'
+"#;
+
+    let vars = parse_env_file(content).unwrap();
+
+    assert_eq!(
+        vars,
+        [
+            ("API_KEY", "abc123"),
+            ("PLAIN", "value with spaces"),
+            ("SINGLE", r"literal $HOME \n"),
+            ("DOUBLE", "line1\nline2 \"quoted\""),
+            ("MULTI", "first\nsecond"),
+            ("EMPTY", ""),
+            ("HASH", "abc#def"),
+            ("JOINED", "it's"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .to_vec()
+    );
+}
+
+#[rstest]
+#[case::missing_equals("API_KEY\n", "Invalid line 1")]
+#[case::bad_key("A=1\n-B=2\n", "Invalid line 2")]
+#[case::unterminated("A=1\nB=\"open\n", "Unterminated quote on line 2")]
+fn rejects_invalid_dotenv(#[case] content: &str, #[case] expected: &str) {
+    let error = parse_env_file(content).unwrap_err();
+    assert!(error.contains(expected), "unexpected error: {}", error);
+}
+
+#[rstest]
+fn fills_registry_environment_variables(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    let schema = serde_json::from_value(serde_json::json!({
+        "name": "io.acme/mcp",
+        "description": "Acme",
+        "version": "1.0.0",
+        "packages": [{
+            "registryType": "npm",
+            "identifier": "@acme/mcp",
+            "transport": { "type": "stdio" },
+            "environmentVariables": [
+                { "name": "ACME_API_KEY", "isRequired": true, "isSecret": true },
+                { "name": "ACME_REGION", "isRequired": true },
+                { "name": "ACME_MODE", "isRequired": true, "default": "fast" },
+                { "name": "ACME_DEBUG" }
+            ]
+        }]
+    }))
+    .unwrap();
+    save_schema_store(&SchemaStore { servers: vec![schema], ..Default::default() }).unwrap();
+
+    let server = local_server(
+        &[("ACME_DEBUG", "")],
+        Some(ServerOrigin {
+            origin_type: OriginType::Registry,
+            schema_name: Some("io.acme/mcp".to_string()),
+            package_id: Some("npm:@acme/mcp".to_string()),
+            version: None,
+        }),
+    );
+    let content = "ACME_API_KEY=k-1\nACME_DEBUG=1\nUNRELATED=x\n";
+
+    let import = apply_env_file(&server, content).unwrap();
+
+    assert_eq!(import.filled, vec!["ACME_API_KEY", "ACME_DEBUG"]);
+    assert_eq!(import.missing_required, vec!["ACME_REGION"]);
+    assert_eq!(import.unused, vec!["UNRELATED"]);
+    let env = env_of(&import.server);
+    assert_eq!(env["ACME_API_KEY"], "k-1");
+    assert_eq!(env["ACME_DEBUG"], "1");
+    assert!(!env.contains_key("UNRELATED"));
+}
+
+#[test]
+fn fills_existing_env_entries() {
+    let server = local_server(&[("TOKEN", "<your token>"), ("URL", ""), ("LOG", "info")], None);
+
+    let import = apply_env_file(&server, "export TOKEN=\"t-1\"\n").unwrap();
+
+    assert_eq!(import.filled, vec!["TOKEN"]);
+    assert_eq!(import.missing_required, vec!["URL"]);
+    let env = env_of(&import.server);
+    assert_eq!(env["TOKEN"], "t-1");
+    assert_eq!(env["LOG"], "info");
+}
+
+#[test]
+fn remote_servers_are_rejected() {
+    let server = UserServer {
+        config: AgentServerEntry::Remote(RemoteServerEntry {
+            base: BaseServerEntry { timeout: None },
+            url: "https://mcp.example.com".to_string(),
+            headers: None,
+        }),
+        ..local_server(&[], None)
+    };
+
+    assert!(apply_env_file(&server, "A=1").unwrap_err().contains("Only local servers"));
+}
+
+#[rstest]
+fn command_reads_env_file(test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>)) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    let path = temp_dir.path().join(".env");
+    fs::write(&path, "TOKEN=from-file\n").unwrap();
+
+    let server = local_server(&[("TOKEN", "")], None);
+    let import =
+        import_env_file_command(server.clone(), path.to_string_lossy().to_string()).unwrap();
+    assert_eq!(env_of(&import.server)["TOKEN"], "from-file");
+
+    let missing = temp_dir.path().join("missing.env").to_string_lossy().to_string();
+    assert!(import_env_file_command(server, missing).unwrap_err().contains("Failed to read"));
+}
@@ -18,7 +18,7 @@ fn get_supported_agents_command_returns_all_agents(
 
     assert_eq!(agents.len(), 15);
 
-    let agent_types: Vec<AgentType> = agents.iter().map(|a| a.agent_type).collect();
+    let agent_types: Vec<AgentType> = agents.iter().map(|a| a.agent_type.clone()).collect();
     assert!(agent_types.contains(&AgentType::ClaudeCode));
     assert!(agent_types.contains(&AgentType::Cursor));
     assert!(agent_types.contains(&AgentType::Windsurf));
@@ -0,0 +1,241 @@
+use std::fs;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::config::{get_journal_path, load_app_config, save_app_config};
+use crate::mcp::agent_config::{read_agent_config, save_agent_config};
+use crate::mcp::journal::{JournalAction, JournalChange};
+use crate::mcp::profiles::Profile;
+use crate::mcp::user_server::UserServer;
+use crate::mcp::{
+    add_server_to_agent_command, add_user_server_command, delete_user_server_command,
+    get_enabled_agents_command, get_journal_command, redo_command, save_profile_command,
+    undo_command, update_enabled_agents_command, AgentServerEntry, AgentType, BaseServerEntry,
+    RemoteServerEntry,
+};
+
+use super::fixtures::{test_env, CLAUDE_CODE_CONFIG_WITH_FIELDS_JSON};
+use super::EnvGuard;
+
+fn remote_server(id: &str) -> UserServer {
+    UserServer {
+        id: id.to_string(),
+        name: id.to_string(),
+        config: AgentServerEntry::Remote(RemoteServerEntry {
+            base: BaseServerEntry { timeout: None },
+            url: format!("https://{}.example.com/mcp", id),
+            headers: None,
+        }),
+        origin: None,
+        created_at: Some("2025-01-01T00:00:00Z".to_string()),
+    }
+}
+
+#[rstest]
+fn journal_records_mutations_newest_first(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+
+    add_user_server_command(remote_server("github")).unwrap();
+    update_enabled_agents_command(vec!["cursor".to_string()]).unwrap();
+    delete_user_server_command("github".to_string()).unwrap();
+
+    let view = get_journal_command(None).unwrap();
+    assert_eq!(view.entries.len(), 3);
+    assert_eq!(view.can_undo, 3);
+    assert_eq!(view.can_redo, 0);
+    assert_eq!(view.entries[0].operation, "Delete user server 'github'");
+    assert_eq!(view.entries[2].operation, "Add user server 'github'");
+
+    assert_eq!(get_journal_command(Some(1)).unwrap().entries.len(), 1);
+}
+
+#[rstest]
+fn undo_and_redo_restore_user_servers_and_enabled_agents(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+
+    add_user_server_command(remote_server("github")).unwrap();
+    update_enabled_agents_command(vec!["cursor".to_string()]).unwrap();
+    delete_user_server_command("github".to_string()).unwrap();
+
+    let undone = undo_command(2).unwrap();
+    assert_eq!(undone.len(), 2);
+    assert!(undone.iter().all(|e| e.action == JournalAction::Undo));
+    assert_eq!(load_app_config().user_servers.len(), 1);
+    assert!(get_enabled_agents_command().unwrap().is_empty());
+
+    let redone = redo_command(1).unwrap();
+    assert_eq!(redone[0].operation, "Redo: Update enabled agents");
+    assert_eq!(get_enabled_agents_command().unwrap(), vec![AgentType::Cursor]);
+
+    let view = get_journal_command(None).unwrap();
+    assert_eq!(view.can_undo, 2);
+    assert_eq!(view.can_redo, 1);
+
+    // A new operation clears the redo stack
+    add_user_server_command(remote_server("sentry")).unwrap();
+    assert_eq!(get_journal_command(None).unwrap().can_redo, 0);
+    assert!(redo_command(1).unwrap().is_empty());
+}
+
+#[rstest]
+fn undo_restores_agent_config_servers(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    let config_path = temp_dir.path().join(".claude.json");
+    fs::write(&config_path, CLAUDE_CODE_CONFIG_WITH_FIELDS_JSON).unwrap();
+    let original = read_agent_config(AgentType::ClaudeCode).unwrap();
+
+    let mut config = original.clone();
+    config.servers.clear();
+    save_agent_config(AgentType::ClaudeCode, config).unwrap();
+    assert!(read_agent_config(AgentType::ClaudeCode).unwrap().servers.is_empty());
+
+    undo_command(1).unwrap();
+    assert_eq!(read_agent_config(AgentType::ClaudeCode).unwrap().servers, original.servers);
+    let restored: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(restored["numStartups"], 12);
+
+    // Undo refuses to clobber changes made after the recorded operation
+    redo_command(1).unwrap();
+    fs::write(&config_path, r#"{ "mcpServers": { "old-server": { "command": "uvx" } } }"#).unwrap();
+    assert!(undo_command(1).is_err());
+    assert!(fs::read_to_string(&config_path).unwrap().contains("uvx"));
+}
+
+fn server_with_token(id: &str, token: &str) -> UserServer {
+    let mut server = remote_server(id);
+    if let AgentServerEntry::Remote(remote) = &mut server.config {
+        remote.headers = Some(std::collections::HashMap::from([(
+            "Authorization".to_string(),
+            format!("Bearer {}", token),
+        )]));
+    }
+    server
+}
+
+#[rstest]
+fn journal_is_sealed_private_and_redacted(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    let token = "ghp_0123456789abcdefghijklmnop";
+
+    add_user_server_command(server_with_token("github", token)).unwrap();
+    add_server_to_agent_command("cursor".to_string(), "github".to_string(), None).unwrap();
+
+    let path = get_journal_path().unwrap();
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(!content.contains(token));
+    assert!(!content.contains("github.example.com"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    let view = get_journal_command(None).unwrap();
+    let view_json = serde_json::to_string(&view).unwrap();
+    assert!(!view_json.contains(token));
+    assert!(view_json.contains("Bearer ghp_********"));
+
+    // Agent config entries hold only the servers that changed
+    let JournalChange::AgentConfig { before, after, .. } = &view.entries[0].changes[0] else {
+        panic!("expected an agent config change, got {:?}", view.entries[0].changes);
+    };
+    assert!(before.is_empty());
+    assert_eq!(after.keys().collect::<Vec<_>>(), vec!["github"]);
+
+    // Undo still restores the real values
+    undo_command(2).unwrap();
+    redo_command(1).unwrap();
+    let AgentServerEntry::Remote(remote) = &load_app_config().user_servers[0].config else {
+        panic!("expected remote server");
+    };
+    assert_eq!(remote.headers.as_ref().unwrap()["Authorization"], format!("Bearer {}", token));
+}
+
+#[rstest]
+fn journal_drops_legacy_lines_and_keeps_newest_entries(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    let path = get_journal_path().unwrap();
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "{\"id\":\"old\",\"changes\":[{\"before\":\"TOKEN=plain\"}]}\n").unwrap();
+
+    add_user_server_command(remote_server("server-0")).unwrap();
+    assert!(!fs::read_to_string(&path).unwrap().contains("TOKEN=plain"));
+
+    for i in 1..=500 {
+        update_enabled_agents_command(vec![if i % 2 == 0 { "cursor" } else { "kiro" }.to_string()])
+            .unwrap();
+    }
+    let lines = fs::read_to_string(&path).unwrap().lines().count();
+    assert!(lines <= 500, "journal has {} lines", lines);
+    let view = get_journal_command(None).unwrap();
+    assert_eq!(view.entries.len(), lines);
+    assert!(view.entries.iter().all(|e| e.operation != "Add user server 'server-0'"));
+}
+
+#[rstest]
+fn undo_delete_restores_deployments_and_profiles(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    let mut app_config = load_app_config();
+    app_config.user_servers.push(remote_server("github"));
+    save_app_config(&app_config).unwrap();
+    save_profile_command(Profile {
+        id: "work".to_string(),
+        name: "Work".to_string(),
+        server_ids: vec!["github".to_string()],
+        agents: vec![AgentType::Cursor],
+    })
+    .unwrap();
+    add_server_to_agent_command("cursor".to_string(), "github".to_string(), None).unwrap();
+    let mut cursor = read_agent_config(AgentType::Cursor).unwrap();
+    cursor.servers.clear();
+    save_agent_config(AgentType::Cursor, cursor).unwrap();
+
+    delete_user_server_command("github".to_string()).unwrap();
+    let app_config = load_app_config();
+    assert!(app_config.server_deployments.is_empty());
+    assert!(app_config.profiles[0].server_ids.is_empty());
+
+    undo_command(1).unwrap();
+    let app_config = load_app_config();
+    assert_eq!(app_config.user_servers.len(), 1);
+    assert_eq!(app_config.server_deployments["github"].len(), 1);
+    assert_eq!(app_config.profiles[0].server_ids, vec!["github"]);
+}
+
+#[rstest]
+fn undo_add_to_agent_forgets_the_deployment(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    let mut app_config = load_app_config();
+    app_config.user_servers.push(remote_server("github"));
+    save_app_config(&app_config).unwrap();
+
+    add_server_to_agent_command("cursor".to_string(), "github".to_string(), None).unwrap();
+    let view = get_journal_command(None).unwrap();
+    assert_eq!(view.entries.len(), 1);
+    assert_eq!(view.entries[0].operation, "Add 'github' to Cursor");
+    assert_eq!(load_app_config().server_deployments["github"].len(), 1);
+
+    undo_command(1).unwrap();
+    assert!(!read_agent_config(AgentType::Cursor).unwrap().servers.contains_key("github"));
+    assert!(load_app_config().server_deployments.is_empty());
+
+    redo_command(1).unwrap();
+    assert!(read_agent_config(AgentType::Cursor).unwrap().servers.contains_key("github"));
+    assert_eq!(load_app_config().server_deployments["github"].len(), 1);
+}
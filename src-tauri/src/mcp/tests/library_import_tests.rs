@@ -0,0 +1,82 @@
+use std::fs;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::config::load_app_config;
+use crate::mcp::user_server::OriginType;
+use crate::mcp::{import_user_servers_command, scan_agent_servers_command, AgentType};
+
+use super::fixtures::{test_env, CLAUDE_CODE_CONFIG_JSON, OPENAI_CODEX_CONFIG_TOML};
+use super::EnvGuard;
+
+const CURSOR_CONFIG_JSON: &str = r#"{
+  "mcpServers": {
+    "my-server": {
+      "command": "npx",
+      "args": ["-y", "mcp-server"]
+    },
+    "figma": {
+      "url": "https://mcp.figma.com/mcp/"
+    }
+  }
+}
+"#;
+
+fn write_agent_configs(temp_dir: &TempDir) {
+    fs::write(temp_dir.path().join(".claude.json"), CLAUDE_CODE_CONFIG_JSON).unwrap();
+    let codex_dir = temp_dir.path().join(".codex");
+    fs::create_dir_all(&codex_dir).unwrap();
+    fs::write(codex_dir.join("config.toml"), OPENAI_CODEX_CONFIG_TOML).unwrap();
+    let cursor_dir = temp_dir.path().join(".cursor");
+    fs::create_dir_all(&cursor_dir).unwrap();
+    fs::write(cursor_dir.join("mcp.json"), CURSOR_CONFIG_JSON).unwrap();
+}
+
+#[rstest]
+fn scan_clusters_servers_across_agents(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    write_agent_configs(&temp_dir);
+
+    let candidates = scan_agent_servers_command().unwrap();
+    assert_eq!(candidates.len(), 4);
+
+    let npx = candidates.iter().find(|c| c.server.name == "server-name").unwrap();
+    assert_eq!(npx.sources.len(), 2);
+    assert_eq!(npx.sources[0].agent_type, AgentType::ClaudeCode);
+    assert_eq!(npx.sources[1].agent_type, AgentType::Cursor);
+    assert_eq!(npx.sources[1].server_name, "my-server");
+    assert!(npx.has_variants);
+    assert!(matches!(npx.server.origin.as_ref().unwrap().origin_type, OriginType::Custom));
+
+    // Trailing slashes do not split remote clusters
+    let figma = candidates.iter().find(|c| c.server.name == "figma").unwrap();
+    assert_eq!(figma.sources.len(), 2);
+
+    assert!(candidates.iter().all(|c| c.existing_id.is_none()));
+}
+
+#[rstest]
+fn import_populates_library_and_marks_existing(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    write_agent_configs(&temp_dir);
+
+    let servers: Vec<_> =
+        scan_agent_servers_command().unwrap().into_iter().map(|c| c.server).collect();
+    let imported = import_user_servers_command(servers.clone()).unwrap();
+    assert_eq!(imported.len(), 4);
+    assert!(imported.iter().all(|s| s.created_at.is_some()));
+    assert_eq!(load_app_config().user_servers.len(), 4);
+
+    let rescanned = scan_agent_servers_command().unwrap();
+    assert!(rescanned.iter().all(|c| c.existing_id.is_some()));
+
+    // Re-importing the same ids is rejected without partial writes
+    let result = import_user_servers_command(servers);
+    assert!(result.unwrap_err().contains("already exists"));
+    assert_eq!(load_app_config().user_servers.len(), 4);
+}
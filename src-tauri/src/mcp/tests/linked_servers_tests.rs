@@ -0,0 +1,143 @@
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::config::{load_app_config, save_app_config};
+use crate::mcp::agent_config::{read_agent_config, save_agent_config};
+use crate::mcp::bulk_ops::ServerSelector;
+use crate::mcp::linked_servers::LinkState;
+use crate::mcp::user_server::UserServer;
+use crate::mcp::{
+    add_server_to_agent_command, delete_user_server_command, get_user_server_links_command,
+    remove_server_from_all_agents_command, sync_user_server_command, update_user_server_command,
+    AgentServerEntry, AgentType, BaseServerEntry, LocalServerEntry,
+};
+
+use super::fixtures::test_env;
+use super::EnvGuard;
+
+fn local_entry(args: &[&str]) -> AgentServerEntry {
+    AgentServerEntry::Local(LocalServerEntry {
+        base: BaseServerEntry { timeout: None },
+        command: "npx".to_string(),
+        args: Some(args.iter().map(|a| a.to_string()).collect()),
+        env: None,
+    })
+}
+
+fn deploy_to_claude_and_cursor() -> UserServer {
+    let server = UserServer {
+        id: "github".to_string(),
+        name: "github".to_string(),
+        config: local_entry(&["-y", "@github/mcp@1"]),
+        origin: None,
+        created_at: None,
+    };
+    let mut app_config = load_app_config();
+    app_config.user_servers.push(server.clone());
+    save_app_config(&app_config).unwrap();
+
+    add_server_to_agent_command("claude-code".to_string(), "github".to_string(), None).unwrap();
+    add_server_to_agent_command("cursor".to_string(), "github".to_string(), Some("gh".to_string()))
+        .unwrap();
+    server
+}
+
+#[rstest]
+fn deploy_records_links(test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>)) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    deploy_to_claude_and_cursor();
+
+    let links = get_user_server_links_command("github".to_string()).unwrap();
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[0].agent_type, AgentType::ClaudeCode);
+    assert_eq!(links[1].server_name, "gh");
+    assert!(links.iter().all(|l| l.state == LinkState::InSync));
+
+    // Deleting the user server keeps tracking the copies so they can still be cleaned up
+    delete_user_server_command("github".to_string()).unwrap();
+    assert_eq!(load_app_config().server_deployments["github"].len(), 2);
+
+    let selector = ServerSelector::UserServer { server_id: "github".to_string() };
+    let results = remove_server_from_all_agents_command(selector).unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.success));
+    assert!(!read_agent_config(AgentType::Cursor).unwrap().servers.contains_key("gh"));
+    assert!(load_app_config().server_deployments.is_empty());
+}
+
+#[rstest]
+fn deleting_a_server_drops_links_to_removed_copies(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    deploy_to_claude_and_cursor();
+
+    let mut cursor = read_agent_config(AgentType::Cursor).unwrap();
+    cursor.servers.remove("gh");
+    save_agent_config(AgentType::Cursor, cursor).unwrap();
+
+    delete_user_server_command("github".to_string()).unwrap();
+    let deployments = &load_app_config().server_deployments["github"];
+    assert_eq!(deployments.len(), 1);
+    assert_eq!(deployments[0].agent_type, AgentType::ClaudeCode);
+}
+
+#[rstest]
+fn sync_pushes_updates_and_holds_back_hand_edits(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    let server = deploy_to_claude_and_cursor();
+
+    // Hand-edit the Cursor copy, then update the user server
+    let mut cursor = read_agent_config(AgentType::Cursor).unwrap();
+    cursor.servers.insert("gh".to_string(), local_entry(&["-y", "@github/mcp@edited"]));
+    save_agent_config(AgentType::Cursor, cursor).unwrap();
+
+    let updated = UserServer { config: local_entry(&["-y", "@github/mcp@2"]), ..server };
+    update_user_server_command(updated.clone()).unwrap();
+
+    let links = get_user_server_links_command("github".to_string()).unwrap();
+    assert_eq!(links[0].state, LinkState::Outdated);
+    assert_eq!(links[1].state, LinkState::HandEdited);
+    assert_eq!(links[1].agent_copy, Some(local_entry(&["-y", "@github/mcp@edited"])));
+
+    let results = sync_user_server_command("github".to_string(), false).unwrap();
+    assert!(results[0].synced);
+    assert!(!results[1].synced);
+    assert_eq!(results[1].state, LinkState::HandEdited);
+
+    let claude = read_agent_config(AgentType::ClaudeCode).unwrap();
+    assert_eq!(claude.servers.get("github"), Some(&updated.config));
+    let cursor = read_agent_config(AgentType::Cursor).unwrap();
+    assert_eq!(cursor.servers.get("gh"), Some(&local_entry(&["-y", "@github/mcp@edited"])));
+
+    let results = sync_user_server_command("github".to_string(), true).unwrap();
+    assert!(!results[0].synced);
+    assert!(results[1].synced);
+
+    let links = get_user_server_links_command("github".to_string()).unwrap();
+    assert!(links.iter().all(|l| l.state == LinkState::InSync));
+}
+
+#[rstest]
+fn sync_does_not_recreate_removed_copies(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    let server = deploy_to_claude_and_cursor();
+
+    let mut claude = read_agent_config(AgentType::ClaudeCode).unwrap();
+    claude.servers.remove("github");
+    save_agent_config(AgentType::ClaudeCode, claude).unwrap();
+    update_user_server_command(UserServer {
+        config: local_entry(&["-y", "@github/mcp@2"]),
+        ..server
+    })
+    .unwrap();
+
+    let results = sync_user_server_command("github".to_string(), true).unwrap();
+    assert_eq!(results[0].state, LinkState::Missing);
+    assert!(!results[0].synced);
+    assert!(!read_agent_config(AgentType::ClaudeCode).unwrap().servers.contains_key("github"));
+}
@@ -34,28 +34,55 @@ impl Drop for EnvGuard {
 }
 
 pub fn set_temp_home(temp_dir: &TempDir) -> EnvGuard {
-    let env_guard = EnvGuard::new(&["HOME", "USERPROFILE", "APPDATA", "XDG_CONFIG_HOME"]);
+    let env_guard =
+        EnvGuard::new(&["HOME", "USERPROFILE", "APPDATA", "XDG_CONFIG_HOME", "XDG_DATA_HOME"]);
     env_guard.set_path("HOME", temp_dir.path());
     env_guard.set_path("USERPROFILE", temp_dir.path());
 
     let appdata = temp_dir.path().join("AppData");
     let xdg_config = temp_dir.path().join(".config");
+    let xdg_data = temp_dir.path().join(".local/share");
     fs::create_dir_all(&appdata).unwrap();
     fs::create_dir_all(&xdg_config).unwrap();
+    fs::create_dir_all(&xdg_data).unwrap();
     env_guard.set_path("APPDATA", &appdata);
     env_guard.set_path("XDG_CONFIG_HOME", &xdg_config);
+    env_guard.set_path("XDG_DATA_HOME", &xdg_data);
 
     env_guard
 }
 
 mod add_server_to_agent_tests;
+mod agent_definitions_tests;
 mod app_config_tests;
+mod bulk_ops_tests;
+mod bundle_tests;
+mod conflicts_tests;
+mod deeplink_tests;
+mod deploy_tests;
 mod enabled_agents_tests;
+mod env_file_tests;
 mod fixtures;
 mod get_config_tests;
 mod get_server_raw_config_tests;
 mod get_supported_agents_tests;
+mod install_snippets_tests;
+mod journal_tests;
+mod library_import_tests;
+mod linked_servers_tests;
 mod opencode_adapter_tests;
+mod preview_config_tests;
+mod profiles_tests;
+mod provider_keys_tests;
+mod redact_tests;
+mod registry_refresh_tests;
+mod schema_search_tests;
 mod schema_store_tests;
+mod secret_scan_tests;
+mod server_updates_tests;
+mod snippet_import_tests;
+mod transfer_tests;
 mod update_config_tests;
 mod user_servers_tests;
+mod vault_tests;
+mod watcher_tests;
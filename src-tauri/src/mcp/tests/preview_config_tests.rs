@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::fs;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::config::{load_app_config, save_app_config};
+use crate::mcp::agent_config::{read_agent_config, save_agent_config};
+use crate::mcp::user_server::UserServer;
+use crate::mcp::{
+    preview_add_server_to_agent_command, preview_agent_mcp_config_command, AgentServerEntry,
+    AgentServers, AgentType, BaseServerEntry, LocalServerEntry,
+};
+
+use super::fixtures::{test_env, CLAUDE_CODE_CONFIG_WITH_FIELDS_JSON, OPENAI_CODEX_CONFIG_TOML};
+use super::EnvGuard;
+
+fn new_local_server() -> AgentServerEntry {
+    AgentServerEntry::Local(LocalServerEntry {
+        base: BaseServerEntry { timeout: None },
+        command: "uvx".to_string(),
+        args: Some(vec!["mcp-server-fetch".to_string()]),
+        env: None,
+    })
+}
+
+#[rstest]
+fn preview_agent_config_does_not_touch_disk(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+
+    let config_path = temp_dir.path().join(".claude.json");
+    fs::write(&config_path, CLAUDE_CODE_CONFIG_WITH_FIELDS_JSON).unwrap();
+
+    let mut config = read_agent_config(AgentType::ClaudeCode).unwrap();
+    config.servers.insert("fetch".to_string(), new_local_server());
+
+    let preview = preview_agent_mcp_config_command("claude-code".to_string(), config).unwrap();
+
+    assert_eq!(fs::read_to_string(&config_path).unwrap(), CLAUDE_CODE_CONFIG_WITH_FIELDS_JSON);
+    assert_eq!(preview.config_path, config_path);
+    assert_eq!(preview.original, CLAUDE_CODE_CONFIG_WITH_FIELDS_JSON);
+    assert!(preview.has_changes);
+    assert!(preview.diff.contains("+    \"fetch\": {"));
+    assert!(preview.diff.contains("\"installMethod\": \"homebrew\""));
+}
+
+#[rstest]
+fn preview_matches_bytes_written_by_save(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+
+    let config_dir = temp_dir.path().join(".codex");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(config_dir.join("config.toml"), OPENAI_CODEX_CONFIG_TOML).unwrap();
+
+    let mut config = read_agent_config(AgentType::OpenAiCodex).unwrap();
+    config.servers.remove("figma");
+
+    let preview = preview_agent_mcp_config_command("codex".to_string(), config.clone()).unwrap();
+    assert!(preview.diff.contains("-[mcp_servers.figma]"));
+
+    save_agent_config(AgentType::OpenAiCodex, config.clone()).unwrap();
+    assert_eq!(fs::read_to_string(config_dir.join("config.toml")).unwrap(), preview.updated);
+
+    let unchanged = preview_agent_mcp_config_command("codex".to_string(), config).unwrap();
+    assert!(!unchanged.has_changes);
+    assert!(unchanged.diff.is_empty());
+}
+
+#[rstest]
+fn preview_add_server_for_missing_config_file(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+
+    let mut app_config = load_app_config();
+    app_config.user_servers.push(UserServer {
+        id: "fetch".to_string(),
+        name: "fetch".to_string(),
+        config: new_local_server(),
+        origin: None,
+        created_at: None,
+    });
+    save_app_config(&app_config).unwrap();
+
+    let preview =
+        preview_add_server_to_agent_command("cursor".to_string(), "fetch".to_string(), None)
+            .unwrap();
+
+    assert!(!temp_dir.path().join(".cursor").exists());
+    assert!(preview.original.is_empty());
+    assert!(preview.has_changes);
+
+    let updated: serde_json::Value = serde_json::from_str(&preview.updated).unwrap();
+    assert_eq!(updated.pointer("/mcpServers/fetch/command").and_then(|v| v.as_str()), Some("uvx"));
+}
+
+#[rstest]
+fn preview_add_server_rejects_existing_name(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+
+    save_agent_config(
+        AgentType::Cursor,
+        AgentServers { servers: HashMap::from([("fetch".to_string(), new_local_server())]) },
+    )
+    .unwrap();
+
+    let mut app_config = load_app_config();
+    app_config.user_servers.push(UserServer {
+        id: "fetch".to_string(),
+        name: "fetch".to_string(),
+        config: new_local_server(),
+        origin: None,
+        created_at: None,
+    });
+    save_app_config(&app_config).unwrap();
+
+    let result =
+        preview_add_server_to_agent_command("cursor".to_string(), "fetch".to_string(), None);
+    assert!(result.unwrap_err().contains("already exists"));
+}
@@ -0,0 +1,169 @@
+use std::fs;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::config::{load_app_config, save_app_config};
+use crate::mcp::agent_config::read_agent_config;
+use crate::mcp::profiles::Profile;
+use crate::mcp::user_server::UserServer;
+use crate::mcp::{
+    activate_profile_command, delete_user_server_command, get_profiles_command,
+    save_profile_command, AgentServerEntry, AgentType, BaseServerEntry, RemoteServerEntry,
+};
+
+use super::fixtures::{test_env, OPENAI_CODEX_CONFIG_TOML};
+use super::EnvGuard;
+
+fn remote_server(id: &str) -> UserServer {
+    UserServer {
+        id: id.to_string(),
+        name: id.to_string(),
+        config: AgentServerEntry::Remote(RemoteServerEntry {
+            base: BaseServerEntry { timeout: None },
+            url: format!("https://{}.example.com/mcp", id),
+            headers: None,
+        }),
+        origin: None,
+        created_at: None,
+    }
+}
+
+fn setup_profiles() {
+    let mut app_config = load_app_config();
+    for id in ["jira", "sentry", "github", "notes"] {
+        app_config.user_servers.push(remote_server(id));
+    }
+    save_app_config(&app_config).unwrap();
+
+    let agents = vec![AgentType::ClaudeCode, AgentType::Cursor];
+    save_profile_command(Profile {
+        id: "work".to_string(),
+        name: "Work".to_string(),
+        server_ids: vec!["jira".to_string(), "sentry".to_string(), "github".to_string()],
+        agents: agents.clone(),
+    })
+    .unwrap();
+    save_profile_command(Profile {
+        id: "personal".to_string(),
+        name: "Personal".to_string(),
+        server_ids: vec!["github".to_string(), "notes".to_string()],
+        agents,
+    })
+    .unwrap();
+}
+
+fn server_names(agent: AgentType) -> Vec<String> {
+    let mut names: Vec<String> = read_agent_config(agent).unwrap().servers.into_keys().collect();
+    names.sort();
+    names
+}
+
+#[rstest]
+fn switching_profiles_swaps_exclusive_servers(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    fs::write(
+        temp_dir.path().join(".claude.json"),
+        r#"{"mcpServers": {"manual": {"url": "https://m"}}}"#,
+    )
+    .unwrap();
+    setup_profiles();
+
+    let changes = activate_profile_command("work".to_string()).unwrap();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(server_names(AgentType::ClaudeCode), vec!["github", "jira", "manual", "sentry"]);
+    assert_eq!(server_names(AgentType::Cursor), vec!["github", "jira", "sentry"]);
+
+    let changes = activate_profile_command("personal".to_string()).unwrap();
+    let claude = changes.iter().find(|c| c.agent_type == AgentType::ClaudeCode).unwrap();
+    assert_eq!(claude.added, vec!["notes"]);
+    let mut removed = claude.removed.clone();
+    removed.sort();
+    assert_eq!(removed, vec!["jira", "sentry"]);
+
+    // Servers not managed by any profile are left alone
+    assert_eq!(server_names(AgentType::ClaudeCode), vec!["github", "manual", "notes"]);
+    assert_eq!(server_names(AgentType::Cursor), vec!["github", "notes"]);
+    assert_eq!(load_app_config().active_profile.as_deref(), Some("personal"));
+}
+
+#[rstest]
+fn activation_reports_updates_and_leaves_unrelated_servers_alone(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    fs::write(
+        temp_dir.path().join(".claude.json"),
+        r#"{"mcpServers": {"sentry": {"url": "https://self-hosted.sentry/mcp"}}}"#,
+    )
+    .unwrap();
+    setup_profiles();
+
+    let changes = activate_profile_command("work".to_string()).unwrap();
+    let claude = changes.iter().find(|c| c.agent_type == AgentType::ClaudeCode).unwrap();
+    assert_eq!(claude.added, vec!["jira", "github"]);
+    assert_eq!(claude.conflicts, vec!["sentry"]);
+    match &read_agent_config(AgentType::ClaudeCode).unwrap().servers["sentry"] {
+        AgentServerEntry::Remote(remote) => {
+            assert_eq!(remote.url, "https://self-hosted.sentry/mcp")
+        }
+        other => panic!("expected remote server, got {:?}", other),
+    }
+    let deployments = load_app_config().server_deployments;
+    assert!(deployments["sentry"].iter().all(|d| d.agent_type != AgentType::ClaudeCode));
+
+    // A changed server rewrites its deployed copies and reports them as updates
+    let mut app_config = load_app_config();
+    app_config.user_servers.iter_mut().find(|s| s.id == "jira").unwrap().config =
+        remote_server("jira-v2").config;
+    save_app_config(&app_config).unwrap();
+    let changes = activate_profile_command("work".to_string()).unwrap();
+    let claude = changes.iter().find(|c| c.agent_type == AgentType::ClaudeCode).unwrap();
+    assert!(claude.added.is_empty());
+    assert_eq!(claude.updated, vec!["jira"]);
+}
+
+#[rstest]
+fn failed_activation_leaves_agents_untouched(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    setup_profiles();
+    activate_profile_command("work".to_string()).unwrap();
+    let claude_before = fs::read_to_string(temp_dir.path().join(".claude.json")).unwrap();
+
+    // Add an agent whose config cannot be parsed
+    let codex_dir = temp_dir.path().join(".codex");
+    fs::create_dir_all(&codex_dir).unwrap();
+    fs::write(codex_dir.join("config.toml"), format!("{}\n[[[broken", OPENAI_CODEX_CONFIG_TOML))
+        .unwrap();
+    let mut personal = get_profiles_command().unwrap().remove(1);
+    personal.agents.push(AgentType::OpenAiCodex);
+    save_profile_command(personal).unwrap();
+
+    assert!(activate_profile_command("personal".to_string()).is_err());
+    assert_eq!(fs::read_to_string(temp_dir.path().join(".claude.json")).unwrap(), claude_before);
+    assert_eq!(load_app_config().active_profile.as_deref(), Some("work"));
+}
+
+#[rstest]
+fn profiles_validate_and_follow_server_deletes(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    setup_profiles();
+
+    let result = save_profile_command(Profile {
+        id: "bad".to_string(),
+        name: "Bad".to_string(),
+        server_ids: vec!["missing".to_string()],
+        agents: vec![],
+    });
+    assert!(result.unwrap_err().contains("not found"));
+
+    delete_user_server_command("github".to_string()).unwrap();
+    let profiles = get_profiles_command().unwrap();
+    assert!(profiles.iter().all(|p| !p.server_ids.contains(&"github".to_string())));
+}
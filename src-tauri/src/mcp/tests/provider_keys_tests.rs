@@ -0,0 +1,151 @@
+use std::fs;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::agent::keys::{is_sealed, seal_plaintext_keys, set_openrouter_api_key};
+use crate::config::{get_app_config_path, get_machine_key_path, load_app_config, AppConfig};
+use crate::mcp::registry::{
+    resolve_auth_header, seal_plaintext_auth_headers, set_registry_auth_header, RegistrySource,
+};
+use crate::mcp::sealed::{open, seal};
+use crate::mcp::{get_app_config_command, update_app_config_command};
+
+use super::fixtures::test_env;
+use super::EnvGuard;
+
+const API_KEY: &str = "sk-or-v1-0123456789abcdef";
+
+#[rstest]
+fn api_key_is_sealed_in_settings(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+
+    set_openrouter_api_key(API_KEY).unwrap();
+
+    let settings = fs::read_to_string(get_app_config_path().unwrap()).unwrap();
+    assert!(!settings.contains(API_KEY));
+    let stored = load_app_config().openrouter_api_key.unwrap();
+    assert!(is_sealed(&stored));
+
+    // A fresh nonce per write
+    set_openrouter_api_key(API_KEY).unwrap();
+    assert_ne!(load_app_config().openrouter_api_key.unwrap(), stored);
+
+    let key_path = get_machine_key_path().unwrap();
+    assert!(!key_path.starts_with(get_app_config_path().unwrap().parent().unwrap()));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&key_path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    set_openrouter_api_key("").unwrap();
+    assert!(load_app_config().openrouter_api_key.is_none());
+}
+
+#[rstest]
+fn app_config_commands_do_not_expose_api_key(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    set_openrouter_api_key(API_KEY).unwrap();
+    let stored = load_app_config().openrouter_api_key;
+
+    assert!(get_app_config_command().openrouter_api_key.is_none());
+
+    // The frontend round-trips the config without the key, or tries to overwrite it
+    update_app_config_command(get_app_config_command()).unwrap();
+    assert_eq!(load_app_config().openrouter_api_key, stored);
+    let config =
+        AppConfig { openrouter_api_key: Some("plain".to_string()), ..get_app_config_command() };
+    update_app_config_command(config).unwrap();
+    assert_eq!(load_app_config().openrouter_api_key, stored);
+}
+
+#[rstest]
+fn plaintext_api_key_is_migrated(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    let config_path = get_app_config_path().unwrap();
+    fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+    fs::write(&config_path, format!(r#"{{"clients": {{}}, "openrouter_api_key": "{}"}}"#, API_KEY))
+        .unwrap();
+
+    seal_plaintext_keys().unwrap();
+
+    assert!(!fs::read_to_string(&config_path).unwrap().contains(API_KEY));
+    let stored = load_app_config().openrouter_api_key.unwrap();
+    assert!(is_sealed(&stored));
+
+    // Already sealed keys are left alone
+    seal_plaintext_keys().unwrap();
+    assert_eq!(load_app_config().openrouter_api_key.unwrap(), stored);
+}
+
+#[rstest]
+fn concurrent_first_uses_share_one_machine_key(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    const AAD: &[u8] = b"rainmcp-test-v1";
+
+    let sealed: Vec<String> = (0..8)
+        .map(|i| std::thread::spawn(move || seal(&format!("value-{}", i), AAD).unwrap()))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+
+    // Every value opens with the one key left on disk
+    for (i, value) in sealed.iter().enumerate() {
+        assert_eq!(*open(value, AAD).unwrap(), format!("value-{}", i));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(get_machine_key_path().unwrap()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
+
+#[rstest]
+fn registry_auth_headers_are_sealed_and_hidden(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    let url = "https://mcp.acme.internal";
+    let config = AppConfig {
+        registry_sources: vec![RegistrySource {
+            name: "Acme".to_string(),
+            url: url.to_string(),
+            auth_header: Some("Bearer tok-123".to_string()),
+            enabled: true,
+        }],
+        ..Default::default()
+    };
+    crate::config::save_app_config(&config).unwrap();
+
+    // Headers written by hand are sealed at startup
+    seal_plaintext_auth_headers().unwrap();
+    let settings = fs::read_to_string(get_app_config_path().unwrap()).unwrap();
+    assert!(!settings.contains("tok-123"));
+    let source = load_app_config().registry_sources[0].clone();
+    assert!(is_sealed(source.auth_header.as_deref().unwrap()));
+    assert_eq!(resolve_auth_header(&source).unwrap().as_deref(), Some("Bearer tok-123"));
+
+    // The frontend never sees the header and cannot overwrite it
+    assert!(get_app_config_command().registry_sources[0].auth_header.is_none());
+    update_app_config_command(get_app_config_command()).unwrap();
+    assert_eq!(load_app_config().registry_sources[0], source);
+
+    set_registry_auth_header(url, Some("Bearer tok-456")).unwrap();
+    let source = load_app_config().registry_sources[0].clone();
+    assert_eq!(resolve_auth_header(&source).unwrap().as_deref(), Some("Bearer tok-456"));
+
+    set_registry_auth_header(url, None).unwrap();
+    assert!(load_app_config().registry_sources[0].auth_header.is_none());
+    assert!(set_registry_auth_header("https://unknown", Some("x")).is_err());
+}
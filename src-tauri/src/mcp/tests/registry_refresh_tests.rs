@@ -0,0 +1,192 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::config::{load_app_config, save_app_config};
+use crate::mcp::registry::{
+    enabled_registry_sources, fetch_schema_store, set_registry_auth_header, RegistrySource,
+    SchemaStore,
+};
+
+use super::fixtures::test_env;
+use super::EnvGuard;
+
+/// A request seen by the mock registry: path with query, and `Authorization` header
+type SeenRequest = (String, Option<String>);
+
+/// Local HTTP registry answering every request with `respond(path)` as (status, body)
+struct MockRegistry {
+    url: String,
+    requests: Arc<Mutex<Vec<SeenRequest>>>,
+}
+
+impl MockRegistry {
+    fn start(respond: impl Fn(&str) -> (u16, String) + Send + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let path = request_line.split_whitespace().nth(1).unwrap_or("").to_string();
+                let mut auth = None;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("authorization") {
+                            auth = Some(value.trim().to_string());
+                        }
+                    }
+                }
+
+                let (status, body) = respond(&path);
+                seen.lock().unwrap().push((path, auth));
+                let response = format!(
+                    "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        MockRegistry { url, requests }
+    }
+
+    fn take_requests(&self) -> Vec<SeenRequest> {
+        std::mem::take(&mut *self.requests.lock().unwrap())
+    }
+}
+
+fn page(servers: &[(&str, &str, &str)]) -> String {
+    let items: Vec<_> = servers
+        .iter()
+        .map(|(name, version, updated_at)| {
+            serde_json::json!({
+                "server": { "name": name, "description": name, "version": version },
+                "_meta": {
+                    "io.modelcontextprotocol.registry/official": {
+                        "status": "active",
+                        "updatedAt": updated_at
+                    }
+                }
+            })
+        })
+        .collect();
+    serde_json::json!({ "servers": items, "metadata": { "nextCursor": null, "count": items.len() } })
+        .to_string()
+}
+
+fn source(name: &str, url: &str) -> RegistrySource {
+    RegistrySource {
+        name: name.to_string(),
+        url: url.to_string(),
+        auth_header: None,
+        enabled: true,
+    }
+}
+
+fn refresh(previous: Result<SchemaStore, String>) -> Result<SchemaStore, String> {
+    let sources = enabled_registry_sources(&load_app_config());
+    tauri::async_runtime::block_on(fetch_schema_store(&sources, previous, &|_| {}))
+}
+
+fn version_of<'a>(store: &'a SchemaStore, name: &str) -> Option<&'a str> {
+    store.latest(name).map(|s| s.version.as_str())
+}
+
+#[rstest]
+fn refresh_keeps_failing_sources_and_syncs_incrementally(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    let private_down = Arc::new(AtomicBool::new(false));
+    let down = private_down.clone();
+    let private = MockRegistry::start(move |path| {
+        if down.load(Ordering::SeqCst) {
+            return (503, "{}".to_string());
+        }
+        if path.contains("updated_since") {
+            (200, page(&[("io.acme/tool", "1.1.0", "2025-03-01T00:00:00Z")]))
+        } else {
+            (
+                200,
+                page(&[
+                    ("io.acme/tool", "1.0.0", "2025-01-01T00:00:00Z"),
+                    ("io.shared/name", "1.0.0", "2025-02-01T00:00:00Z"),
+                ]),
+            )
+        }
+    });
+    let mirror_down = Arc::new(AtomicBool::new(true));
+    let down = mirror_down.clone();
+    let mirror = MockRegistry::start(move |_| {
+        if down.load(Ordering::SeqCst) {
+            return (500, "{}".to_string());
+        }
+        (
+            200,
+            page(&[
+                ("io.shared/name", "9.0.0", "2025-02-01T00:00:00Z"),
+                ("io.mirror/only", "1.0.0", "2025-02-01T00:00:00Z"),
+            ]),
+        )
+    });
+
+    let mut app_config = load_app_config();
+    app_config.registry_sources =
+        vec![source("Private", &private.url), source("Mirror", &mirror.url)];
+    save_app_config(&app_config).unwrap();
+    set_registry_auth_header(&private.url, Some("Bearer private-token")).unwrap();
+
+    // The mirror fails, so only the private registry's servers are stored
+    let store = refresh(Err("no store yet".to_string())).unwrap();
+    assert_eq!(version_of(&store, "io.acme/tool"), Some("1.0.0"));
+    assert!(store.source_errors[&mirror.url].contains("500"));
+    assert!(!store.source_errors.contains_key(&private.url));
+    assert_eq!(store.synced_at[&private.url], "2025-02-01T00:00:00Z");
+    assert!(!store.synced_at.contains_key(&mirror.url));
+    let requests = private.take_requests();
+    assert_eq!(
+        requests,
+        vec![("/v0/servers".to_string(), Some("Bearer private-token".to_string()))]
+    );
+    assert_eq!(mirror.take_requests()[0].1, None);
+
+    // Next time the private registry is asked for updates only, and the mirror, which was
+    // never synced, is fetched in full; the first source wins name clashes
+    mirror_down.store(false, Ordering::SeqCst);
+    let store = refresh(Ok(store)).unwrap();
+    let (path, _) = &private.take_requests()[0];
+    assert_eq!(path, "/v0/servers?updated_since=2025-02-01T00%3A00%3A00Z");
+    assert_eq!(mirror.take_requests()[0].0, "/v0/servers");
+    assert_eq!(version_of(&store, "io.acme/tool"), Some("1.1.0"));
+    assert_eq!(version_of(&store, "io.shared/name"), Some("1.0.0"));
+    assert_eq!(version_of(&store, "io.mirror/only"), Some("1.0.0"));
+    assert!(store.source_errors.is_empty());
+    assert_eq!(store.synced_at[&private.url], "2025-03-01T00:00:00Z");
+
+    // A refresh fails only when every source does
+    private_down.store(true, Ordering::SeqCst);
+    let partial = refresh(Ok(store.clone())).unwrap();
+    assert_eq!(version_of(&partial, "io.acme/tool"), Some("1.1.0"));
+    assert!(partial.source_errors[&private.url].contains("503"));
+    assert_eq!(partial.synced_at[&private.url], "2025-03-01T00:00:00Z");
+    mirror_down.store(true, Ordering::SeqCst);
+    let error = refresh(Ok(store)).unwrap_err();
+    assert!(error.contains("503") && error.contains("500"), "unexpected error: {}", error);
+}
@@ -0,0 +1,162 @@
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::mcp::registry::{save_schema_store, SchemaStore};
+use crate::mcp::schema_search::{SchemaIndex, SchemaSearchQuery, TransportFilter};
+use crate::mcp::search_schema_store_command;
+use crate::mcp::server_schema::ServerSchema;
+
+use super::fixtures::test_env;
+use super::EnvGuard;
+
+fn servers() -> Vec<ServerSchema> {
+    serde_json::from_value(serde_json::json!([
+        {
+            "name": "io.github.acme/weather",
+            "title": "Weather",
+            "description": "Forecasts and alerts",
+            "version": "1.0.0",
+            "packages": [{
+                "registryType": "npm",
+                "identifier": "@acme/weather-mcp",
+                "transport": { "type": "stdio" },
+                "environmentVariables": [{ "name": "WEATHER_API_KEY", "isSecret": true }]
+            }]
+        },
+        {
+            "name": "io.github.other/travel",
+            "description": "Plan trips with live weather data",
+            "version": "1.0.0",
+            "packages": [{
+                "registryType": "pypi",
+                "identifier": "travel-mcp",
+                "transport": { "type": "stdio" }
+            }]
+        },
+        {
+            "name": "com.example/github",
+            "title": "GitHub",
+            "description": "Issues, pull requests and weather-independent code search",
+            "version": "2.0.0",
+            "remotes": [{ "type": "streamable-http", "url": "https://mcp.example.com" }]
+        },
+        {
+            "name": "com.example/postgres",
+            "description": "Query Postgres databases",
+            "version": "0.3.0",
+            "packages": [{
+                "registryType": "oci",
+                "identifier": "docker.io/example/postgres-mcp",
+                "transport": { "type": "stdio" },
+                "packageArguments": [{ "type": "named", "name": "--password", "isSecret": true }]
+            }]
+        }
+    ]))
+    .unwrap()
+}
+
+fn names(index: &SchemaIndex, query: SchemaSearchQuery) -> Vec<String> {
+    index.search(&query).hits.into_iter().map(|hit| hit.server.name).collect()
+}
+
+fn text(text: &str) -> SchemaSearchQuery {
+    SchemaSearchQuery { text: text.to_string(), ..Default::default() }
+}
+
+#[test]
+fn ranks_name_matches_above_description_matches() {
+    let index = SchemaIndex::build(servers());
+
+    let page = index.search(&text("weather"));
+
+    let names: Vec<_> = page.hits.iter().map(|h| h.server.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["io.github.acme/weather", "com.example/github", "io.github.other/travel"]
+    );
+    assert!(page.hits.windows(2).all(|w| w[0].score >= w[1].score));
+}
+
+#[test]
+fn matches_prefixes_and_requires_every_token() {
+    let index = SchemaIndex::build(servers());
+
+    assert_eq!(names(&index, text("postg")), vec!["com.example/postgres"]);
+    assert_eq!(names(&index, text("Weather TRAV")), vec!["io.github.other/travel"]);
+    assert!(names(&index, text("weather postgres")).is_empty());
+
+    // Name segments are terms of their own; single letters only match whole terms
+    let hits = index.search(&text("git")).hits;
+    assert_eq!(hits.len(), 3);
+    assert_eq!(hits[0].server.name, "com.example/github");
+    assert!(names(&index, text("g")).is_empty());
+}
+
+#[rstest]
+#[case::npm(SchemaSearchQuery { registry_types: vec!["NPM".to_string()], ..Default::default() }, &["io.github.acme/weather"])]
+#[case::pypi_or_oci(
+    SchemaSearchQuery { registry_types: vec!["pypi".to_string(), "oci".to_string()], ..Default::default() },
+    &["com.example/postgres", "io.github.other/travel"]
+)]
+#[case::remote(SchemaSearchQuery { transport: Some(TransportFilter::Remote), ..Default::default() }, &["com.example/github"])]
+#[case::stdio_with_secrets(
+    SchemaSearchQuery { transport: Some(TransportFilter::Stdio), has_secrets: Some(true), ..Default::default() },
+    &["com.example/postgres", "io.github.acme/weather"]
+)]
+#[case::without_secrets(
+    SchemaSearchQuery { text: "weather".to_string(), has_secrets: Some(false), ..Default::default() },
+    &["com.example/github", "io.github.other/travel"]
+)]
+fn filters_results(#[case] query: SchemaSearchQuery, #[case] expected: &[&str]) {
+    let index = SchemaIndex::build(servers());
+    assert_eq!(names(&index, query), expected);
+}
+
+#[test]
+fn paginates_large_stores() {
+    let servers: Vec<ServerSchema> = (0..20_000)
+        .map(|i| {
+            serde_json::from_value(serde_json::json!({
+                "name": format!("io.example/server-{:05}", i),
+                "description": if i % 100 == 0 { "Talks to Slack" } else { "Generic tools" },
+                "version": "1.0.0"
+            }))
+            .unwrap()
+        })
+        .collect();
+    let index = SchemaIndex::build(servers);
+
+    let query = SchemaSearchQuery {
+        text: "slack".to_string(),
+        offset: 190,
+        limit: Some(20),
+        ..Default::default()
+    };
+    let page = index.search(&query);
+
+    assert_eq!(page.total, 200);
+    assert_eq!(page.offset, 190);
+    assert_eq!(page.hits.len(), 10);
+    assert_eq!(page.hits[0].server.name, "io.example/server-19000");
+
+    let everything = index.search(&SchemaSearchQuery::default());
+    assert_eq!(everything.total, 20_000);
+    assert_eq!(everything.hits.len(), 50);
+    assert_eq!(everything.hits[0].server.name, "io.example/server-00000");
+}
+
+#[rstest]
+fn command_reindexes_when_store_changes(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+
+    assert_eq!(search_schema_store_command(text("weather")).unwrap().total, 0);
+
+    save_schema_store(&SchemaStore { servers: servers(), ..Default::default() }).unwrap();
+    assert_eq!(search_schema_store_command(text("weather")).unwrap().total, 3);
+
+    save_schema_store(&SchemaStore { servers: servers()[..1].to_vec(), ..Default::default() })
+        .unwrap();
+    assert_eq!(search_schema_store_command(text("weather")).unwrap().total, 1);
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs;
 
 use rstest::rstest;
@@ -7,6 +8,9 @@ use crate::mcp::registry::{load_schema_store, save_schema_store, SchemaStore};
 use crate::mcp::server_schema::{
     EnvironmentVariable, Icon, LocalTransport, Package, Repository, ServerSchema,
 };
+use crate::mcp::user_server::{OriginType, ServerOrigin, UserServer};
+use crate::mcp::{add_user_server_command, list_server_versions_command};
+use crate::mcp::{AgentServerEntry, BaseServerEntry, RemoteServerEntry};
 
 use super::fixtures::test_env;
 use super::EnvGuard;
@@ -57,6 +61,8 @@ fn create_test_server_schema() -> ServerSchema {
             registry_base_url: None,
         }],
         remotes: vec![],
+        registry_source: None,
+        registry_status: None,
     }
 }
 
@@ -78,6 +84,7 @@ fn save_and_load_schema_store(test_env: (TempDir, EnvGuard, std::sync::MutexGuar
     let store = SchemaStore {
         servers: vec![create_test_server_schema()],
         updated_at: Some("2025-01-01T00:00:00Z".to_string()),
+        ..Default::default()
     };
 
     save_schema_store(&store).unwrap();
@@ -100,7 +107,7 @@ fn save_schema_store_creates_parent_directories(
     let config_dir = temp_dir.path().join(".config").join("rain-mcp");
     assert!(!config_dir.exists());
 
-    let store = SchemaStore { servers: vec![create_test_server_schema()], updated_at: None };
+    let store = SchemaStore { servers: vec![create_test_server_schema()], ..Default::default() };
 
     save_schema_store(&store).unwrap();
 
@@ -114,7 +121,7 @@ fn schema_store_preserves_package_details(
 ) {
     let (_temp_dir, _env_guard, _lock) = test_env;
 
-    let store = SchemaStore { servers: vec![create_test_server_schema()], updated_at: None };
+    let store = SchemaStore { servers: vec![create_test_server_schema()], ..Default::default() };
 
     save_schema_store(&store).unwrap();
 
@@ -147,3 +154,72 @@ fn schema_store_handles_invalid_json(
     assert!(result.is_err());
     assert!(result.unwrap_err().contains("Failed to parse schema store"));
 }
+
+fn versioned(version: &str) -> ServerSchema {
+    ServerSchema { version: version.to_string(), ..create_test_server_schema() }
+}
+
+fn registry_origin(version: Option<&str>) -> ServerOrigin {
+    ServerOrigin {
+        origin_type: OriginType::Registry,
+        schema_name: Some("io.github.test/test-server".to_string()),
+        package_id: None,
+        version: version.map(str::to_string),
+    }
+}
+
+#[rstest]
+fn lists_versions_newest_first(test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>)) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    let store = SchemaStore {
+        servers: vec![versioned("2.0.0")],
+        history: HashMap::from([(
+            "io.github.test/test-server".to_string(),
+            vec![versioned("1.1.0"), versioned("1.0.0")],
+        )]),
+        ..Default::default()
+    };
+    save_schema_store(&store).unwrap();
+
+    let versions = list_server_versions_command("io.github.test/test-server".to_string()).unwrap();
+    let versions: Vec<_> = versions.iter().map(|s| s.version.as_str()).collect();
+    assert_eq!(versions, vec!["2.0.0", "1.1.0", "1.0.0"]);
+
+    assert!(list_server_versions_command("io.github.test/missing".to_string())
+        .unwrap_err()
+        .contains("not found"));
+
+    // History survives a save/load round trip
+    let loaded = load_schema_store().unwrap();
+    assert_eq!(loaded.schema_for_origin(&registry_origin(Some("1.1.0"))).unwrap().version, "1.1.0");
+    assert_eq!(loaded.schema_for_origin(&registry_origin(None)).unwrap().version, "2.0.0");
+    // A pinned version no longer in the store is not replaced by another one
+    assert!(loaded.schema_for_origin(&registry_origin(Some("0.1.0"))).is_none());
+}
+
+#[rstest]
+fn added_registry_servers_pin_the_current_version(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    save_schema_store(&SchemaStore { servers: vec![versioned("2.0.0")], ..Default::default() })
+        .unwrap();
+    let server = |origin: ServerOrigin| UserServer {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: "test".to_string(),
+        config: AgentServerEntry::Remote(RemoteServerEntry {
+            base: BaseServerEntry { timeout: None },
+            url: "https://example.com/mcp".to_string(),
+            headers: None,
+        }),
+        origin: Some(origin),
+        created_at: None,
+    };
+
+    let added = add_user_server_command(server(registry_origin(None))).unwrap();
+    assert_eq!(added.origin.unwrap().version.as_deref(), Some("2.0.0"));
+
+    // An explicit version is kept
+    let added = add_user_server_command(server(registry_origin(Some("1.0.0")))).unwrap();
+    assert_eq!(added.origin.unwrap().version.as_deref(), Some("1.0.0"));
+}
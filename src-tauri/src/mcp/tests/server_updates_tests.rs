@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::config::{load_app_config, save_app_config, AppConfig};
+use crate::mcp::registry::{save_schema_store, SchemaStore};
+use crate::mcp::server_schema::Package;
+use crate::mcp::server_schema::ServerSchema;
+use crate::mcp::server_updates::check_server_updates;
+use crate::mcp::user_server::{OriginType, ServerOrigin, UserServer};
+use crate::mcp::{
+    get_server_updates_command, package_config_command, upgrade_user_server_command,
+    AgentServerEntry, BaseServerEntry, LocalServerEntry,
+};
+
+use super::fixtures::test_env;
+use super::EnvGuard;
+
+fn version_1() -> ServerSchema {
+    serde_json::from_value(serde_json::json!({
+        "name": "io.acme/mcp",
+        "description": "Acme",
+        "version": "1.0.0",
+        "packages": [{
+            "registryType": "npm",
+            "identifier": "@acme/mcp",
+            "version": "1.0.0",
+            "transport": { "type": "stdio" },
+            "environmentVariables": [
+                { "name": "ACME_KEY", "isRequired": true, "isSecret": true },
+                { "name": "ACME_LEGACY" }
+            ],
+            "packageArguments": [{ "type": "named", "name": "--port", "value": "3000" }]
+        }]
+    }))
+    .unwrap()
+}
+
+fn version_2() -> ServerSchema {
+    serde_json::from_value(serde_json::json!({
+        "name": "io.acme/mcp",
+        "description": "Acme",
+        "version": "2.0.0",
+        "packages": [{
+            "registryType": "npm",
+            "identifier": "@acme/mcp",
+            "version": "2.0.0",
+            "transport": { "type": "stdio" },
+            "environmentVariables": [
+                { "name": "ACME_KEY", "isRequired": true, "isSecret": true },
+                { "name": "ACME_REGION", "isRequired": true },
+                { "name": "ACME_MODE", "isRequired": true, "default": "fast" }
+            ],
+            "packageArguments": [{ "type": "named", "name": "--port", "value": "8080" }]
+        }],
+        "remotes": [{ "type": "streamable-http", "url": "https://mcp.acme.com" }]
+    }))
+    .unwrap()
+}
+
+fn installed_server() -> UserServer {
+    UserServer {
+        id: "acme-id".to_string(),
+        name: "acme".to_string(),
+        config: AgentServerEntry::Local(LocalServerEntry {
+            base: BaseServerEntry { timeout: Some(30) },
+            command: "npx".to_string(),
+            args: Some(["-y", "@acme/mcp@1.0.0", "--port", "3000"].map(str::to_string).to_vec()),
+            env: Some(HashMap::from(
+                [("ACME_KEY", "k-1"), ("ACME_LEGACY", "x"), ("CUSTOM", "mine")]
+                    .map(|(k, v)| (k.to_string(), v.to_string())),
+            )),
+        }),
+        origin: Some(ServerOrigin {
+            origin_type: OriginType::Registry,
+            schema_name: Some("io.acme/mcp".to_string()),
+            package_id: None,
+            version: None,
+        }),
+        created_at: None,
+    }
+}
+
+fn refreshed_store() -> SchemaStore {
+    SchemaStore {
+        servers: vec![version_2()],
+        history: HashMap::from([("io.acme/mcp".to_string(), vec![version_1()])]),
+        ..Default::default()
+    }
+}
+
+fn setup() {
+    save_app_config(&AppConfig { user_servers: vec![installed_server()], ..Default::default() })
+        .unwrap();
+    save_schema_store(&refreshed_store()).unwrap();
+}
+
+#[rstest]
+fn reports_newer_versions_with_diff(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    setup();
+    let previous = SchemaStore { servers: vec![version_1()], ..Default::default() };
+
+    let updates = check_server_updates(&previous, &refreshed_store()).unwrap();
+
+    assert_eq!(updates.len(), 1);
+    let update = &updates[0];
+    assert_eq!(update.server_id, "acme-id");
+    assert_eq!(update.installed_version, "1.0.0");
+    assert_eq!(update.latest_version, "2.0.0");
+    let diff = update.diff.as_ref().unwrap();
+    assert_eq!(diff.new_required_env_vars, vec!["ACME_REGION"]);
+    assert_eq!(diff.removed_env_vars, vec!["ACME_LEGACY"]);
+    assert_eq!(diff.added_remotes, vec!["https://mcp.acme.com"]);
+    let change = &diff.changed_arguments[0];
+    assert_eq!(change.key, "--port");
+    assert_eq!(change.before.as_ref().unwrap().value.as_deref(), Some("3000"));
+    assert_eq!(change.after.as_ref().unwrap().value.as_deref(), Some("8080"));
+
+    // The version the config came from is recorded, so updates are still reported later
+    let origin = load_app_config().user_servers[0].origin.clone().unwrap();
+    assert_eq!(origin.version.as_deref(), Some("1.0.0"));
+    assert_eq!(get_server_updates_command().unwrap().len(), 1);
+}
+
+#[rstest]
+fn reports_unknown_diff_when_installed_version_was_pruned(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    setup();
+    set_installed_args(&["-y", "@acme/mcp@1.0.0", "--port", "3000"]);
+    let pruned = SchemaStore { servers: vec![version_2()], ..Default::default() };
+
+    let updates = check_server_updates(&pruned, &pruned).unwrap();
+
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0].installed_version, "1.0.0");
+    assert!(updates[0].diff.is_none());
+}
+
+#[rstest]
+fn upgrade_regenerates_config(test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>)) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    setup();
+    let mut config = load_app_config();
+    config.user_servers[0].origin.as_mut().unwrap().version = Some("1.0.0".to_string());
+    save_app_config(&config).unwrap();
+
+    let upgraded = upgrade_user_server_command("acme-id".to_string(), None).unwrap();
+
+    let AgentServerEntry::Local(local) = &upgraded.config else {
+        panic!("expected local server, got {:?}", upgraded.config);
+    };
+    assert_eq!(local.base.timeout, Some(30));
+    assert_eq!(local.command, "npx");
+    assert_eq!(local.args.as_deref().unwrap(), ["-y", "@acme/mcp@2.0.0", "--port", "8080"]);
+    let env = local.env.as_ref().unwrap();
+    assert_eq!(env.len(), 4);
+    assert_eq!(env["ACME_KEY"], "k-1");
+    assert_eq!(env["ACME_REGION"], "");
+    assert_eq!(env["ACME_MODE"], "fast");
+    assert_eq!(env["CUSTOM"], "mine");
+
+    let origin = upgraded.origin.as_ref().unwrap();
+    assert_eq!(origin.version.as_deref(), Some("2.0.0"));
+    assert_eq!(origin.package_id.as_deref(), Some("npm:@acme/mcp"));
+    assert_eq!(load_app_config().user_servers[0].config, upgraded.config);
+    assert!(get_server_updates_command().unwrap().is_empty());
+
+    // Rolling back to the older release works the same way
+    let rolled_back =
+        upgrade_user_server_command("acme-id".to_string(), Some("1.0.0".to_string())).unwrap();
+    let AgentServerEntry::Local(local) = &rolled_back.config else {
+        panic!("expected local server");
+    };
+    assert_eq!(local.args.as_deref().unwrap(), ["-y", "@acme/mcp@1.0.0", "--port", "3000"]);
+    assert!(!local.env.as_ref().unwrap().contains_key("ACME_REGION"));
+}
+
+fn set_installed_args(args: &[&str]) {
+    let mut config = load_app_config();
+    let server = &mut config.user_servers[0];
+    server.origin.as_mut().unwrap().version = Some("1.0.0".to_string());
+    if let AgentServerEntry::Local(local) = &mut server.config {
+        local.args = Some(args.iter().map(|a| a.to_string()).collect());
+    }
+    save_app_config(&config).unwrap();
+}
+
+#[rstest]
+fn upgrade_keeps_appended_arguments(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    setup();
+    set_installed_args(&["-y", "@acme/mcp@1.0.0", "--port", "3000", "--verbose"]);
+
+    let upgraded = upgrade_user_server_command("acme-id".to_string(), None).unwrap();
+
+    let AgentServerEntry::Local(local) = &upgraded.config else {
+        panic!("expected local server");
+    };
+    assert_eq!(
+        local.args.as_deref().unwrap(),
+        ["-y", "@acme/mcp@2.0.0", "--port", "8080", "--verbose"]
+    );
+}
+
+#[rstest]
+fn upgrade_refuses_edited_arguments(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    setup();
+    set_installed_args(&["-y", "@acme/mcp@1.0.0", "--port", "4000"]);
+
+    let error = upgrade_user_server_command("acme-id".to_string(), None).unwrap_err();
+    assert!(error.contains("edited by hand"), "unexpected error: {}", error);
+    let AgentServerEntry::Local(local) = &load_app_config().user_servers[0].config else {
+        panic!("expected local server");
+    };
+    assert_eq!(local.args.as_deref().unwrap(), ["-y", "@acme/mcp@1.0.0", "--port", "4000"]);
+}
+
+#[rstest]
+fn upgrade_accepts_arguments_generated_without_runtime_arguments(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    setup();
+    let with_registry = |mut schema: ServerSchema| {
+        schema.packages[0].runtime_arguments = vec![serde_json::from_value(serde_json::json!(
+            { "type": "named", "name": "--registry", "value": "https://npm.acme.com" }
+        ))
+        .unwrap()];
+        schema
+    };
+    save_schema_store(&SchemaStore {
+        servers: vec![with_registry(version_2())],
+        history: HashMap::from([("io.acme/mcp".to_string(), vec![with_registry(version_1())])]),
+        ..Default::default()
+    })
+    .unwrap();
+    // The add-server form used to leave runtime arguments out
+    set_installed_args(&["-y", "@acme/mcp@1.0.0", "--port", "3000", "--verbose"]);
+
+    let upgraded = upgrade_user_server_command("acme-id".to_string(), None).unwrap();
+
+    let AgentServerEntry::Local(local) = &upgraded.config else {
+        panic!("expected local server");
+    };
+    assert_eq!(
+        local.args.as_deref().unwrap(),
+        [
+            "-y",
+            "--registry",
+            "https://npm.acme.com",
+            "@acme/mcp@2.0.0",
+            "--port",
+            "8080",
+            "--verbose"
+        ]
+    );
+}
+
+#[rstest]
+#[case::npm(
+    serde_json::json!({
+        "registryType": "npm",
+        "identifier": "@acme/mcp",
+        "version": "1.0.0",
+        "transport": { "type": "stdio" },
+        "packageArguments": [
+            { "type": "named", "name": "--port", "value": "3000" },
+            { "type": "positional", "valueHint": "path", "value": "/data" }
+        ]
+    }),
+    "npx",
+    &["-y", "@acme/mcp@1.0.0", "--port", "3000", "/data"]
+)]
+#[case::oci(
+    serde_json::json!({
+        "registryType": "oci",
+        "identifier": "ghcr.io/acme/mcp",
+        "transport": { "type": "stdio" },
+        "runtimeArguments": [{ "type": "named", "name": "-e", "value": "ACME_KEY" }],
+        "environmentVariables": [{ "name": "ACME_KEY", "isRequired": true }]
+    }),
+    "docker",
+    &["run", "-i", "-e", "ACME_KEY", "ghcr.io/acme/mcp"]
+)]
+#[case::pypi_with_hint(
+    serde_json::json!({
+        "registryType": "pypi",
+        "runtimeHint": "uvx",
+        "identifier": "acme-mcp",
+        "version": "0.3.0",
+        "transport": { "type": "stdio" }
+    }),
+    "uvx",
+    &["acme-mcp@0.3.0"]
+)]
+fn package_config_builds_commands(
+    #[case] package: serde_json::Value,
+    #[case] command: &str,
+    #[case] args: &[&str],
+) {
+    let package: Package = serde_json::from_value(package).unwrap();
+    let values = HashMap::from([("ACME_KEY".to_string(), "k-1".to_string())]);
+
+    let AgentServerEntry::Local(local) = package_config_command(package, values.clone()) else {
+        panic!("expected local server");
+    };
+    assert_eq!(local.command, command);
+    assert_eq!(local.args.as_deref().unwrap(), args);
+    assert_eq!(local.env, Some(values));
+}
+
+#[rstest]
+fn package_config_serves_http_packages_as_remotes() {
+    let package: Package = serde_json::from_value(serde_json::json!({
+        "registryType": "npm",
+        "identifier": "@acme/mcp",
+        "transport": { "type": "streamable-http", "url": "http://localhost:3000/mcp" }
+    }))
+    .unwrap();
+
+    let config = package_config_command(package, HashMap::new());
+
+    let AgentServerEntry::Remote(remote) = config else {
+        panic!("expected remote server");
+    };
+    assert_eq!(remote.url, "http://localhost:3000/mcp");
+    assert_eq!(remote.headers, None);
+}
+
+#[rstest]
+fn upgrade_rejects_unknown_targets(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    setup();
+
+    let error =
+        upgrade_user_server_command("acme-id".to_string(), Some("9.9.9".to_string())).unwrap_err();
+    assert!(error.contains("Version 9.9.9"), "unexpected error: {}", error);
+    assert!(upgrade_user_server_command("missing".to_string(), None)
+        .unwrap_err()
+        .contains("not found"));
+
+    let mut config = load_app_config();
+    config.user_servers[0].origin = None;
+    save_app_config(&config).unwrap();
+    assert!(upgrade_user_server_command("acme-id".to_string(), None)
+        .unwrap_err()
+        .contains("not added from the registry"));
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use rstest::rstest;
+
+use crate::mcp::parse_config_snippet_command;
+use crate::mcp::snippet_import::{Confidence, SnippetCandidate, SnippetFormat};
+use crate::mcp::{AgentServerEntry, LocalServerEntry, RemoteServerEntry};
+
+fn parse(snippet: &str) -> Vec<SnippetCandidate> {
+    let mut candidates = parse_config_snippet_command(snippet.to_string()).unwrap();
+    candidates.sort_by(|a, b| a.server.name.cmp(&b.server.name));
+    candidates
+}
+
+fn local(candidate: &SnippetCandidate) -> &LocalServerEntry {
+    match &candidate.server.config {
+        AgentServerEntry::Local(local) => local,
+        other => panic!("expected local server, got {:?}", other),
+    }
+}
+
+fn remote(candidate: &SnippetCandidate) -> &RemoteServerEntry {
+    match &candidate.server.config {
+        AgentServerEntry::Remote(remote) => remote,
+        other => panic!("expected remote server, got {:?}", other),
+    }
+}
+
+fn args(candidate: &SnippetCandidate) -> Vec<&str> {
+    local(candidate).args.iter().flatten().map(String::as_str).collect()
+}
+
+#[test]
+fn test_mcp_servers_block_in_code_fence() {
+    let snippet = r#"
+```json
+{
+  "mcpServers": {
+    "github": {
+      "command": "npx",
+      "args": ["-y", "@modelcontextprotocol/server-github"],
+      "env": { "GITHUB_PERSONAL_ACCESS_TOKEN": "<token>" }
+    },
+    "linear": { "url": "https://mcp.linear.app/sse" }
+  }
+}
+```
+"#;
+
+    let candidates = parse(snippet);
+
+    assert_eq!(candidates.len(), 2);
+    assert!(candidates.iter().all(|c| c.format == SnippetFormat::McpServersJson));
+    assert!(candidates.iter().all(|c| c.confidence == Confidence::High));
+    assert_eq!(candidates[0].server.name, "github");
+    assert_eq!(
+        local(&candidates[0]).env.as_ref().unwrap()["GITHUB_PERSONAL_ACCESS_TOKEN"],
+        "<token>"
+    );
+    assert_eq!(remote(&candidates[1]).url, "https://mcp.linear.app/sse");
+}
+
+#[test]
+fn test_named_entry_fragment_without_braces() {
+    let snippet = r#""context7": {
+      "command": "npx",
+      "args": ["-y", "@upstash/context7-mcp"]
+    },"#;
+
+    let candidates = parse(snippet);
+
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].format, SnippetFormat::NamedEntries);
+    assert_eq!(candidates[0].confidence, Confidence::Medium);
+    assert_eq!(candidates[0].server.name, "context7");
+}
+
+#[test]
+fn test_bare_entry_derives_name() {
+    let snippet = r#"{ "command": "npx", "args": ["-y", "@upstash/context7-mcp@latest"] }"#;
+
+    let candidates = parse(snippet);
+
+    assert_eq!(candidates[0].format, SnippetFormat::BareEntry);
+    assert_eq!(candidates[0].server.name, "context7");
+    assert_eq!(args(&candidates[0]), vec!["-y", "@upstash/context7-mcp@latest"]);
+}
+
+#[test]
+fn test_opencode_block() {
+    let snippet = r#"{
+  // OpenCode config
+  "mcp": {
+    "fetch": {
+      "type": "local",
+      "command": ["uvx", "mcp-server-fetch"],
+      "environment": { "LOG": "1" }
+    }
+  }
+}"#;
+
+    let candidates = parse(snippet);
+
+    assert_eq!(candidates[0].format, SnippetFormat::OpenCodeJson);
+    assert_eq!(local(&candidates[0]).command, "uvx");
+    assert_eq!(args(&candidates[0]), vec!["mcp-server-fetch"]);
+    assert_eq!(local(&candidates[0]).env.as_ref().unwrap()["LOG"], "1");
+}
+
+#[test]
+fn test_codex_toml() {
+    let snippet = r#"
+[mcp_servers.context7]
+command = "npx"
+args = ["-y", "@upstash/context7-mcp"]
+
+[mcp_servers.figma]
+url = "https://mcp.figma.com/mcp"
+"#;
+
+    let candidates = parse(snippet);
+
+    assert_eq!(candidates.len(), 2);
+    assert!(candidates.iter().all(|c| c.format == SnippetFormat::CodexToml));
+    assert_eq!(candidates[0].server.name, "context7");
+    assert_eq!(remote(&candidates[1]).url, "https://mcp.figma.com/mcp");
+}
+
+#[test]
+fn test_shell_command_with_env_and_quotes() {
+    let snippet = "$ GITHUB_TOKEN=abc npx -y @foo/mcp \\\n  --token 'X Y' --name \"a\\\"b\"";
+
+    let candidates = parse(snippet);
+
+    assert_eq!(candidates.len(), 1);
+    let candidate = &candidates[0];
+    assert_eq!(candidate.format, SnippetFormat::ShellCommand);
+    assert_eq!(candidate.confidence, Confidence::Medium);
+    assert_eq!(candidate.server.name, "foo");
+    assert_eq!(local(candidate).command, "npx");
+    assert_eq!(args(candidate), vec!["-y", "@foo/mcp", "--token", "X Y", "--name", "a\"b"]);
+    assert_eq!(
+        local(candidate).env,
+        Some(HashMap::from([("GITHUB_TOKEN".to_string(), "abc".to_string())]))
+    );
+}
+
+#[rstest]
+#[case::uvx("uvx mcp-server-fetch", "fetch")]
+#[case::scoped("npx -y @modelcontextprotocol/server-github", "github")]
+#[case::docker("docker run -i --rm -e TOKEN ghcr.io/github/github-mcp-server", "github")]
+#[case::pnpm("pnpm dlx @upstash/context7-mcp", "context7")]
+#[case::unknown("./my-server --port 3000", "my-server")]
+fn test_shell_command_names(#[case] snippet: &str, #[case] expected: &str) {
+    assert_eq!(parse(snippet)[0].server.name, expected);
+}
+
+#[rstest]
+#[case::bool("DEBUG=true")]
+#[case::quoted("GITHUB_TOKEN='abc'")]
+fn test_toml_without_servers_falls_back_to_shell(#[case] snippet: &str) {
+    // Valid TOML, but without server tables it is read as a shell command
+    let error = parse_config_snippet_command(snippet.to_string()).unwrap_err();
+    assert_eq!(error, "Could not detect the snippet format");
+}
+
+#[test]
+fn test_unknown_launcher_has_low_confidence() {
+    let candidate = &parse("./my-server --port 3000")[0];
+
+    assert_eq!(candidate.confidence, Confidence::Low);
+    assert!(candidate.note.contains("not a known MCP launcher"));
+}
+
+#[test]
+fn test_url_snippet() {
+    let candidate = &parse("https://mcp.linear.app/sse")[0];
+
+    assert_eq!(candidate.format, SnippetFormat::Url);
+    assert_eq!(candidate.server.name, "linear");
+    assert_eq!(remote(candidate).url, "https://mcp.linear.app/sse");
+}
+
+#[rstest]
+#[case::empty("   ", "empty")]
+#[case::json_without_servers(r#"{"theme": "dark"}"#, "does not contain")]
+#[case::invalid_entry(r#"{"mcpServers": {"x": {"args": []}}}"#, "Failed to parse server 'x'")]
+#[case::unterminated_quote("npx 'oops", "Unterminated")]
+fn test_invalid_snippets(#[case] snippet: &str, #[case] expected: &str) {
+    let error = parse_config_snippet_command(snippet.to_string()).unwrap_err();
+    assert!(error.contains(expected), "unexpected error: {}", error);
+}
@@ -0,0 +1,133 @@
+use std::fs;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::mcp::agent_config::read_agent_config;
+use crate::mcp::transfer::{ConflictStrategy, TransferStatus};
+use crate::mcp::{copy_server_to_agents_command, move_server_to_agents_command, AgentType};
+
+use super::fixtures::{test_env, CLAUDE_CODE_CONFIG_JSON, OPENAI_CODEX_CONFIG_TOML};
+use super::EnvGuard;
+
+fn write_claude_and_codex(temp_dir: &TempDir) {
+    fs::write(temp_dir.path().join(".claude.json"), CLAUDE_CODE_CONFIG_JSON).unwrap();
+    let codex_dir = temp_dir.path().join(".codex");
+    fs::create_dir_all(&codex_dir).unwrap();
+    fs::write(codex_dir.join("config.toml"), OPENAI_CODEX_CONFIG_TOML).unwrap();
+}
+
+#[rstest]
+fn copy_server_translates_dialect(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    write_claude_and_codex(&temp_dir);
+
+    let report = copy_server_to_agents_command(
+        "claude-code".to_string(),
+        "server-name".to_string(),
+        vec!["codex".to_string(), "cursor".to_string()],
+        ConflictStrategy::Skip,
+    )
+    .unwrap();
+
+    assert!(!report.removed_from_source);
+    assert_eq!(report.results.len(), 2);
+    assert!(report.results.iter().all(|r| r.status == TransferStatus::Added));
+
+    let source = read_agent_config(AgentType::ClaudeCode).unwrap();
+    let codex = read_agent_config(AgentType::OpenAiCodex).unwrap();
+    let cursor = read_agent_config(AgentType::Cursor).unwrap();
+    assert_eq!(codex.servers.get("server-name"), source.servers.get("server-name"));
+    assert_eq!(cursor.servers.get("server-name"), source.servers.get("server-name"));
+
+    let codex_raw = fs::read_to_string(temp_dir.path().join(".codex/config.toml")).unwrap();
+    assert!(codex_raw.contains("[mcp_servers.server-name]"));
+    assert!(codex_raw.contains("[mcp_servers.figma]"));
+}
+
+#[rstest]
+fn copy_server_resolves_conflicts(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    write_claude_and_codex(&temp_dir);
+
+    let copy = |strategy| {
+        copy_server_to_agents_command(
+            "codex".to_string(),
+            "figma".to_string(),
+            vec!["claude-code".to_string()],
+            strategy,
+        )
+        .unwrap()
+    };
+
+    assert_eq!(copy(ConflictStrategy::Skip).results[0].status, TransferStatus::Added);
+    assert_eq!(copy(ConflictStrategy::Skip).results[0].status, TransferStatus::Skipped);
+    assert_eq!(copy(ConflictStrategy::Overwrite).results[0].status, TransferStatus::Overwritten);
+
+    let renamed = copy(ConflictStrategy::Rename);
+    assert_eq!(renamed.results[0].status, TransferStatus::Renamed);
+    assert_eq!(renamed.results[0].server_name, "figma-2");
+    assert_eq!(copy(ConflictStrategy::Rename).results[0].server_name, "figma-3");
+
+    let claude = read_agent_config(AgentType::ClaudeCode).unwrap();
+    assert!(claude.servers.contains_key("figma"));
+    assert!(claude.servers.contains_key("figma-2"));
+    assert!(claude.servers.contains_key("figma-3"));
+}
+
+#[rstest]
+fn move_server_removes_source_only_when_delivered(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    write_claude_and_codex(&temp_dir);
+
+    // context7 exists in codex only; moving to claude succeeds
+    let report = move_server_to_agents_command(
+        "codex".to_string(),
+        "context7".to_string(),
+        vec!["claude-code".to_string()],
+        ConflictStrategy::Skip,
+    )
+    .unwrap();
+    assert!(report.removed_from_source);
+    assert!(!read_agent_config(AgentType::OpenAiCodex).unwrap().servers.contains_key("context7"));
+    assert!(read_agent_config(AgentType::ClaudeCode).unwrap().servers.contains_key("context7"));
+
+    // Moving back with a skipped target keeps the source entry
+    copy_server_to_agents_command(
+        "claude-code".to_string(),
+        "remote".to_string(),
+        vec!["codex".to_string()],
+        ConflictStrategy::Skip,
+    )
+    .unwrap();
+    let report = move_server_to_agents_command(
+        "claude-code".to_string(),
+        "remote".to_string(),
+        vec!["codex".to_string()],
+        ConflictStrategy::Skip,
+    )
+    .unwrap();
+    assert_eq!(report.results[0].status, TransferStatus::Skipped);
+    assert!(!report.removed_from_source);
+    assert!(read_agent_config(AgentType::ClaudeCode).unwrap().servers.contains_key("remote"));
+}
+
+#[rstest]
+fn copy_missing_server_fails(test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>)) {
+    let (temp_dir, _env_guard, _lock) = test_env;
+    write_claude_and_codex(&temp_dir);
+
+    let result = copy_server_to_agents_command(
+        "claude-code".to_string(),
+        "missing".to_string(),
+        vec!["codex".to_string()],
+        ConflictStrategy::Skip,
+    );
+    assert!(result.unwrap_err().contains("not found"));
+}
@@ -27,6 +27,7 @@ fn create_test_user_server(id: &str, name: &str) -> UserServer {
             origin_type: OriginType::Registry,
             schema_name: Some("io.github.test/test-server".to_string()),
             package_id: Some("npm:@test/mcp-server".to_string()),
+            version: None,
         }),
         created_at: Some("2025-01-01T00:00:00Z".to_string()),
     }
@@ -146,6 +147,7 @@ fn user_server_with_custom_command(
             origin_type: OriginType::Custom,
             schema_name: None,
             package_id: None,
+            version: None,
         }),
         created_at: None,
     };
@@ -181,6 +183,7 @@ fn user_server_with_remote_url(test_env: (TempDir, EnvGuard, std::sync::MutexGua
             origin_type: OriginType::Custom,
             schema_name: None,
             package_id: None,
+            version: None,
         }),
         created_at: None,
     };
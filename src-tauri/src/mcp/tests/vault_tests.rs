@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::fs;
+
+use rstest::rstest;
+use tempfile::TempDir;
+
+use crate::config::{get_secrets_env_path, get_vault_path, load_app_config, save_app_config};
+use crate::mcp::agent_config::read_agent_config;
+use crate::mcp::linked_servers::LinkState;
+use crate::mcp::secret_scan::load_managed_env;
+use crate::mcp::user_server::UserServer;
+use crate::mcp::vault::{secret_references, AgentEnvRequirement};
+use crate::mcp::{
+    create_vault_command, delete_vault_secret_command, deploy_user_server_command,
+    get_secret_exports_command, get_user_server_links_command, get_vault_status_command,
+    lock_vault_command, set_vault_secret_command, sync_user_server_command, unlock_vault_command,
+    AgentServerEntry, AgentType, BaseServerEntry, LocalServerEntry, RemoteServerEntry,
+};
+
+use super::fixtures::test_env;
+use super::EnvGuard;
+
+const PASSPHRASE: &str = "correct horse battery";
+
+fn save_servers() {
+    let mut app_config = load_app_config();
+    app_config.user_servers.push(UserServer {
+        id: "github".to_string(),
+        name: "github".to_string(),
+        config: AgentServerEntry::Local(LocalServerEntry {
+            base: BaseServerEntry { timeout: None },
+            command: "npx".to_string(),
+            args: Some(vec!["-y".to_string(), "@modelcontextprotocol/server-github".to_string()]),
+            env: Some(HashMap::from([(
+                "GITHUB_PERSONAL_ACCESS_TOKEN".to_string(),
+                "${secret:GITHUB_TOKEN}".to_string(),
+            )])),
+        }),
+        origin: None,
+        created_at: None,
+    });
+    app_config.user_servers.push(UserServer {
+        id: "jira".to_string(),
+        name: "jira".to_string(),
+        config: AgentServerEntry::Remote(RemoteServerEntry {
+            base: BaseServerEntry { timeout: None },
+            url: "https://jira.example.com/mcp".to_string(),
+            headers: Some(HashMap::from([(
+                "Authorization".to_string(),
+                "Bearer ${secret:JIRA_TOKEN}".to_string(),
+            )])),
+        }),
+        origin: None,
+        created_at: None,
+    });
+    save_app_config(&app_config).unwrap();
+}
+
+fn deployed_env(agent: AgentType, name: &str) -> HashMap<String, String> {
+    match read_agent_config(agent).unwrap().servers.remove(name).unwrap() {
+        AgentServerEntry::Local(local) => local.env.unwrap(),
+        other => panic!("expected local server, got {:?}", other),
+    }
+}
+
+fn deployed_headers(agent: AgentType, name: &str) -> HashMap<String, String> {
+    match read_agent_config(agent).unwrap().servers.remove(name).unwrap() {
+        AgentServerEntry::Remote(remote) => remote.headers.unwrap(),
+        other => panic!("expected remote server, got {:?}", other),
+    }
+}
+
+#[rstest]
+fn create_lock_and_unlock_vault(test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>)) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+
+    assert!(!get_vault_status_command().exists);
+    let status = create_vault_command(PASSPHRASE.to_string()).unwrap();
+    assert!(status.exists && status.unlocked);
+
+    set_vault_secret_command("GITHUB_TOKEN".to_string(), "ghp_plaintext".to_string()).unwrap();
+    assert_eq!(get_vault_status_command().secret_names, vec!["GITHUB_TOKEN"]);
+
+    let content = fs::read_to_string(get_vault_path().unwrap()).unwrap();
+    assert!(!content.contains("ghp_plaintext"));
+    assert!(!content.contains("GITHUB_TOKEN"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(get_vault_path().unwrap()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    lock_vault_command();
+    let status = get_vault_status_command();
+    assert!(status.exists && !status.unlocked);
+    assert!(status.secret_names.is_empty());
+    assert!(set_vault_secret_command("X".to_string(), "y".to_string())
+        .unwrap_err()
+        .contains("locked"));
+
+    let error = unlock_vault_command("wrong passphrase".to_string()).unwrap_err();
+    assert!(error.contains("Incorrect passphrase"));
+
+    let status = unlock_vault_command(PASSPHRASE.to_string()).unwrap();
+    assert_eq!(status.secret_names, vec!["GITHUB_TOKEN"]);
+}
+
+#[rstest]
+fn vault_rejects_bad_input(test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>)) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+
+    assert!(unlock_vault_command(PASSPHRASE.to_string()).unwrap_err().contains("does not exist"));
+    assert!(create_vault_command("short".to_string()).unwrap_err().contains("at least"));
+
+    create_vault_command(PASSPHRASE.to_string()).unwrap();
+    assert!(create_vault_command(PASSPHRASE.to_string()).unwrap_err().contains("already exists"));
+    assert!(set_vault_secret_command("not-valid".to_string(), "x".to_string())
+        .unwrap_err()
+        .contains("Invalid secret name"));
+    assert!(delete_vault_secret_command("MISSING".to_string()).unwrap_err().contains("not found"));
+
+    set_vault_secret_command("TOKEN".to_string(), "x".to_string()).unwrap();
+    delete_vault_secret_command("TOKEN".to_string()).unwrap();
+    assert!(get_vault_status_command().secret_names.is_empty());
+}
+
+#[test]
+fn finds_secret_references() {
+    assert_eq!(
+        secret_references("Bearer ${secret:A} ${secret:B_2} ${env:C} ${secret:unterminated"),
+        vec!["A", "B_2"]
+    );
+}
+
+#[rstest]
+fn deploy_resolves_secrets_for_agents_without_interpolation(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    save_servers();
+    create_vault_command(PASSPHRASE.to_string()).unwrap();
+    set_vault_secret_command("GITHUB_TOKEN".to_string(), "ghp_123".to_string()).unwrap();
+    set_vault_secret_command("JIRA_TOKEN".to_string(), "jira-456".to_string()).unwrap();
+
+    let report =
+        deploy_user_server_command("github".to_string(), vec!["kiro".to_string()], None).unwrap();
+    assert!(report.committed);
+    deploy_user_server_command("jira".to_string(), vec!["kiro".to_string()], None).unwrap();
+
+    assert_eq!(deployed_env(AgentType::Kiro, "github")["GITHUB_PERSONAL_ACCESS_TOKEN"], "ghp_123");
+    assert_eq!(deployed_headers(AgentType::Kiro, "jira")["Authorization"], "Bearer jira-456");
+
+    // The library keeps the reference, and the resolved copy counts as in sync
+    let app_config = load_app_config();
+    match &app_config.user_servers[0].config {
+        AgentServerEntry::Local(local) => assert_eq!(
+            local.env.as_ref().unwrap()["GITHUB_PERSONAL_ACCESS_TOKEN"],
+            "${secret:GITHUB_TOKEN}"
+        ),
+        other => panic!("expected local server, got {:?}", other),
+    }
+    let links = get_user_server_links_command("github".to_string()).unwrap();
+    assert_eq!(links[0].state, LinkState::InSync);
+}
+
+#[rstest]
+fn links_are_unknown_while_the_vault_is_locked(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    save_servers();
+    create_vault_command(PASSPHRASE.to_string()).unwrap();
+    set_vault_secret_command("GITHUB_TOKEN".to_string(), "ghp_123".to_string()).unwrap();
+    deploy_user_server_command("github".to_string(), vec!["kiro".to_string()], None).unwrap();
+
+    // The resolved copy cannot be compared, so it is neither in sync nor hand edited
+    lock_vault_command();
+    let links = get_user_server_links_command("github".to_string()).unwrap();
+    assert_eq!(links[0].state, LinkState::Unknown);
+    assert!(links[0].error.as_ref().unwrap().contains("locked"));
+
+    let results = sync_user_server_command("github".to_string(), true).unwrap();
+    assert_eq!(results[0].state, LinkState::Unknown);
+    assert!(!results[0].synced);
+    assert_eq!(deployed_env(AgentType::Kiro, "github")["GITHUB_PERSONAL_ACCESS_TOKEN"], "ghp_123");
+
+    unlock_vault_command(PASSPHRASE.to_string()).unwrap();
+    let links = get_user_server_links_command("github".to_string()).unwrap();
+    assert_eq!(links[0].state, LinkState::InSync);
+}
+
+#[rstest]
+fn deploy_fails_when_vault_is_locked_or_secret_missing(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    save_servers();
+
+    let report =
+        deploy_user_server_command("github".to_string(), vec!["kiro".to_string()], None).unwrap();
+    assert!(!report.committed);
+    assert!(report.agents[0].error.as_ref().unwrap().contains("locked"));
+
+    create_vault_command(PASSPHRASE.to_string()).unwrap();
+    let report =
+        deploy_user_server_command("github".to_string(), vec!["kiro".to_string()], None).unwrap();
+    assert!(!report.committed);
+    assert!(report.agents[0].error.as_ref().unwrap().contains("'GITHUB_TOKEN' not found"));
+}
+
+#[rstest]
+fn deploy_exports_secrets_for_agents_started_from_a_shell(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    save_servers();
+    create_vault_command(PASSPHRASE.to_string()).unwrap();
+    set_vault_secret_command("GITHUB_TOKEN".to_string(), "ghp_123".to_string()).unwrap();
+    set_vault_secret_command("JIRA_TOKEN".to_string(), "jira-456".to_string()).unwrap();
+
+    let agents = vec!["cursor".to_string(), "claude-code".to_string()];
+    assert!(
+        deploy_user_server_command("github".to_string(), agents.clone(), None).unwrap().committed
+    );
+    assert!(deploy_user_server_command("jira".to_string(), agents, None).unwrap().committed);
+
+    // Cursor is a GUI app and never reads the shell profile, so it gets the values inline
+    assert_eq!(
+        deployed_env(AgentType::Cursor, "github")["GITHUB_PERSONAL_ACCESS_TOKEN"],
+        "ghp_123"
+    );
+    assert_eq!(
+        deployed_env(AgentType::ClaudeCode, "github")["GITHUB_PERSONAL_ACCESS_TOKEN"],
+        "${GITHUB_TOKEN}"
+    );
+    assert_eq!(
+        deployed_headers(AgentType::ClaudeCode, "jira")["Authorization"],
+        "Bearer ${JIRA_TOKEN}"
+    );
+
+    let exports = get_secret_exports_command().unwrap();
+    assert_eq!(exports.env_file, get_secrets_env_path().unwrap());
+    assert_eq!(
+        exports.agents,
+        vec![AgentEnvRequirement {
+            agent_type: AgentType::ClaudeCode,
+            variables: vec!["GITHUB_TOKEN".to_string(), "JIRA_TOKEN".to_string()],
+        }]
+    );
+    let env = load_managed_env(&exports.env_file).unwrap();
+    assert_eq!(env["GITHUB_TOKEN"], "ghp_123");
+    assert_eq!(env["JIRA_TOKEN"], "jira-456");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&exports.env_file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let links = get_user_server_links_command("jira".to_string()).unwrap();
+    assert!(links.iter().all(|l| l.state == LinkState::InSync));
+
+    // Exported values still reach shell agents while the vault is locked
+    lock_vault_command();
+    let report =
+        deploy_user_server_command("github".to_string(), vec!["gemini-cli".to_string()], None)
+            .unwrap();
+    assert!(report.committed);
+
+    // Changing a secret updates its exported copy
+    unlock_vault_command(PASSPHRASE.to_string()).unwrap();
+    set_vault_secret_command("GITHUB_TOKEN".to_string(), "ghp_789".to_string()).unwrap();
+    assert_eq!(load_managed_env(&exports.env_file).unwrap()["GITHUB_TOKEN"], "ghp_789");
+}
+
+#[rstest]
+fn deploy_to_shell_agents_needs_the_vault_until_exported(
+    test_env: (TempDir, EnvGuard, std::sync::MutexGuard<'static, ()>),
+) {
+    let (_temp_dir, _env_guard, _lock) = test_env;
+    save_servers();
+
+    let report =
+        deploy_user_server_command("github".to_string(), vec!["claude-code".to_string()], None)
+            .unwrap();
+    assert!(!report.committed);
+    assert!(report.agents[0].error.as_ref().unwrap().contains("Cannot export secrets"));
+    assert!(!get_secrets_env_path().unwrap().exists());
+}
@@ -0,0 +1,168 @@
+//! All-or-nothing writes across several agent configs
+//!
+//! New file contents are rendered through the adapters up front, so serialization or
+//! parse problems surface before anything is written. On commit the originals are
+//! restored if any write fails.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use log::{error, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::agent_adapters;
+use super::journal::{self, JournalChange};
+use super::{AgentServers, AgentType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum WriteStatus {
+    /// New content is on disk
+    Written,
+    /// Was written, then restored after a later failure
+    RolledBack,
+    /// This write failed
+    Failed,
+    /// Not attempted because an earlier step failed
+    NotAttempted,
+}
+
+/// Outcome of a transaction for one agent
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentWriteOutcome {
+    pub agent_type: AgentType,
+    pub status: WriteStatus,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+struct StagedWrite {
+    agent: AgentType,
+    path: PathBuf,
+    /// Content before the transaction (`None` if the file did not exist)
+    original: Option<String>,
+    /// Servers before the transaction, for the journal
+    servers_before: AgentServers,
+    content: String,
+}
+
+#[derive(Default)]
+pub struct AgentConfigTransaction {
+    staged: Vec<StagedWrite>,
+    unjournaled: bool,
+}
+
+impl AgentConfigTransaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Commit without a journal entry, for writes whose old configs must not be kept
+    pub fn without_journal(mut self) -> Self {
+        self.unjournaled = true;
+        self
+    }
+
+    /// Render `config` for `agent` and stage it, replacing an earlier stage for the agent
+    pub fn stage(&mut self, agent: AgentType, config: AgentServers) -> Result<(), String> {
+        let adapter = agent_adapters::get_adapter(&agent)?;
+        let path = adapter.config_path()?;
+        let original = if path.exists() {
+            Some(fs::read_to_string(&path).map_err(|e| format!("{}: {}", agent, e))?)
+        } else {
+            None
+        };
+        let servers_before = match original {
+            Some(_) => adapter.read_config().map_err(|e| format!("{}: {}", agent, e))?,
+            None => AgentServers { servers: HashMap::new() },
+        };
+        let content = adapter.render_config(config).map_err(|e| format!("{}: {}", agent, e))?;
+
+        self.staged.retain(|w| w.agent != agent);
+        self.staged.push(StagedWrite { agent, path, original, servers_before, content });
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.staged.is_empty()
+    }
+
+    /// Write every staged config, restoring the originals if any write fails
+    pub fn commit(self) -> Result<(), String> {
+        let outcomes = self.commit_with_report();
+        match outcomes.into_iter().find(|o| o.status == WriteStatus::Failed) {
+            Some(failed) => Err(format!(
+                "Failed to write {} config: {}",
+                failed.agent_type,
+                failed.error.unwrap_or_default()
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Like `commit`, but reports what happened to every staged agent
+    pub fn commit_with_report(self) -> Vec<AgentWriteOutcome> {
+        let mut outcomes: Vec<AgentWriteOutcome> = self
+            .staged
+            .iter()
+            .map(|w| AgentWriteOutcome {
+                agent_type: w.agent.clone(),
+                status: WriteStatus::NotAttempted,
+                error: None,
+            })
+            .collect();
+
+        for (index, write) in self.staged.iter().enumerate() {
+            if let Err(e) = write_file(&write.path, &write.content) {
+                error!("Failed to write {} config, rolling back: {}", write.agent, e);
+                rollback(&self.staged[..=index]);
+                for outcome in &mut outcomes[..index] {
+                    outcome.status = WriteStatus::RolledBack;
+                }
+                outcomes[index].status = WriteStatus::Failed;
+                outcomes[index].error = Some(e);
+                return outcomes;
+            }
+            outcomes[index].status = WriteStatus::Written;
+        }
+
+        if self.unjournaled {
+            return outcomes;
+        }
+        let agents: Vec<String> = self.staged.iter().map(|w| w.agent.to_string()).collect();
+        let changes = self
+            .staged
+            .into_iter()
+            .filter_map(|w| {
+                let after = agent_adapters::get_adapter(&w.agent).ok()?.read_config().ok()?;
+                Some(JournalChange::agent_config(w.agent, &w.servers_before, &after))
+            })
+            .collect();
+        journal::record(format!("Update {} config", agents.join(", ")), changes);
+        outcomes
+    }
+}
+
+fn write_file(path: &PathBuf, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+fn rollback(written: &[StagedWrite]) {
+    for write in written {
+        let restored = match &write.original {
+            Some(original) => write_file(&write.path, original),
+            None if write.path.exists() => fs::remove_file(&write.path).map_err(|e| e.to_string()),
+            None => Ok(()),
+        };
+        if let Err(e) = restored {
+            warn!("Failed to restore {} config {:?}: {}", write.agent, write.path, e);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Copy or move servers directly between agents
+//!
+//! The source entry is read through the source adapter (normalized form) and written
+//! through each target adapter, so dialect differences are handled by the adapters.
+
+use super::agent_config::{read_agent_config, read_agent_config_or_empty, save_agent_config};
+use super::{AgentServerEntry, AgentServers, AgentType};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// What to do when the target agent already has a server with the same name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    /// Leave the target's server untouched
+    Skip,
+    /// Replace the target's server
+    Overwrite,
+    /// Add under a free name (`name-2`, `name-3`, ...)
+    Rename,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferStatus {
+    Added,
+    Overwritten,
+    Renamed,
+    Skipped,
+    Failed,
+}
+
+/// Outcome for a single target agent
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferResult {
+    pub agent_type: AgentType,
+    pub status: TransferStatus,
+
+    /// Name the server has in the target config (differs from the source on rename)
+    pub server_name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferReport {
+    pub source: AgentType,
+    pub server_name: String,
+    pub results: Vec<TransferResult>,
+
+    /// Whether the server was removed from the source agent (move only)
+    pub removed_from_source: bool,
+}
+
+/// Copy a server from `source` into every target agent
+pub fn copy_server(
+    source: AgentType,
+    server_name: &str,
+    targets: Vec<AgentType>,
+    strategy: ConflictStrategy,
+) -> Result<TransferReport, String> {
+    transfer_server(source, server_name, targets, strategy, false)
+}
+
+/// Copy a server into every target agent, then remove it from `source`
+///
+/// The source entry is only removed when every target received the server.
+pub fn move_server(
+    source: AgentType,
+    server_name: &str,
+    targets: Vec<AgentType>,
+    strategy: ConflictStrategy,
+) -> Result<TransferReport, String> {
+    transfer_server(source, server_name, targets, strategy, true)
+}
+
+fn transfer_server(
+    source: AgentType,
+    server_name: &str,
+    targets: Vec<AgentType>,
+    strategy: ConflictStrategy,
+    remove_source: bool,
+) -> Result<TransferReport, String> {
+    let mut source_config = read_agent_config(source.clone())?;
+    let entry = source_config
+        .servers
+        .get(server_name)
+        .cloned()
+        .ok_or_else(|| format!("Server '{}' not found in {}", server_name, source))?;
+
+    let results: Vec<TransferResult> = targets
+        .into_iter()
+        .filter(|target| *target != source)
+        .map(|target| transfer_to_target(target, server_name, &entry, strategy))
+        .collect();
+
+    let all_delivered = !results.is_empty()
+        && results
+            .iter()
+            .all(|r| !matches!(r.status, TransferStatus::Failed | TransferStatus::Skipped));
+
+    let mut removed_from_source = false;
+    if remove_source && all_delivered {
+        source_config.servers.remove(server_name);
+        save_agent_config(source.clone(), source_config)?;
+        removed_from_source = true;
+    }
+
+    Ok(TransferReport {
+        source,
+        server_name: server_name.to_string(),
+        results,
+        removed_from_source,
+    })
+}
+
+fn transfer_to_target(
+    target: AgentType,
+    server_name: &str,
+    entry: &AgentServerEntry,
+    strategy: ConflictStrategy,
+) -> TransferResult {
+    let mut result = TransferResult {
+        agent_type: target.clone(),
+        status: TransferStatus::Failed,
+        server_name: server_name.to_string(),
+        error: None,
+    };
+
+    let mut config = match read_agent_config_or_empty(target.clone()) {
+        Ok(config) => config,
+        Err(e) => {
+            result.error = Some(e);
+            return result;
+        }
+    };
+
+    let (name, status) = if !config.servers.contains_key(server_name) {
+        (server_name.to_string(), TransferStatus::Added)
+    } else {
+        match strategy {
+            ConflictStrategy::Skip => {
+                result.status = TransferStatus::Skipped;
+                return result;
+            }
+            ConflictStrategy::Overwrite => (server_name.to_string(), TransferStatus::Overwritten),
+            ConflictStrategy::Rename => {
+                (next_free_name(&config, server_name), TransferStatus::Renamed)
+            }
+        }
+    };
+
+    config.servers.insert(name.clone(), entry.clone());
+    match save_agent_config(target, config) {
+        Ok(()) => {
+            result.status = status;
+            result.server_name = name;
+        }
+        Err(e) => result.error = Some(e),
+    }
+    result
+}
+
+/// First `name-N` (N >= 2) not used in `config`
+pub fn next_free_name(config: &AgentServers, name: &str) -> String {
+    (2..)
+        .map(|n| format!("{}-{}", name, n))
+        .find(|candidate| !config.servers.contains_key(candidate))
+        .unwrap_or_else(|| name.to_string())
+}
@@ -3,7 +3,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use super::AgentServerEntry;
+use super::{AgentServerEntry, AgentType};
 
 /// User-configured MCP server instance
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -41,6 +41,11 @@ pub struct ServerOrigin {
     /// Package identifier: "{registry_type}:{identifier}" (e.g., "npm:@jina-ai/mcp-server")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub package_id: Option<String>,
+
+    /// Registry version the server is pinned to, i.e. the one its config was generated
+    /// from; unset falls back to the latest version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
 }
 
 /// Origin type for a user server
@@ -52,3 +57,18 @@ pub enum OriginType {
     /// Server manually configured by user
     Custom,
 }
+
+/// A copy of a user server written into an agent config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerDeployment {
+    pub agent_type: AgentType,
+
+    /// Name of the server in the agent config
+    pub server_name: String,
+
+    /// Config as written at the last deploy, used to detect hand edits
+    pub deployed_config: AgentServerEntry,
+
+    pub deployed_at: String,
+}
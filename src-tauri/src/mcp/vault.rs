@@ -0,0 +1,493 @@
+//! Passphrase-protected secret vault
+//!
+//! Secrets live in `vault.json`, encrypted with AES-256-GCM under a key derived from the
+//! passphrase with PBKDF2-HMAC-SHA256. The derived key stays in memory while the vault
+//! is unlocked. User server env values and headers reference secrets as
+//! `${secret:NAME}`; references are resolved only when a config is written to an agent.
+//! Agents started from a shell that support interpolation get their own env reference
+//! syntax instead, with the values exported to the managed `secrets.env` file.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use once_cell::sync::Lazy;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::config::{get_secrets_env_path, get_vault_path, load_app_config};
+
+use super::agent_definition::get_agent_definition;
+use super::secret_scan::{export_managed_env, load_managed_env};
+use super::{AgentServerEntry, AgentType};
+
+const VAULT_VERSION: u32 = 1;
+const KDF: &str = "pbkdf2-sha256";
+const CIPHER: &str = "aes-256-gcm";
+const VAULT_AAD: &[u8] = b"rainmcp-vault-v1";
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+#[cfg(not(test))]
+const PBKDF2_ITERATIONS: u32 = 600_000;
+// The iteration count is stored per vault, so tests can use a cheaper one
+#[cfg(test)]
+const PBKDF2_ITERATIONS: u32 = 1_000;
+
+pub const SECRET_REF_PREFIX: &str = "${secret:";
+
+/// On-disk vault format; `ciphertext` decrypts to a JSON object of name → value
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VaultFile {
+    version: u32,
+    kdf: String,
+    iterations: u32,
+    salt: String,
+    cipher: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultStatus {
+    pub exists: bool,
+    pub unlocked: bool,
+
+    /// Names of stored secrets, only listed while unlocked
+    pub secret_names: Vec<String>,
+}
+
+struct UnlockedVault {
+    path: PathBuf,
+    key: Zeroizing<[u8; KEY_LEN]>,
+    salt: Vec<u8>,
+    iterations: u32,
+}
+
+type Secrets = BTreeMap<String, String>;
+
+static UNLOCKED: Lazy<Mutex<Option<UnlockedVault>>> = Lazy::new(|| Mutex::new(None));
+
+fn vault_path() -> Result<PathBuf, String> {
+    get_vault_path().ok_or_else(|| "Could not determine vault path".to_string())
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    iterations: u32,
+) -> Result<Zeroizing<[u8; KEY_LEN]>, String> {
+    let iterations = NonZeroU32::new(iterations).ok_or("Invalid vault iteration count")?;
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, iterations, salt, passphrase.as_bytes(), &mut *key);
+    Ok(key)
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![0u8; len];
+    SystemRandom::new().fill(&mut bytes).map_err(|_| "Failed to generate random bytes")?;
+    Ok(bytes)
+}
+
+fn cipher_key(key: &[u8]) -> Result<LessSafeKey, String> {
+    UnboundKey::new(&AES_256_GCM, key)
+        .map(LessSafeKey::new)
+        .map_err(|_| "Invalid vault key".to_string())
+}
+
+fn read_vault_file(path: &Path) -> Result<VaultFile, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read vault: {}", e))?;
+    let file: VaultFile =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid vault file: {}", e))?;
+    if file.version != VAULT_VERSION || file.kdf != KDF || file.cipher != CIPHER {
+        return Err(format!("Unsupported vault format (version {})", file.version));
+    }
+    Ok(file)
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, String> {
+    STANDARD.decode(value).map_err(|e| format!("Invalid vault file: {}", e))
+}
+
+fn decrypt(file: &VaultFile, key: &[u8]) -> Result<Secrets, String> {
+    let nonce = Nonce::try_assume_unique_for_key(&decode(&file.nonce)?)
+        .map_err(|_| "Invalid vault file: bad nonce")?;
+    let mut data = Zeroizing::new(decode(&file.ciphertext)?);
+    let plaintext = cipher_key(key)?
+        .open_in_place(nonce, Aad::from(VAULT_AAD), &mut data)
+        .map_err(|_| "Incorrect passphrase or corrupted vault")?;
+    serde_json::from_slice(plaintext).map_err(|e| format!("Invalid vault content: {}", e))
+}
+
+fn encrypt_and_write(vault: &UnlockedVault, secrets: &Secrets) -> Result<(), String> {
+    let nonce = random_bytes(NONCE_LEN)?;
+    let mut data = Zeroizing::new(serde_json::to_vec(secrets).map_err(|e| e.to_string())?);
+    cipher_key(vault.key.as_ref())?
+        .seal_in_place_append_tag(
+            Nonce::try_assume_unique_for_key(&nonce).map_err(|_| "Invalid nonce")?,
+            Aad::from(VAULT_AAD),
+            &mut *data,
+        )
+        .map_err(|_| "Failed to encrypt vault")?;
+
+    let file = VaultFile {
+        version: VAULT_VERSION,
+        kdf: KDF.to_string(),
+        iterations: vault.iterations,
+        salt: STANDARD.encode(&vault.salt),
+        cipher: CIPHER.to_string(),
+        nonce: STANDARD.encode(&nonce),
+        ciphertext: STANDARD.encode(&*data),
+    };
+    let content = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    write_private_file(&vault.path, &content)
+}
+
+/// Write a file readable only by the current user
+pub fn write_private_file(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).map_err(|e| e.to_string())?;
+
+    // The mode only applies to new files; tighten an existing one before writing to it
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600)).map_err(|e| e.to_string())?;
+    }
+    file.write_all(content.as_bytes()).map_err(|e| e.to_string())
+}
+
+/// Run `f` with the unlocked vault and its decrypted secrets
+fn with_unlocked<T>(
+    f: impl FnOnce(&UnlockedVault, Secrets) -> Result<T, String>,
+) -> Result<T, String> {
+    let path = vault_path()?;
+    let guard = UNLOCKED.lock().map_err(|e| e.to_string())?;
+    let vault = guard
+        .as_ref()
+        .filter(|v| v.path == path && path.exists())
+        .ok_or("Secret vault is locked")?;
+
+    let secrets = decrypt(&read_vault_file(&path)?, vault.key.as_ref())?;
+    f(vault, secrets)
+}
+
+pub fn vault_status() -> VaultStatus {
+    let exists = vault_path().is_ok_and(|p| p.exists());
+    let names = with_unlocked(|_, secrets| Ok(secrets.into_keys().collect()));
+    VaultStatus { exists, unlocked: names.is_ok(), secret_names: names.unwrap_or_default() }
+}
+
+/// Create an empty vault protected by `passphrase`; the new vault is left unlocked
+pub fn create_vault(passphrase: &str) -> Result<VaultStatus, String> {
+    let path = vault_path()?;
+    if path.exists() {
+        return Err("Secret vault already exists".to_string());
+    }
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!("Passphrase must be at least {} characters", MIN_PASSPHRASE_LEN));
+    }
+
+    let salt = random_bytes(SALT_LEN)?;
+    let vault = UnlockedVault {
+        key: derive_key(passphrase, &salt, PBKDF2_ITERATIONS)?,
+        path,
+        salt,
+        iterations: PBKDF2_ITERATIONS,
+    };
+    encrypt_and_write(&vault, &Secrets::new())?;
+    *UNLOCKED.lock().map_err(|e| e.to_string())? = Some(vault);
+
+    Ok(vault_status())
+}
+
+pub fn unlock_vault(passphrase: &str) -> Result<VaultStatus, String> {
+    let path = vault_path()?;
+    if !path.exists() {
+        return Err("Secret vault does not exist".to_string());
+    }
+
+    let file = read_vault_file(&path)?;
+    let salt = decode(&file.salt)?;
+    let key = derive_key(passphrase, &salt, file.iterations)?;
+    decrypt(&file, key.as_ref())?;
+
+    *UNLOCKED.lock().map_err(|e| e.to_string())? =
+        Some(UnlockedVault { path, key, salt, iterations: file.iterations });
+    Ok(vault_status())
+}
+
+/// Forget the derived key
+pub fn lock_vault() {
+    if let Ok(mut guard) = UNLOCKED.lock() {
+        *guard = None;
+    }
+}
+
+/// Secret names double as env var names when written as env references
+fn validate_secret_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid secret name '{}': use letters, digits and underscores", name))
+    }
+}
+
+/// Add or replace a secret
+pub fn set_secret(name: &str, value: &str) -> Result<(), String> {
+    validate_secret_name(name)?;
+    with_unlocked(|vault, mut secrets| {
+        secrets.insert(name.to_string(), value.to_string());
+        encrypt_and_write(vault, &secrets)
+    })?;
+
+    // Keep an exported copy current
+    if load_managed_env(&secrets_env_path()?)?.contains_key(name) {
+        export_managed_env(&BTreeMap::from([(name.to_string(), value.to_string())]))?;
+    }
+    Ok(())
+}
+
+pub fn delete_secret(name: &str) -> Result<(), String> {
+    with_unlocked(|vault, mut secrets| {
+        secrets.remove(name).ok_or_else(|| format!("Secret '{}' not found in vault", name))?;
+        encrypt_and_write(vault, &secrets)
+    })
+}
+
+/// Names referenced as `${secret:NAME}` in `value`
+pub fn secret_references(value: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let _ = replace_references(value, |name| {
+        names.push(name.to_string());
+        Ok(String::new())
+    });
+    names
+}
+
+fn replace_references(
+    value: &str,
+    mut replacement: impl FnMut(&str) -> Result<String, String>,
+) -> Result<String, String> {
+    let mut output = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find(SECRET_REF_PREFIX) {
+        let after = &rest[start + SECRET_REF_PREFIX.len()..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        output.push_str(&replacement(&after[..end])?);
+        rest = &after[end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// `value` with `${secret:NAME}` references replaced by values from the unlocked vault
+pub fn resolve_secret_references(value: &str) -> Result<String, String> {
+    if !value.contains(SECRET_REF_PREFIX) {
+        return Ok(value.to_string());
+    }
+    let secrets = with_unlocked(|_, secrets| Ok(secrets))?;
+    replace_references(value, |name| {
+        secrets.get(name).cloned().ok_or_else(|| format!("Secret '{}' not found in vault", name))
+    })
+}
+
+/// Env values of a local server or header values of a remote one
+fn secret_fields_mut(config: &mut AgentServerEntry) -> Vec<&mut String> {
+    let values = match config {
+        AgentServerEntry::Local(local) => local.env.as_mut(),
+        AgentServerEntry::Remote(remote) => remote.headers.as_mut(),
+    };
+    values.into_iter().flat_map(|map| map.values_mut()).collect()
+}
+
+pub fn has_secret_references(config: &AgentServerEntry) -> bool {
+    let mut config = config.clone();
+    secret_fields_mut(&mut config).iter().any(|value| value.contains(SECRET_REF_PREFIX))
+}
+
+/// `config` as written to `agent`
+///
+/// For agents started from a shell that support interpolation, `${secret:NAME}` references
+/// become the agent's env reference syntax and the values are exported to the managed
+/// `secrets.env` file (see [`secret_exports`]). Other agents never read that file, so they
+/// get the values from the unlocked vault inline.
+pub fn config_for_agent(
+    config: &AgentServerEntry,
+    agent: &AgentType,
+) -> Result<AgentServerEntry, String> {
+    render_for_agent(config, agent, true)
+}
+
+/// `config` as [`config_for_agent`] writes it, without exporting any values
+pub fn agent_form(
+    config: &AgentServerEntry,
+    agent: &AgentType,
+) -> Result<AgentServerEntry, String> {
+    render_for_agent(config, agent, false)
+}
+
+/// `config` as [`config_for_agent`] writes it, for sharing: agents started from a shell
+/// get the same env references, others a `{{NAME}}` placeholder instead of the value
+///
+/// Needs no unlocked vault and never includes secret values.
+pub fn shareable_form(
+    config: &AgentServerEntry,
+    agent: &AgentType,
+) -> Result<AgentServerEntry, String> {
+    if !has_secret_references(config) {
+        return Ok(config.clone());
+    }
+
+    let definition = get_agent_definition(agent)?;
+    let template = definition.shell_env_reference();
+    let mut config = config.clone();
+    for value in secret_fields_mut(&mut config) {
+        *value = replace_references(value, |name| {
+            Ok(match template {
+                Some(template) => template.replace("VAR", name),
+                None => format!("{{{{{}}}}}", name),
+            })
+        })?;
+    }
+    Ok(config)
+}
+
+fn render_for_agent(
+    config: &AgentServerEntry,
+    agent: &AgentType,
+    export: bool,
+) -> Result<AgentServerEntry, String> {
+    if !has_secret_references(config) {
+        return Ok(config.clone());
+    }
+
+    let definition = get_agent_definition(agent)?;
+    let template = definition.shell_env_reference();
+    let secrets = with_unlocked(|_, secrets| Ok(secrets));
+    let lookup = |name: &str| match &secrets {
+        Ok(secrets) => secrets
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Secret '{}' not found in vault", name)),
+        Err(e) => Err(e.clone()),
+    };
+    // Values exported earlier still reach the agent while the vault is locked
+    let exported = match template {
+        Some(_) => load_managed_env(&secrets_env_path()?)?,
+        None => BTreeMap::new(),
+    };
+
+    let mut exports = BTreeMap::new();
+    let mut config = config.clone();
+    for value in secret_fields_mut(&mut config) {
+        *value = replace_references(value, |name| {
+            let Some(template) = template else {
+                return lookup(name)
+                    .map_err(|e| format!("Cannot resolve secrets for {}: {}", agent, e));
+            };
+            match lookup(name) {
+                Ok(secret) => {
+                    exports.insert(name.to_string(), secret);
+                }
+                Err(e) if !exported.contains_key(name) => {
+                    return Err(format!("Cannot export secrets for {}: {}", agent, e));
+                }
+                Err(_) => {}
+            }
+            Ok(template.replace("VAR", name))
+        })?;
+    }
+
+    if export && !exports.is_empty() {
+        export_managed_env(&exports)?;
+    }
+    Ok(config)
+}
+
+fn secrets_env_path() -> Result<PathBuf, String> {
+    get_secrets_env_path().ok_or_else(|| "Could not determine secrets env path".to_string())
+}
+
+/// Vault secrets an agent reads from the managed env file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentEnvRequirement {
+    pub agent_type: AgentType,
+    pub variables: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretExports {
+    /// The managed env file; it must be sourced by the shell these agents start from
+    pub env_file: PathBuf,
+    pub agents: Vec<AgentEnvRequirement>,
+}
+
+/// Deployed agents that reference vault secrets as env vars, with the variables each needs
+pub fn secret_exports() -> Result<SecretExports, String> {
+    let app_config = load_app_config();
+    let mut agents: Vec<AgentEnvRequirement> = Vec::new();
+    for server in &app_config.user_servers {
+        let mut config = server.config.clone();
+        let mut names: Vec<String> = secret_fields_mut(&mut config)
+            .into_iter()
+            .flat_map(|value| secret_references(value))
+            .collect();
+        if names.is_empty() {
+            continue;
+        }
+        names.sort();
+        names.dedup();
+
+        for deployment in app_config.server_deployments.get(&server.id).into_iter().flatten() {
+            let agent = &deployment.agent_type;
+            if !get_agent_definition(agent).is_ok_and(|d| d.shell_env_reference().is_some()) {
+                continue;
+            }
+            let variables = match agents.iter_mut().find(|a| a.agent_type == *agent) {
+                Some(existing) => &mut existing.variables,
+                None => {
+                    agents.push(AgentEnvRequirement {
+                        agent_type: agent.clone(),
+                        variables: Vec::new(),
+                    });
+                    &mut agents.last_mut().expect("just pushed").variables
+                }
+            };
+            variables.extend(names.iter().cloned());
+            variables.sort();
+            variables.dedup();
+        }
+    }
+
+    Ok(SecretExports { env_file: secrets_env_path()?, agents })
+}
@@ -0,0 +1,335 @@
+//! File watcher for agent config files and `settings.json`
+//!
+//! Agents such as Claude Code or Cursor may rewrite their own config while rainMcp
+//! is open. The watcher observes the config files of all enabled agents (plus the
+//! app config), re-parses changed files through the adapters and emits
+//! `agent-config-changed` events so the UI never works on stale data.
+//!
+//! Config directories that do not exist yet (an agent installed after rainMcp started)
+//! are covered by watching their nearest existing ancestor; once the directory appears
+//! the watch moves down to it and the new config is reported like any other change.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use log::{debug, error, info, warn};
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use once_cell::sync::Lazy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::config::{get_app_config_path, load_app_config};
+
+use super::agent::get_global_config_path;
+use super::agent_config::read_agent_config;
+use super::redact::{redact_config, RegistrySecrets};
+use super::{get_enabled_agents, AgentServerEntry, AgentServers, AgentType};
+
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(300);
+
+pub const AGENT_CONFIG_CHANGED_EVENT: &str = "agent-config-changed";
+pub const APP_CONFIG_CHANGED_EVENT: &str = "app-config-changed";
+
+/// Server-level difference between two parsed agent configs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerChanges {
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl ServerChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Payload of the `agent-config-changed` event
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentConfigChangedEvent {
+    pub agent_type: AgentType,
+
+    /// Names of added, modified and removed servers
+    pub changes: ServerChanges,
+
+    /// Current definitions of added and modified servers, with secrets masked
+    pub servers: HashMap<String, AgentServerEntry>,
+
+    /// Set when the changed file could not be parsed (e.g. mid-edit or deleted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct WatchState {
+    /// Normalized config file path -> agent
+    targets: HashMap<PathBuf, AgentType>,
+    /// Directories currently registered with the watcher
+    watched_dirs: HashSet<PathBuf>,
+    /// Config directories that do not exist yet, below a watched ancestor
+    missing_dirs: Vec<PathBuf>,
+    /// Last parsed config per agent, used to compute changes
+    snapshots: HashMap<AgentType, AgentServers>,
+}
+
+static WATCHER: Lazy<Mutex<Option<Debouncer<RecommendedWatcher>>>> = Lazy::new(|| Mutex::new(None));
+static WATCH_STATE: Lazy<Mutex<WatchState>> = Lazy::new(|| Mutex::new(WatchState::default()));
+
+/// Compare two parsed configs server by server
+pub fn diff_servers(old: &AgentServers, new: &AgentServers) -> ServerChanges {
+    let mut changes = ServerChanges::default();
+
+    for (name, entry) in &new.servers {
+        match old.servers.get(name) {
+            None => changes.added.push(name.clone()),
+            Some(previous) if previous != entry => changes.modified.push(name.clone()),
+            Some(_) => {}
+        }
+    }
+    for name in old.servers.keys() {
+        if !new.servers.contains_key(name) {
+            changes.removed.push(name.clone());
+        }
+    }
+
+    changes.added.sort();
+    changes.modified.sort();
+    changes.removed.sort();
+    changes
+}
+
+/// Config files to watch: every enabled agent's global config path
+pub fn collect_watch_targets() -> Vec<(PathBuf, AgentType)> {
+    get_enabled_agents(&load_app_config())
+        .into_iter()
+        .filter_map(|agent| match get_global_config_path(&agent) {
+            Ok(path) => Some((path, agent)),
+            Err(e) => {
+                warn!("Skipping watch for {}: {}", agent, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Resolve symlinks in the parent directory so event paths and targets compare equal
+fn normalize_path(path: &Path) -> PathBuf {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => {
+            parent.canonicalize().unwrap_or_else(|_| parent.to_path_buf()).join(name)
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Nearest existing directory at or above `dir`, with symlinks resolved
+pub fn nearest_existing_dir(dir: &Path) -> Option<PathBuf> {
+    let existing = dir.ancestors().find(|ancestor| ancestor.is_dir())?;
+    Some(existing.canonicalize().unwrap_or_else(|_| existing.to_path_buf()))
+}
+
+/// Start watching config files, emitting change events through `app`
+pub fn start_config_watcher(app: AppHandle) -> Result<(), String> {
+    let handler_app = app.clone();
+    let debouncer =
+        new_debouncer(DEBOUNCE_TIMEOUT, move |result: DebounceEventResult| match result {
+            Ok(events) => {
+                let paths: HashSet<PathBuf> = events.into_iter().map(|e| e.path).collect();
+                handle_changed_paths(&handler_app, paths);
+            }
+            Err(e) => error!("Config watcher error: {}", e),
+        })
+        .map_err(|e| format!("Failed to create config watcher: {}", e))?;
+
+    *WATCHER.lock().map_err(|e| e.to_string())? = Some(debouncer);
+    sync_watch_targets()?;
+
+    info!("Config watcher started");
+    Ok(())
+}
+
+/// Re-register watched directories after the enabled agents changed
+pub fn sync_watch_targets() -> Result<(), String> {
+    sync_targets().map(|_| ())
+}
+
+/// Re-register watched directories, returning agents whose config is newly watched
+fn sync_targets() -> Result<Vec<AgentType>, String> {
+    let mut watcher_guard = WATCHER.lock().map_err(|e| e.to_string())?;
+    let Some(debouncer) = watcher_guard.as_mut() else {
+        return Ok(Vec::new());
+    };
+    let mut state = WATCH_STATE.lock().map_err(|e| e.to_string())?;
+
+    let mut files: Vec<(PathBuf, Option<AgentType>)> =
+        collect_watch_targets().into_iter().map(|(path, agent)| (path, Some(agent))).collect();
+    if let Some(settings_path) = get_app_config_path() {
+        files.push((settings_path, None));
+    }
+
+    let mut targets = HashMap::new();
+    let mut dirs = HashSet::new();
+    let mut missing_dirs = Vec::new();
+    for (path, agent) in files {
+        let Some(parent) = path.parent() else { continue };
+        if !parent.is_dir() {
+            let Some(ancestor) = nearest_existing_dir(parent) else { continue };
+            debug!("Config directory {:?} does not exist yet, watching {:?}", parent, ancestor);
+            let existing = parent.ancestors().find(|a| a.is_dir()).unwrap_or(parent);
+            if let Ok(rest) = parent.strip_prefix(existing) {
+                missing_dirs.push(ancestor.join(rest));
+            }
+            dirs.insert(ancestor);
+            continue;
+        }
+        dirs.insert(parent.canonicalize().unwrap_or_else(|_| parent.to_path_buf()));
+        if let Some(agent) = agent {
+            targets.insert(normalize_path(&path), agent);
+        }
+    }
+
+    for dir in state.watched_dirs.difference(&dirs) {
+        if let Err(e) = debouncer.watcher().unwatch(dir) {
+            debug!("Failed to unwatch {:?}: {}", dir, e);
+        }
+    }
+    for dir in dirs.difference(&state.watched_dirs) {
+        if let Err(e) = debouncer.watcher().watch(dir, RecursiveMode::NonRecursive) {
+            warn!("Failed to watch {:?}: {}", dir, e);
+        }
+    }
+
+    let newly_watched: Vec<AgentType> = targets
+        .values()
+        .filter(|agent| !state.targets.values().any(|watched| watched == *agent))
+        .cloned()
+        .collect();
+
+    let mut snapshots = HashMap::new();
+    for agent in targets.values() {
+        let snapshot = match state.snapshots.remove(agent) {
+            Some(existing) => Some(existing),
+            None => read_agent_config(agent.clone()).ok(),
+        };
+        if let Some(snapshot) = snapshot {
+            snapshots.insert(agent.clone(), snapshot);
+        }
+    }
+
+    state.targets = targets;
+    state.watched_dirs = dirs;
+    state.missing_dirs = missing_dirs;
+    state.snapshots = snapshots;
+    Ok(newly_watched)
+}
+
+/// Whether `path` is, or leads to, a config directory that did not exist yet
+fn creates_missing_dir(path: &Path) -> bool {
+    match WATCH_STATE.lock() {
+        Ok(state) => state.missing_dirs.iter().any(|missing| missing.starts_with(path)),
+        Err(_) => false,
+    }
+}
+
+/// Start watching directories that appeared and report the configs found in them
+fn watch_new_dirs(app: &AppHandle) {
+    let agents = match sync_targets() {
+        Ok(agents) => agents,
+        Err(e) => {
+            error!("Failed to update watched config files: {}", e);
+            return;
+        }
+    };
+    for agent in agents {
+        // The whole config is new to the UI, so diff it against an empty snapshot
+        if let Ok(mut state) = WATCH_STATE.lock() {
+            state.snapshots.remove(&agent);
+        }
+        if let Some(event) = reload_agent(agent) {
+            let _ = app.emit(AGENT_CONFIG_CHANGED_EVENT, event);
+        }
+    }
+}
+
+fn handle_changed_paths(app: &AppHandle, paths: HashSet<PathBuf>) {
+    let settings_path = get_app_config_path().map(|p| normalize_path(&p));
+
+    for path in paths {
+        let path = normalize_path(&path);
+
+        if settings_path.as_ref() == Some(&path) {
+            debug!("App config changed on disk");
+            if let Err(e) = sync_watch_targets() {
+                error!("Failed to update watched config files: {}", e);
+            }
+            let _ = app.emit(APP_CONFIG_CHANGED_EVENT, ());
+            continue;
+        }
+
+        if creates_missing_dir(&path) {
+            debug!("Config directory {:?} appeared", path);
+            watch_new_dirs(app);
+            continue;
+        }
+
+        let agent = match WATCH_STATE.lock() {
+            Ok(state) => state.targets.get(&path).cloned(),
+            Err(_) => None,
+        };
+        if let Some(agent) = agent {
+            if let Some(event) = reload_agent(agent) {
+                let _ = app.emit(AGENT_CONFIG_CHANGED_EVENT, event);
+            }
+        }
+    }
+}
+
+/// Re-parse an agent config and build a change event (None when nothing changed)
+pub fn reload_agent(agent: AgentType) -> Option<AgentConfigChangedEvent> {
+    let mut state = WATCH_STATE.lock().ok()?;
+
+    match read_agent_config(agent.clone()) {
+        Ok(current) => {
+            let previous = state
+                .snapshots
+                .get(&agent)
+                .cloned()
+                .unwrap_or(AgentServers { servers: HashMap::new() });
+            let changes = diff_servers(&previous, &current);
+            if changes.is_empty() {
+                return None;
+            }
+
+            debug!("{} config changed on disk: {:?}", agent, changes);
+            // Events reach the webview, so secrets are masked as in every other view
+            let registry_secrets = RegistrySecrets::load();
+            let servers = changes
+                .added
+                .iter()
+                .chain(changes.modified.iter())
+                .filter_map(|name| {
+                    let entry = current.servers.get(name)?;
+                    Some((name.clone(), redact_config(entry, registry_secrets.for_server(name))))
+                })
+                .collect();
+            state.snapshots.insert(agent.clone(), current);
+
+            Some(AgentConfigChangedEvent { agent_type: agent, changes, servers, error: None })
+        }
+        Err(e) => {
+            warn!("Failed to reload {} config: {}", agent, e);
+            Some(AgentConfigChangedEvent {
+                agent_type: agent,
+                changes: ServerChanges::default(),
+                servers: HashMap::new(),
+                error: Some(e),
+            })
+        }
+    }
+}
@@ -1,8 +1,10 @@
 import { invoke } from '@tauri-apps/api/core';
 import type {
+  AgentServerEntry,
   AgentType,
   AppConfig,
   AgentServers,
+  Package,
   SchemaStore,
   SupportedAgent,
   UserServer,
@@ -58,6 +60,13 @@ export async function refreshSchemaStore(): Promise<SchemaStore> {
   return invoke('refresh_schema_store_command');
 }
 
+export async function getPackageConfig(
+  pkg: Package,
+  values: Record<string, string>
+): Promise<AgentServerEntry> {
+  return invoke('package_config_command', { package: pkg, values });
+}
+
 // ===== User Server APIs =====
 
 export async function getUserServers(): Promise<UserServer[]> {
@@ -77,6 +77,9 @@ export const useServersStore = defineStore('servers', () => {
         }
       );
       schemaStore.value = await api.refreshSchemaStore();
+      for (const [source, error] of Object.entries(schemaStore.value.source_errors ?? {})) {
+        logger.warn(`Kept cached servers of ${source}:`, error);
+      }
     } catch (error) {
       logger.error('Failed to refresh schema store:', error);
       throw error;
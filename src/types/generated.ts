@@ -21,23 +21,28 @@ export type AgentServerEntry =
     };
 /**
  * Supported AI coding agent types
+ *
+ * Built-in agents have their own variant; agents described only by a user definition file are represented as `Custom` with the definition id.
  */
 export type AgentType =
-  | "ClaudeCode"
-  | "Cursor"
-  | "Windsurf"
-  | "Cline"
-  | "ClaudeDesktop"
-  | "RooCode"
-  | "Trae"
-  | "GeminiCli"
-  | "Kiro"
-  | "OpenAiCodex"
-  | "Comate"
-  | "VsCodeCopilot"
-  | "CopilotCli"
-  | "Alma"
-  | "OpenCode";
+  | (
+      | "ClaudeCode"
+      | "Cursor"
+      | "Windsurf"
+      | "Cline"
+      | "ClaudeDesktop"
+      | "RooCode"
+      | "Trae"
+      | "GeminiCli"
+      | "Kiro"
+      | "OpenAiCodex"
+      | "Comate"
+      | "VsCodeCopilot"
+      | "CopilotCli"
+      | "Alma"
+      | "OpenCode"
+    )
+  | string;
 /**
  * Local transport configuration
  */
@@ -88,10 +93,32 @@ export interface AgentServers {
  * Schema store containing all cached server schemas
  */
 export interface SchemaStore {
+  /**
+   * Superseded versions of each server, newest first
+   */
+  history?: {
+    [k: string]: ServerSchema[];
+  };
   /**
    * List of server schemas
    */
   servers: ServerSchema[];
+  /**
+   * Per source URL, why the last refresh of that source failed; its servers are the ones cached before
+   */
+  source_errors?: {
+    [k: string]: string;
+  };
+  /**
+   * URLs of the registry sources the store was built from, in precedence order
+   */
+  sources?: string[];
+  /**
+   * Per source URL, the newest `updatedAt` the registry reported; the next refresh asks for entries updated since then
+   */
+  synced_at?: {
+    [k: string]: string;
+  };
   /**
    * Timestamp of last update (ISO 8601 format)
    */
@@ -118,6 +145,14 @@ export interface ServerSchema {
    * Package configurations for local installation
    */
   packages?: Package[];
+  /**
+   * URL of the registry source the schema was fetched from (set by rainMcp)
+   */
+  registrySource?: string | null;
+  /**
+   * Registry status of this version when it is not active, e.g. `deprecated` (set by rainMcp)
+   */
+  registryStatus?: string | null;
   /**
    * Remote transport configurations
    */
@@ -374,4 +409,8 @@ export interface ServerOrigin {
    * Registry schema name (e.g., "io.jina/mcp-jina")
    */
   schemaName?: string | null;
+  /**
+   * Registry version the server is pinned to, i.e. the one its config was generated from; unset falls back to the latest version
+   */
+  version?: string | null;
 }
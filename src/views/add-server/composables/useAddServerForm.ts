@@ -11,7 +11,7 @@ import type {
 import { useServersStore } from '@/stores/servers';
 import { logger } from '@/utils/logger';
 import type { ParsedServer } from './useClipboardParser';
-import type { GeneratedSchema } from '@/api/tauri';
+import { getPackageConfig, type GeneratedSchema } from '@/api/tauri';
 
 const PAGE_SIZE = 50;
 
@@ -250,64 +250,6 @@ export function useAddServerForm() {
     return result;
   }
 
-  function convertPackageToConfig(
-    pkg: PackageType,
-    userEnvValues: Record<string, string>
-  ): AgentServerEntry {
-    let command: string;
-    let args: string[];
-
-    const runtimeHint = pkg.runtimeHint;
-    const registryType = pkg.registryType;
-
-    if (runtimeHint === 'npx' || (!runtimeHint && registryType === 'npm')) {
-      command = 'npx';
-      args = ['-y'];
-    } else if (runtimeHint === 'uvx' || (!runtimeHint && registryType === 'pypi')) {
-      command = 'uvx';
-      args = [];
-    } else if (runtimeHint === 'docker' || (!runtimeHint && registryType === 'oci')) {
-      command = 'docker';
-      args = ['run', '-i'];
-    } else if (runtimeHint === 'dnx') {
-      command = 'dnx';
-      args = [];
-    } else {
-      command = 'npx';
-      args = ['-y'];
-    }
-
-    const identifier = pkg.version ? `${pkg.identifier}@${pkg.version}` : pkg.identifier;
-    args.push(identifier);
-
-    if (pkg.packageArguments) {
-      for (const arg of pkg.packageArguments) {
-        if (arg.type === 'named' && arg.name) {
-          args.push(arg.name);
-        }
-        if (arg.value) {
-          args.push(arg.value);
-        }
-      }
-    }
-
-    if (pkg.transport.type === 'sse' || pkg.transport.type === 'streamable-http') {
-      const transportWithUrl = pkg.transport as { url: string };
-      return {
-        type: 'remote',
-        url: transportWithUrl.url,
-        headers: Object.keys(userEnvValues).length > 0 ? userEnvValues : null,
-      };
-    }
-
-    return {
-      type: 'local',
-      command,
-      args: args.length > 0 ? args : null,
-      env: Object.keys(userEnvValues).length > 0 ? userEnvValues : null,
-    };
-  }
-
   function convertRemoteToConfig(
     remote: RemoteTransport,
     userEnvValues: Record<string, string>
@@ -328,7 +270,7 @@ export function useAddServerForm() {
       let config: AgentServerEntry;
 
       if (selectedPackage.value) {
-        config = convertPackageToConfig(selectedPackage.value, envValues.value);
+        config = await getPackageConfig(selectedPackage.value, envValues.value);
       } else if (selectedRemote.value) {
         config = convertRemoteToConfig(selectedRemote.value, envValues.value);
       } else {
@@ -341,6 +283,7 @@ export function useAddServerForm() {
         origin: {
           originType: 'registry',
           schemaName: selectedSchema.value.name,
+          version: selectedSchema.value.version,
         },
       });
 